@@ -0,0 +1,289 @@
+//! Benchmarks of the witness generation, key generation, proving and
+//! verification of the circuits of `cost::examples::visit_benchmarks`, and
+//! of the throughput of `BatchProver` on the batches of
+//! `cost::examples::visit_batch_benchmarks`.
+//!
+//! ```text
+//! cargo bench --bench proving
+//! cargo bench --bench proving -- mimc_hash
+//! ```
+//!
+//! The results of the run are printed as markdown tables at the end.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use std::{env, fs};
+
+use criterion::{Criterion, Throughput};
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, SingleVerifier},
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use halo2lg_experiments::{
+    cost::{
+        examples::{visit_batch_benchmarks, visit_benchmarks, BatchVisitor, ExampleVisitor},
+        rows::{minimal_k, witness},
+    },
+    proving::prover::{BatchProver, ProofRng},
+    soundness::mutation::witness_instances,
+};
+use rand_core::OsRng;
+
+/// The steps measured for each circuit, in the order of the table columns.
+const STEPS: [&str; 5] = [
+    "witness",
+    "keygen_vk",
+    "keygen_pk",
+    "create_proof",
+    "verify_proof",
+];
+
+/// The step measured for each batch, with the size of the batch.
+const BATCH_STEP: &str = "prove_batch";
+
+/// Registers the benchmarks of each step of a circuit in a group named after
+/// the circuit. The witness generation is measured with `rows::witness`,
+/// which synthesizes the circuit with its values but does not check them.
+struct Benchmarks<'a>(&'a mut Criterion);
+
+impl ExampleVisitor for Benchmarks<'_> {
+    fn visit<C, ConcreteCircuit>(&mut self, name: &str, circuit: &ConcreteCircuit)
+    where
+        C: CurveAffine,
+        ConcreteCircuit: Circuit<C::Scalar>,
+    {
+        let k = minimal_k(circuit);
+        let params: Params<C> = Params::new(k);
+        let instances = witness_instances(circuit).unwrap();
+        let instances: Vec<&[C::Scalar]> = instances.iter().map(|column| &column[..]).collect();
+
+        let vk = keygen_vk(&params, circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), circuit).unwrap();
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            std::slice::from_ref(circuit),
+            &[&instances],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+
+        let mut group = self.0.benchmark_group(name);
+        let columns: Vec<Vec<C::Scalar>> = instances.iter().map(|column| column.to_vec()).collect();
+        group.bench_function("witness", |b| {
+            b.iter(|| witness(circuit, &columns).unwrap())
+        });
+        group.bench_function("keygen_vk", |b| {
+            b.iter(|| keygen_vk(&params, circuit).unwrap())
+        });
+        group.bench_function("keygen_pk", |b| {
+            b.iter(|| keygen_pk(&params, vk.clone(), circuit).unwrap())
+        });
+        group.bench_function("create_proof", |b| {
+            b.iter(|| {
+                let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+                create_proof(
+                    &params,
+                    &pk,
+                    std::slice::from_ref(circuit),
+                    &[&instances],
+                    OsRng,
+                    &mut transcript,
+                )
+                .unwrap();
+                transcript.finalize()
+            })
+        });
+        group.bench_function("verify_proof", |b| {
+            b.iter(|| {
+                let strategy = SingleVerifier::new(&params);
+                let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+                verify_proof(
+                    &params,
+                    pk.get_vk(),
+                    strategy,
+                    &[&instances],
+                    &mut transcript,
+                )
+                .unwrap()
+            })
+        });
+        group.finish();
+    }
+}
+
+/// Registers the benchmark of the proofs of a batch with a `BatchProver` with
+/// one thread per CPU, and records the size of the batch.
+struct BatchBenchmarks<'a> {
+    criterion: &'a mut Criterion,
+    sizes: BTreeMap<String, usize>,
+}
+
+impl BatchVisitor for BatchBenchmarks<'_> {
+    fn visit_batch<C, ConcreteCircuit>(
+        &mut self,
+        name: &str,
+        batch: Vec<(ConcreteCircuit, Vec<Vec<C::Scalar>>)>,
+    ) where
+        C: CurveAffine,
+        ConcreteCircuit: Circuit<C::Scalar> + Sync,
+    {
+        let circuit = &batch[0].0;
+        let params: Params<C> = Params::new(minimal_k(circuit));
+        let vk = keygen_vk(&params, circuit).unwrap();
+        let pk = keygen_pk(&params, vk, circuit).unwrap();
+        let prover = BatchProver::new(&params, &pk, 0, ProofRng::Os).unwrap();
+
+        let mut group = self.criterion.benchmark_group(name);
+        group.throughput(Throughput::Elements(batch.len() as u64));
+        group.bench_function(BATCH_STEP, |b| b.iter(|| prover.prove(&batch).unwrap()));
+        group.finish();
+        self.sizes.insert(name.to_string(), batch.len());
+    }
+}
+
+/// The directory where Criterion writes its results.
+fn criterion_home() -> PathBuf {
+    match env::var_os("CRITERION_HOME") {
+        Some(home) => PathBuf::from(home),
+        None => env::var_os("CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("target"))
+            .join("criterion"),
+    }
+}
+
+/// Reads the mean times of the benchmarks run since `start`, by circuit and
+/// step, from the `new` results of Criterion.
+fn read_results(dir: &Path, start: SystemTime) -> BTreeMap<String, BTreeMap<String, Duration>> {
+    let mut results: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.file_name().is_some_and(|name| name != "new") {
+                dirs.push(path);
+                continue;
+            }
+
+            let estimates = path.join("estimates.json");
+            let fresh = fs::metadata(&estimates)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified >= start);
+            if !fresh {
+                continue;
+            }
+            let read = |file: &str| -> serde_json::Value {
+                let json = fs::read_to_string(path.join(file)).unwrap();
+                serde_json::from_str(&json).unwrap()
+            };
+            let benchmark = read("benchmark.json");
+            let mean = read("estimates.json")["mean"]["point_estimate"]
+                .as_f64()
+                .unwrap();
+            if let (Some(group), Some(step)) = (
+                benchmark["group_id"].as_str(),
+                benchmark["function_id"].as_str(),
+            ) {
+                results
+                    .entry(group.to_string())
+                    .or_default()
+                    .insert(step.to_string(), Duration::from_nanos(mean as u64));
+            }
+        }
+    }
+    results
+}
+
+fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs_f64();
+    if seconds >= 1.0 {
+        format!("{:.2} s", seconds)
+    } else if seconds >= 1e-3 {
+        format!("{:.2} ms", seconds * 1e3)
+    } else {
+        format!("{:.2} µs", seconds * 1e6)
+    }
+}
+
+/// Prints the mean times of the run as a markdown table with a row per
+/// circuit and a column per step.
+fn print_table(results: &BTreeMap<String, BTreeMap<String, Duration>>) {
+    println!("\n| circuit | {} |", STEPS.join(" | "));
+    println!("|---------|{}", "---:|".repeat(STEPS.len()));
+    for (circuit, times) in results {
+        if !STEPS.iter().any(|step| times.contains_key(*step)) {
+            continue;
+        }
+        let times: Vec<String> = STEPS
+            .iter()
+            .map(|step| {
+                times
+                    .get(*step)
+                    .map_or("-".to_string(), |time| format_time(*time))
+            })
+            .collect();
+        println!("| {} | {} |", circuit, times.join(" | "));
+    }
+}
+
+/// Prints the mean times of the batches of the run as a markdown table with
+/// the number of proofs per second.
+fn print_batch_table(
+    results: &BTreeMap<String, BTreeMap<String, Duration>>,
+    sizes: &BTreeMap<String, usize>,
+) {
+    println!("\n| batch | proofs | {} | proofs/s |", BATCH_STEP);
+    println!("|-------|---:|---:|---:|");
+    for (batch, size) in sizes {
+        if let Some(time) = results.get(batch).and_then(|times| times.get(BATCH_STEP)) {
+            println!(
+                "| {} | {} | {} | {:.2} |",
+                batch,
+                size,
+                format_time(*time),
+                *size as f64 / time.as_secs_f64()
+            );
+        }
+    }
+}
+
+fn main() {
+    let start = SystemTime::now();
+    // Proving takes long enough that 10 samples in a few seconds are
+    // representative
+    let mut criterion = Criterion::default()
+        .sample_size(10)
+        .warm_up_time(Duration::from_secs(1))
+        .measurement_time(Duration::from_secs(5))
+        .configure_from_args();
+    visit_benchmarks(&mut Benchmarks(&mut criterion));
+    let mut batches = BatchBenchmarks {
+        criterion: &mut criterion,
+        sizes: BTreeMap::new(),
+    };
+    visit_batch_benchmarks(&mut batches);
+    let sizes = batches.sizes;
+    criterion.final_summary();
+
+    let results = read_results(&criterion_home(), start);
+    if results
+        .values()
+        .flat_map(BTreeMap::keys)
+        .any(|step| STEPS.contains(&step.as_str()))
+    {
+        print_table(&results);
+    }
+    if results.values().any(|times| times.contains_key(BATCH_STEP)) {
+        print_batch_table(&results, &sizes);
+    }
+}
@@ -0,0 +1,268 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+use halo2_proofs::{arithmetic::FieldExt, circuit::Layouter, plonk::Error};
+
+use super::standard_plonk::{FieldInstructions, Number};
+
+/// A formula over numbers and constants which can be laid out with a single
+/// call to `eval`, e.g. `k * a^2 * b^2` is built as
+/// `Formula::constant(k) * Formula::num(&a).square() * Formula::num(&b).square()`.
+#[derive(Clone, Debug)]
+pub enum Formula<F: FieldExt> {
+    Num(Number<F>),
+    Constant(F),
+    Add(Box<Formula<F>>, Box<Formula<F>>),
+    Sub(Box<Formula<F>>, Box<Formula<F>>),
+    Mul(Box<Formula<F>>, Box<Formula<F>>),
+    Neg(Box<Formula<F>>),
+    Pow(Box<Formula<F>>, u64),
+}
+
+impl<F: FieldExt> Formula<F> {
+    pub fn num(num: &Number<F>) -> Self {
+        Formula::Num(num.clone())
+    }
+
+    pub fn constant(constant: F) -> Self {
+        Formula::Constant(constant)
+    }
+
+    pub fn square(self) -> Self {
+        self.pow(2)
+    }
+
+    pub fn pow(self, exponent: u64) -> Self {
+        Formula::Pow(Box::new(self), exponent)
+    }
+}
+
+impl<F: FieldExt> Add for Formula<F> {
+    type Output = Formula<F>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Formula::Add(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl<F: FieldExt> Sub for Formula<F> {
+    type Output = Formula<F>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Formula::Sub(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl<F: FieldExt> Mul for Formula<F> {
+    type Output = Formula<F>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Formula::Mul(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl<F: FieldExt> Neg for Formula<F> {
+    type Output = Formula<F>;
+
+    fn neg(self) -> Self::Output {
+        Formula::Neg(Box::new(self))
+    }
+}
+
+/// The result of evaluating a sub-formula. Constant sub-formulas are folded
+/// natively and only enter the circuit as gate coefficients.
+enum Evaluated<F: FieldExt> {
+    Num(Number<F>),
+    Constant(F),
+}
+
+/// Lays out the formula using the instructions of `chip` and returns the
+/// number holding its value.
+pub fn eval<F: FieldExt, C: FieldInstructions<F, Num = Number<F>>>(
+    chip: &C,
+    mut layouter: impl Layouter<F>,
+    formula: &Formula<F>,
+) -> Result<Number<F>, Error> {
+    match eval_inner(chip, &mut layouter, formula)? {
+        Evaluated::Num(num) => Ok(num),
+        Evaluated::Constant(constant) => {
+            chip.load_constant(layouter.namespace(|| "constant formula"), constant)
+        }
+    }
+}
+
+fn eval_inner<F: FieldExt, C: FieldInstructions<F, Num = Number<F>>>(
+    chip: &C,
+    layouter: &mut impl Layouter<F>,
+    formula: &Formula<F>,
+) -> Result<Evaluated<F>, Error> {
+    use Evaluated::{Constant, Num};
+
+    let evaluated = match formula {
+        Formula::Num(num) => Num(num.clone()),
+        Formula::Constant(constant) => Constant(*constant),
+        Formula::Add(lhs, rhs) => {
+            match (
+                eval_inner(chip, layouter, lhs)?,
+                eval_inner(chip, layouter, rhs)?,
+            ) {
+                (Constant(lhs), Constant(rhs)) => Constant(lhs + rhs),
+                (Num(num), Constant(constant)) | (Constant(constant), Num(num)) => {
+                    Num(chip.add_constant(layouter.namespace(|| "add constant"), num, constant)?)
+                }
+                (Num(lhs), Num(rhs)) => Num(chip.add(layouter.namespace(|| "add"), lhs, rhs)?),
+            }
+        }
+        Formula::Sub(lhs, rhs) => {
+            match (
+                eval_inner(chip, layouter, lhs)?,
+                eval_inner(chip, layouter, rhs)?,
+            ) {
+                (Constant(lhs), Constant(rhs)) => Constant(lhs - rhs),
+                (Num(num), Constant(constant)) => {
+                    Num(chip.add_constant(layouter.namespace(|| "sub constant"), num, -constant)?)
+                }
+                (Constant(constant), Num(num)) => {
+                    let neg = chip.neg(layouter.namespace(|| "neg"), num)?;
+                    Num(chip.add_constant(layouter.namespace(|| "add constant"), neg, constant)?)
+                }
+                (Num(lhs), Num(rhs)) => Num(chip.sub(layouter.namespace(|| "sub"), lhs, rhs)?),
+            }
+        }
+        Formula::Mul(lhs, rhs) => {
+            match (
+                eval_inner(chip, layouter, lhs)?,
+                eval_inner(chip, layouter, rhs)?,
+            ) {
+                (Constant(lhs), Constant(rhs)) => Constant(lhs * rhs),
+                (Num(num), Constant(constant)) | (Constant(constant), Num(num)) => Num(
+                    chip.mul_by_constant(layouter.namespace(|| "mul by constant"), num, constant)?
+                ),
+                (Num(lhs), Num(rhs)) => Num(chip.mul(layouter.namespace(|| "mul"), lhs, rhs)?),
+            }
+        }
+        Formula::Neg(inner) => match eval_inner(chip, layouter, inner)? {
+            Constant(constant) => Constant(-constant),
+            Num(num) => Num(chip.neg(layouter.namespace(|| "neg"), num)?),
+        },
+        Formula::Pow(base, exponent) => match eval_inner(chip, layouter, base)? {
+            Constant(constant) => Constant(constant.pow(&[*exponent, 0, 0, 0])),
+            Num(num) => Num(chip.pow(layouter.namespace(|| "pow"), num, *exponent)?),
+        },
+    };
+    Ok(evaluated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_missing_instance;
+    use crate::soundness::mutation::unconstrained_cells;
+    use crate::arithmetic::standard_plonk::{StandardPlonkChip, StandardPlonkConfig};
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, ConstraintSystem},
+    };
+
+    /// The circuit of the simple example, `c = constant * a^2 * b^2`, laid
+    /// out with a single formula.
+    #[derive(Default)]
+    struct FormulaCircuit<F: FieldExt> {
+        constant: F,
+        a: Value<F>,
+        b: Value<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for FormulaCircuit<F> {
+        type Config = StandardPlonkConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            let instance = meta.instance_column();
+            let constant = meta.fixed_column();
+            StandardPlonkChip::configure(meta, advice, instance, constant)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = StandardPlonkChip::<F>::construct(config);
+
+            let a = chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+            let b = chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+
+            let c = Formula::constant(self.constant)
+                * Formula::num(&a).square()
+                * Formula::num(&b).square();
+            let c = eval(&chip, layouter.namespace(|| "constant * a^2 * b^2"), &c)?;
+
+            // A formula mixing all operations, (a - 2*b)^3 - (-(3 + 4) + a*b)
+            let d = (Formula::num(&a) - Formula::constant(F::from(2)) * Formula::num(&b)).pow(3)
+                - (-(Formula::constant(F::from(3)) + Formula::constant(F::from(4)))
+                    + Formula::num(&a) * Formula::num(&b));
+            let d = eval(&chip, layouter.namespace(|| "formula d"), &d)?;
+
+            chip.expose_public(layouter.namespace(|| "expose c"), c, 0)?;
+            chip.expose_public(layouter.namespace(|| "expose d"), d, 1)
+        }
+    }
+
+    #[test]
+    fn test_formula() {
+        let constant = Fp::from(7);
+        let a = Fp::from(2);
+        let b = Fp::from(3);
+        let c = constant * a.square() * b.square();
+        let d = (a - Fp::from(2) * b).pow(&[3, 0, 0, 0]) - (-Fp::from(7) + a * b);
+
+        let circuit = FormulaCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+
+        let mut public_inputs = vec![c, d];
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        public_inputs[0] += Fp::one();
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert_missing_instance(&prover, 0, 0);
+    }
+
+    #[test]
+    fn test_formula_mutations() {
+        let constant = Fp::from(7);
+        let (a, b) = (Fp::from(2), Fp::from(3));
+        let c = constant * a.square() * b.square();
+        let d = (a - Fp::from(2) * b).pow(&[3, 0, 0, 0]) - (-Fp::from(7) + a * b);
+        let circuit = FormulaCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        // Only the unused wires of the gates are free
+        let unconstrained = unconstrained_cells(&circuit, vec![vec![c, d]]).unwrap();
+        let regions: Vec<&str> = unconstrained
+            .iter()
+            .flat_map(|cells| &cells.cells)
+            .inspect(|cell| assert_eq!(cell.name, "wire"))
+            .map(|cell| cell.region.as_str())
+            .collect();
+        assert_eq!(regions, ["mul by constant", "mul by constant", "add constant"]);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod formula;
+pub mod standard_plonk;
+pub mod uint;
@@ -0,0 +1,769 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Instance},
+    poly::Rotation,
+};
+
+/// Instructions for field arithmetic. This extends the `NumericInstructions`
+/// of the simple example with the remaining field operations.
+pub trait FieldInstructions<F: FieldExt>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Loads a number into the circuit as a private input.
+    fn load_private(&self, layouter: impl Layouter<F>, a: Value<F>) -> Result<Self::Num, Error>;
+
+    /// Loads a number into the circuit as a fixed constant.
+    fn load_constant(&self, layouter: impl Layouter<F>, constant: F) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a + b`.
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a + constant`.
+    fn add_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a - b`.
+    fn sub(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `c = -a`.
+    fn neg(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a * b`.
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `c = constant * a`.
+    fn mul_by_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a * a`.
+    fn square(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error>;
+
+    /// Returns `c = 1 / a`. The constraint `a * c = 1` cannot be satisfied when `a = 0`.
+    fn inv(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a / b`. The constraints cannot be satisfied when `b = 0`.
+    fn div(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `c = a^exponent` for a constant exponent.
+    fn pow(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        exponent: u64,
+    ) -> Result<Self::Num, Error>;
+
+    /// Returns `c = sum_i a_i * b_i`.
+    fn inner_product(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &[Self::Num],
+        b: &[Self::Num],
+    ) -> Result<Self::Num, Error>;
+
+    /// Constrains `a = b`.
+    fn assert_equal(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<(), Error>;
+
+    /// Exposes a number as a public input to the circuit.
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        num: Self::Num,
+        row: usize,
+    ) -> Result<(), Error>;
+}
+
+/// A variable representing a number.
+#[derive(Clone, Debug)]
+pub struct Number<F: FieldExt>(pub AssignedCell<F, F>);
+
+/// The coefficients of the standard PLONK gate in a single row.
+#[derive(Clone, Copy, Debug)]
+struct Coefficients<F: FieldExt> {
+    q_a: F,
+    q_b: F,
+    q_m: F,
+    q_c: F,
+    q_const: F,
+}
+
+impl<F: FieldExt> Coefficients<F> {
+    fn zero() -> Self {
+        Self {
+            q_a: F::zero(),
+            q_b: F::zero(),
+            q_m: F::zero(),
+            q_c: F::zero(),
+            q_const: F::zero(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StandardPlonkConfig {
+    /// The `a`, `b` and `c` wires of the gate.
+    advice: [Column<Advice>; 3],
+
+    /// The gate coefficients `q_a`, `q_b`, `q_m`, `q_c` and `q_const`. Rows in
+    /// which they are all zero are unconstrained, so no selector is needed.
+    q_a: Column<Fixed>,
+    q_b: Column<Fixed>,
+    q_m: Column<Fixed>,
+    q_c: Column<Fixed>,
+    q_const: Column<Fixed>,
+
+    instance: Column<Instance>,
+}
+
+/// A field arithmetic chip in which every instruction is an instance of the
+/// single gate `q_a*a + q_b*b + q_m*a*b + q_c*c + q_const = 0`.
+pub struct StandardPlonkChip<F: FieldExt> {
+    config: StandardPlonkConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> StandardPlonkChip<F> {
+    pub fn construct(config: StandardPlonkConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+    ) -> StandardPlonkConfig {
+        meta.enable_equality(instance);
+        meta.enable_constant(constant);
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+
+        let q_a = meta.fixed_column();
+        let q_b = meta.fixed_column();
+        let q_m = meta.fixed_column();
+        let q_c = meta.fixed_column();
+        let q_const = meta.fixed_column();
+
+        meta.create_gate("standard plonk", |meta| {
+            //
+            // | a | b | c | q_a | q_b | q_m | q_c | q_const |
+            //
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let c = meta.query_advice(advice[2], Rotation::cur());
+            let q_a = meta.query_fixed(q_a, Rotation::cur());
+            let q_b = meta.query_fixed(q_b, Rotation::cur());
+            let q_m = meta.query_fixed(q_m, Rotation::cur());
+            let q_c = meta.query_fixed(q_c, Rotation::cur());
+            let q_const = meta.query_fixed(q_const, Rotation::cur());
+
+            vec![q_a * a.clone() + q_b * b.clone() + q_m * a * b + q_c * c + q_const]
+        });
+
+        StandardPlonkConfig {
+            advice,
+            q_a,
+            q_b,
+            q_m,
+            q_c,
+            q_const,
+            instance,
+        }
+    }
+
+    /// Assigns one row of the gate. Each wire is either copied from an
+    /// existing number or assigned the given value.
+    fn assign_gate(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &str,
+        wires: [(Option<&Number<F>>, Value<F>); 3],
+        coeffs: Coefficients<F>,
+    ) -> Result<[Number<F>; 3], Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                let fixed = [
+                    (config.q_a, coeffs.q_a),
+                    (config.q_b, coeffs.q_b),
+                    (config.q_m, coeffs.q_m),
+                    (config.q_c, coeffs.q_c),
+                    (config.q_const, coeffs.q_const),
+                ];
+                for (column, coeff) in fixed {
+                    region.assign_fixed(|| "coefficient", column, 0, || Value::known(coeff))?;
+                }
+
+                let mut cells = vec![];
+                for (&column, (copy, value)) in config.advice.iter().zip(wires.iter()) {
+                    let cell = match copy {
+                        Some(num) => num
+                            .0
+                            .copy_advice(|| "copied wire", &mut region, column, 0)?,
+                        None => region.assign_advice(|| "wire", column, 0, || *value)?,
+                    };
+                    cells.push(Number(cell));
+                }
+                Ok(cells.try_into().unwrap())
+            },
+        )
+    }
+
+    fn unused_wire() -> (Option<&'static Number<F>>, Value<F>) {
+        (None, Value::known(F::zero()))
+    }
+}
+
+impl<F: FieldExt> Chip<F> for StandardPlonkChip<F> {
+    type Config = StandardPlonkConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> FieldInstructions<F> for StandardPlonkChip<F> {
+    type Num = Number<F>;
+
+    fn load_private(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "load private",
+            |mut region| {
+                region
+                    .assign_advice(|| "private input", config.advice[0], 0, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    fn load_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        constant: F,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "load constant",
+            |mut region| {
+                region
+                    .assign_advice_from_constant(|| "constant value", config.advice[0], 0, constant)
+                    .map(Number)
+            },
+        )
+    }
+
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        // a + b - c = 0
+        let c = a.0.value().copied() + b.0.value();
+        let [_, _, c] = self.assign_gate(
+            layouter,
+            "add",
+            [
+                (Some(&a), Value::unknown()),
+                (Some(&b), Value::unknown()),
+                (None, c),
+            ],
+            Coefficients {
+                q_a: F::one(),
+                q_b: F::one(),
+                q_c: -F::one(),
+                ..Coefficients::zero()
+            },
+        )?;
+        Ok(c)
+    }
+
+    fn add_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error> {
+        // a + constant - c = 0
+        let c = a.0.value().map(|a| *a + constant);
+        let [_, _, c] = self.assign_gate(
+            layouter,
+            "add constant",
+            [(Some(&a), Value::unknown()), Self::unused_wire(), (None, c)],
+            Coefficients {
+                q_a: F::one(),
+                q_c: -F::one(),
+                q_const: constant,
+                ..Coefficients::zero()
+            },
+        )?;
+        Ok(c)
+    }
+
+    fn sub(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        // a - b - c = 0
+        let c = a.0.value().copied() - b.0.value();
+        let [_, _, c] = self.assign_gate(
+            layouter,
+            "sub",
+            [
+                (Some(&a), Value::unknown()),
+                (Some(&b), Value::unknown()),
+                (None, c),
+            ],
+            Coefficients {
+                q_a: F::one(),
+                q_b: -F::one(),
+                q_c: -F::one(),
+                ..Coefficients::zero()
+            },
+        )?;
+        Ok(c)
+    }
+
+    fn neg(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+        // a + c = 0
+        let c = a.0.value().map(|a| -*a);
+        let [_, _, c] = self.assign_gate(
+            layouter,
+            "neg",
+            [(Some(&a), Value::unknown()), Self::unused_wire(), (None, c)],
+            Coefficients {
+                q_a: F::one(),
+                q_c: F::one(),
+                ..Coefficients::zero()
+            },
+        )?;
+        Ok(c)
+    }
+
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        // a * b - c = 0
+        let c = a.0.value().copied() * b.0.value();
+        let [_, _, c] = self.assign_gate(
+            layouter,
+            "mul",
+            [
+                (Some(&a), Value::unknown()),
+                (Some(&b), Value::unknown()),
+                (None, c),
+            ],
+            Coefficients {
+                q_m: F::one(),
+                q_c: -F::one(),
+                ..Coefficients::zero()
+            },
+        )?;
+        Ok(c)
+    }
+
+    fn mul_by_constant(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        constant: F,
+    ) -> Result<Self::Num, Error> {
+        // constant * a - c = 0
+        let c = a.0.value().map(|a| *a * constant);
+        let [_, _, c] = self.assign_gate(
+            layouter,
+            "mul by constant",
+            [(Some(&a), Value::unknown()), Self::unused_wire(), (None, c)],
+            Coefficients {
+                q_a: constant,
+                q_c: -F::one(),
+                ..Coefficients::zero()
+            },
+        )?;
+        Ok(c)
+    }
+
+    fn square(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+        // a * b - c = 0 with b copied from a
+        let c = a.0.value().map(|a| a.square());
+        let [_, _, c] = self.assign_gate(
+            layouter,
+            "square",
+            [
+                (Some(&a), Value::unknown()),
+                (Some(&a), Value::unknown()),
+                (None, c),
+            ],
+            Coefficients {
+                q_m: F::one(),
+                q_c: -F::one(),
+                ..Coefficients::zero()
+            },
+        )?;
+        Ok(c)
+    }
+
+    fn inv(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+        // a * b - 1 = 0, where b is the inverse. Zero has no inverse, so the
+        // witness for a = 0 is left as zero and fails the constraint.
+        let a_inv = a.0.value().map(|a| a.invert().unwrap_or(F::zero()));
+        let [_, a_inv, _] = self.assign_gate(
+            layouter,
+            "inv",
+            [
+                (Some(&a), Value::unknown()),
+                (None, a_inv),
+                Self::unused_wire(),
+            ],
+            Coefficients {
+                q_m: F::one(),
+                q_const: -F::one(),
+                ..Coefficients::zero()
+            },
+        )?;
+        Ok(a_inv)
+    }
+
+    fn div(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        // Checking b * c = a alone would allow any c when a = b = 0
+        let b_inv = self.inv(layouter.namespace(|| "divisor inverse"), b)?;
+        self.mul(layouter.namespace(|| "a * (1/b)"), a, b_inv)
+    }
+
+    fn pow(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        exponent: u64,
+    ) -> Result<Self::Num, Error> {
+        if exponent == 0 {
+            return self.load_constant(layouter.namespace(|| "a^0"), F::one());
+        }
+
+        // Square-and-multiply, starting below the most significant bit
+        let mut acc = a.clone();
+        for i in (0..(63 - exponent.leading_zeros())).rev() {
+            acc = self.square(layouter.namespace(|| format!("square for bit {}", i)), acc)?;
+            if (exponent >> i) & 1 == 1 {
+                acc = self.mul(
+                    layouter.namespace(|| format!("multiply for bit {}", i)),
+                    acc,
+                    a.clone(),
+                )?;
+            }
+        }
+        Ok(acc)
+    }
+
+    fn inner_product(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &[Self::Num],
+        b: &[Self::Num],
+    ) -> Result<Self::Num, Error> {
+        assert_eq!(a.len(), b.len());
+        assert!(!a.is_empty());
+
+        let mut acc = self.mul(layouter.namespace(|| "term 0"), a[0].clone(), b[0].clone())?;
+        for (i, (a, b)) in a.iter().zip(b.iter()).enumerate().skip(1) {
+            let term = self.mul(
+                layouter.namespace(|| format!("term {}", i)),
+                a.clone(),
+                b.clone(),
+            )?;
+            acc = self.add(layouter.namespace(|| format!("sum {}", i)), acc, term)?;
+        }
+        Ok(acc)
+    }
+
+    fn assert_equal(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<(), Error> {
+        // a - b = 0
+        self.assign_gate(
+            layouter,
+            "assert equal",
+            [
+                (Some(&a), Value::unknown()),
+                (Some(&b), Value::unknown()),
+                Self::unused_wire(),
+            ],
+            Coefficients {
+                q_a: F::one(),
+                q_b: -F::one(),
+                ..Coefficients::zero()
+            },
+        )?;
+        Ok(())
+    }
+
+    fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        num: Self::Num,
+        row: usize,
+    ) -> Result<(), Error> {
+        let config = self.config();
+
+        layouter.constrain_instance(num.0.cell(), config.instance, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_constraint_failure;
+    use crate::soundness::mutation::unconstrained_cells;
+    use halo2_proofs::{
+        arithmetic::Field, circuit::SimpleFloorPlanner, dev::MockProver, pasta::Fp, plonk::Circuit,
+    };
+
+    #[derive(Default)]
+    struct ArithmeticCircuit<F: FieldExt> {
+        a: Value<F>,
+        b: Value<F>,
+        // Replaces the witness of the output of `inv` when set
+        malicious_a_inv: Option<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for ArithmeticCircuit<F> {
+        type Config = StandardPlonkConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            let instance = meta.instance_column();
+            let constant = meta.fixed_column();
+            StandardPlonkChip::configure(meta, advice, instance, constant)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = StandardPlonkChip::<F>::construct(config.clone());
+
+            let a = chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+            let b = chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+            let seven = chip.load_constant(layouter.namespace(|| "load 7"), F::from(7))?;
+
+            let a_inv = match self.malicious_a_inv {
+                None => chip.inv(layouter.namespace(|| "1/a"), a.clone())?,
+                Some(a_inv) => {
+                    let [_, a_inv, _] = chip.assign_gate(
+                        layouter.namespace(|| "malicious 1/a"),
+                        "inv",
+                        [
+                            (Some(&a), Value::unknown()),
+                            (None, Value::known(a_inv)),
+                            StandardPlonkChip::unused_wire(),
+                        ],
+                        Coefficients {
+                            q_m: F::one(),
+                            q_const: -F::one(),
+                            ..Coefficients::zero()
+                        },
+                    )?;
+                    a_inv
+                }
+            };
+
+            let outputs = [
+                chip.add(layouter.namespace(|| "a + b"), a.clone(), b.clone())?,
+                chip.add_constant(layouter.namespace(|| "a + 7"), a.clone(), F::from(7))?,
+                chip.sub(layouter.namespace(|| "a - b"), a.clone(), b.clone())?,
+                chip.neg(layouter.namespace(|| "-a"), a.clone())?,
+                chip.mul(layouter.namespace(|| "a * b"), a.clone(), b.clone())?,
+                chip.mul_by_constant(layouter.namespace(|| "7 * a"), a.clone(), F::from(7))?,
+                chip.square(layouter.namespace(|| "a^2"), a.clone())?,
+                a_inv,
+                chip.div(layouter.namespace(|| "b / a"), b.clone(), a.clone())?,
+                chip.pow(layouter.namespace(|| "a^13"), a.clone(), 13)?,
+                chip.pow(layouter.namespace(|| "a^0"), a.clone(), 0)?,
+                chip.inner_product(
+                    layouter.namespace(|| "<(a, b, 7), (b, 7, a)>"),
+                    &[a.clone(), b.clone(), seven.clone()],
+                    &[b.clone(), seven, a],
+                )?,
+            ];
+
+            for (row, output) in outputs.into_iter().enumerate() {
+                chip.expose_public(
+                    layouter.namespace(|| format!("output {}", row)),
+                    output,
+                    row,
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    fn expected_outputs(a: Fp, b: Fp) -> Vec<Fp> {
+        let seven = Fp::from(7);
+        let a_inv = a.invert().unwrap_or(Fp::zero());
+        vec![
+            a + b,
+            a + seven,
+            a - b,
+            -a,
+            a * b,
+            seven * a,
+            a.square(),
+            a_inv,
+            b * a_inv,
+            a.pow(&[13, 0, 0, 0]),
+            Fp::one(),
+            a * b + b * seven + seven * a,
+        ]
+    }
+
+    #[test]
+    fn test_standard_plonk_chip() {
+        let (a, b) = (Fp::from(3), Fp::from(10));
+
+        let circuit = ArithmeticCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+            malicious_a_inv: None,
+        };
+
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![expected_outputs(a, b)]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_standard_plonk_chip_inverse_of_zero() {
+        let (a, b) = (Fp::zero(), Fp::from(10));
+
+        for malicious_a_inv in [None, Some(Fp::from(5))] {
+            let circuit = ArithmeticCircuit {
+                a: Value::known(a),
+                b: Value::known(b),
+                malicious_a_inv,
+            };
+            let mut public_inputs = expected_outputs(a, b);
+            public_inputs[7] = malicious_a_inv.unwrap_or(Fp::zero());
+
+            let k = minimal_k(&circuit);
+            let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+            assert_constraint_failure(&prover, "standard plonk", "inv");
+        }
+    }
+
+    #[test]
+    fn test_standard_plonk_chip_wrong_inverse() {
+        let (a, b) = (Fp::from(3), Fp::from(10));
+
+        let circuit = ArithmeticCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+            malicious_a_inv: Some(Fp::from(5)),
+        };
+        let mut public_inputs = expected_outputs(a, b);
+        public_inputs[7] = Fp::from(5);
+
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert_constraint_failure(&prover, "standard plonk", "inv");
+    }
+
+    #[test]
+    fn test_standard_plonk_chip_mutations() {
+        let (a, b) = (Fp::from(3), Fp::from(10));
+        let circuit = ArithmeticCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+            malicious_a_inv: None,
+        };
+        // Only the unused wires of the gates are free
+        let unconstrained = unconstrained_cells(&circuit, vec![expected_outputs(a, b)]).unwrap();
+        let regions: Vec<&str> = unconstrained
+            .iter()
+            .flat_map(|cells| &cells.cells)
+            .inspect(|cell| assert_eq!(cell.name, "wire"))
+            .map(|cell| cell.region.as_str())
+            .collect();
+        assert_eq!(
+            regions,
+            ["inv", "add constant", "neg", "mul by constant", "inv"]
+        );
+    }
+}
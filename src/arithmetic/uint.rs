@@ -0,0 +1,692 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use crate::comparison::less_than::load_u8_table;
+use crate::is_zero_example::is_zero::{IsZeroChip, IsZeroConfig};
+use crate::logic::boolean::Bit;
+
+/// An unsigned integer which has been range checked to `BITS` bits.
+#[derive(Clone, Debug)]
+pub struct UInt<F: FieldExt, const BITS: usize>(pub AssignedCell<F, F>);
+
+#[derive(Clone, Debug)]
+pub struct UIntConfig<F, const BITS: usize> {
+    /// The operands `a`, `b` and results `c`, `d`, `e` of an operation. The
+    /// column `a` also holds the values which are range checked.
+    advice: [Column<Advice>; 5],
+    /// The little endian bytes of a range checked value.
+    bytes: Vec<Column<Advice>>,
+    /// `2^k` for a shift by `k` bits.
+    shift: Column<Fixed>,
+    u8_table: TableColumn,
+    /// Checks whether the high half of a product is zero.
+    hi_is_zero: IsZeroConfig<F>,
+    s_range: Selector,
+    s_add: Selector,
+    s_sub: Selector,
+    s_mul: Selector,
+    s_div: Selector,
+    s_shr: Selector,
+    s_shl: Selector,
+}
+
+/// Arithmetic on unsigned integers of `BITS` bits, e.g. `u8`, `u16`, `u32`
+/// or `u64`.
+///
+/// Each operation is a single row in which the results are witnessed,
+/// followed by one row per result which must be range checked:
+///
+/// | a     | b | c | d | e | bytes[0] ... bytes[BITS/8-1] |
+/// |-------|---|---|---|---|------------------------------|
+/// | lhs   |rhs| results   |                              |
+/// | c     |   |   |   |   | bytes of c                   |
+/// | ...   |   |   |   |   | ...                          |
+///
+/// A range check decomposes the value into bytes, as the Collatz example
+/// decomposes its elements into bits, and looks each byte up in a table.
+pub struct UIntChip<F: FieldExt, const BITS: usize> {
+    config: UIntConfig<F, BITS>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const BITS: usize> UIntChip<F, BITS> {
+    pub fn construct(config: UIntConfig<F, BITS>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The columns for the bytes of range checked values and the inverse used
+    /// by the overflow check are allocated by the chip.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 5],
+        shift: Column<Fixed>,
+        u8_table: TableColumn,
+    ) -> UIntConfig<F, BITS> {
+        // The product of two integers must not wrap around the field modulus
+        assert!(matches!(BITS, 8 | 16 | 32 | 64));
+
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let bytes: Vec<Column<Advice>> = (0..BITS / 8).map(|_| meta.advice_column()).collect();
+        let value_inv = meta.advice_column();
+
+        let s_range = meta.complex_selector();
+        let s_add = meta.selector();
+        let s_sub = meta.selector();
+        let s_mul = meta.selector();
+        let s_div = meta.selector();
+        let s_shr = meta.selector();
+        let s_shl = meta.selector();
+
+        let one = Expression::Constant(F::one());
+        let range = Expression::Constant(F::from_u128(1 << BITS));
+
+        meta.create_gate("range check", |meta| {
+            let s = meta.query_selector(s_range);
+            let value = meta.query_advice(advice[0], Rotation::cur());
+            let sum = bytes
+                .iter()
+                .rev()
+                .fold(Expression::Constant(F::zero()), |acc, byte| {
+                    acc * Expression::Constant(F::from(256))
+                        + meta.query_advice(*byte, Rotation::cur())
+                });
+            vec![s * (value - sum)]
+        });
+
+        for byte in bytes.iter() {
+            meta.lookup(|meta| {
+                let s = meta.query_selector(s_range);
+                let byte = meta.query_advice(*byte, Rotation::cur());
+                vec![(s * byte, u8_table)]
+            });
+        }
+
+        meta.create_gate("add", |meta| {
+            let s = meta.query_selector(s_add);
+            let [a, b, sum, carry, _] =
+                advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![
+                s.clone() * (a + b - sum - carry.clone() * range.clone()),
+                s * carry.clone() * (one.clone() - carry),
+            ]
+        });
+
+        meta.create_gate("sub", |meta| {
+            let s = meta.query_selector(s_sub);
+            let [a, b, diff, borrow, _] =
+                advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![
+                s.clone() * (a - b - diff + borrow.clone() * range.clone()),
+                s * borrow.clone() * (one.clone() - borrow),
+            ]
+        });
+
+        let hi_is_zero = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(s_mul),
+            |meta| meta.query_advice(advice[3], Rotation::cur()),
+            value_inv,
+        );
+
+        meta.create_gate("mul", |meta| {
+            let s = meta.query_selector(s_mul);
+            let [a, b, lo, hi, overflow] =
+                advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![
+                s.clone() * (a * b - lo - hi * range.clone()),
+                s * (overflow - (one.clone() - hi_is_zero.expr())),
+            ]
+        });
+
+        meta.create_gate("div rem", |meta| {
+            let s = meta.query_selector(s_div);
+            let [a, b, quotient, remainder, slack] =
+                advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            // The range check of b - remainder - 1 ensures remainder < b
+            vec![
+                s.clone() * (a - b.clone() * quotient - remainder.clone()),
+                s * (slack - (b - remainder - one.clone())),
+            ]
+        });
+
+        meta.create_gate("shr", |meta| {
+            let s = meta.query_selector(s_shr);
+            let shift = meta.query_fixed(shift, Rotation::cur());
+            let [a, _, quotient, remainder, scaled] =
+                advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            // The range check of remainder * 2^(BITS-k) ensures remainder < 2^k
+            vec![
+                s.clone() * (a - quotient * shift.clone() - remainder.clone()),
+                s * (scaled * shift - remainder * range.clone()),
+            ]
+        });
+
+        meta.create_gate("shl", |meta| {
+            let s = meta.query_selector(s_shl);
+            let shift = meta.query_fixed(shift, Rotation::cur());
+            let [a, _, lo, hi, _] = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![s * (a * shift - lo - hi * range.clone())]
+        });
+
+        UIntConfig {
+            advice,
+            bytes,
+            shift,
+            u8_table,
+            hi_is_zero,
+            s_range,
+            s_add,
+            s_sub,
+            s_mul,
+            s_div,
+            s_shr,
+            s_shl,
+        }
+    }
+
+    /// Loads the byte table. If several chips share the same `u8_table`,
+    /// it must be loaded only once.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        load_u8_table(layouter, self.config.u8_table)
+    }
+
+    /// Assigns a range checked integer. The constraints cannot be satisfied
+    /// when the value does not fit into `BITS` bits.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<UInt<F, BITS>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "assign uint",
+            |mut region| {
+                let cell = region.assign_advice(|| "value", config.advice[0], 0, || value)?;
+                self.range_check(&mut region, 0, &cell)?;
+                Ok(UInt(cell))
+            },
+        )
+    }
+
+    /// Returns `(a + b mod 2^BITS, carry)`.
+    pub fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &UInt<F, BITS>,
+        b: &UInt<F, BITS>,
+    ) -> Result<(UInt<F, BITS>, Bit<F>), Error> {
+        let sum = to_u128(a).zip(to_u128(b)).map(|(a, b)| a + b);
+        let [sum, carry, _] = self.assign_op(
+            layouter,
+            "add",
+            self.config.s_add,
+            [a, b],
+            None,
+            [
+                sum.map(|sum| sum & mask::<BITS>()),
+                sum.map(|sum| sum >> BITS),
+                Value::known(0),
+            ],
+            &[0],
+        )?;
+        Ok((UInt(sum), Bit(carry)))
+    }
+
+    /// Returns `(a - b mod 2^BITS, borrow)`.
+    pub fn sub(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &UInt<F, BITS>,
+        b: &UInt<F, BITS>,
+    ) -> Result<(UInt<F, BITS>, Bit<F>), Error> {
+        let a_b = to_u128(a).zip(to_u128(b));
+        let [diff, borrow, _] = self.assign_op(
+            layouter,
+            "sub",
+            self.config.s_sub,
+            [a, b],
+            None,
+            [
+                a_b.map(|(a, b)| a.wrapping_sub(b) & mask::<BITS>()),
+                a_b.map(|(a, b)| (a < b) as u128),
+                Value::known(0),
+            ],
+            &[0],
+        )?;
+        Ok((UInt(diff), Bit(borrow)))
+    }
+
+    /// Returns `(a * b mod 2^BITS, overflow)`.
+    pub fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &UInt<F, BITS>,
+        b: &UInt<F, BITS>,
+    ) -> Result<(UInt<F, BITS>, Bit<F>), Error> {
+        let product = to_u128(a).zip(to_u128(b)).map(|(a, b)| a * b);
+        let [lo, _, overflow] = self.assign_op(
+            layouter,
+            "mul",
+            self.config.s_mul,
+            [a, b],
+            None,
+            [
+                product.map(|product| product & mask::<BITS>()),
+                product.map(|product| product >> BITS),
+                product.map(|product| (product >> BITS != 0) as u128),
+            ],
+            &[0, 1],
+        )?;
+        Ok((UInt(lo), Bit(overflow)))
+    }
+
+    /// Returns `(a / b, a % b)`. The constraints cannot be satisfied when `b = 0`.
+    pub fn div_rem(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &UInt<F, BITS>,
+        b: &UInt<F, BITS>,
+    ) -> Result<(UInt<F, BITS>, UInt<F, BITS>), Error> {
+        let a_b = to_u128(a).zip(to_u128(b));
+        let [quotient, remainder, _] = self.assign_op(
+            layouter,
+            "div rem",
+            self.config.s_div,
+            [a, b],
+            None,
+            [
+                a_b.map(|(a, b)| a.checked_div(b).unwrap_or(0)),
+                a_b.map(|(a, b)| a.checked_rem(b).unwrap_or(a)),
+                a_b.map(|(a, b)| b.wrapping_sub(a.checked_rem(b).unwrap_or(a) + 1)),
+            ],
+            &[0, 1, 2],
+        )?;
+        Ok((UInt(quotient), UInt(remainder)))
+    }
+
+    /// Returns `a >> k` for a constant `k <= BITS`.
+    pub fn shr(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &UInt<F, BITS>,
+        k: usize,
+    ) -> Result<UInt<F, BITS>, Error> {
+        assert!(k <= BITS);
+        let a_u128 = to_u128(a);
+        let remainder = a_u128.map(|a| a & ((1 << k) - 1));
+        let [quotient, _, _] = self.assign_op(
+            layouter,
+            "shr",
+            self.config.s_shr,
+            [a, a],
+            Some(F::from_u128(1 << k)),
+            [
+                a_u128.map(|a| a >> k),
+                remainder,
+                remainder.map(|remainder| remainder << (BITS - k)),
+            ],
+            &[0, 1, 2],
+        )?;
+        Ok(UInt(quotient))
+    }
+
+    /// Returns `a << k mod 2^BITS` for a constant `k <= BITS`.
+    pub fn shl(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &UInt<F, BITS>,
+        k: usize,
+    ) -> Result<UInt<F, BITS>, Error> {
+        assert!(k <= BITS);
+        let shifted = to_u128(a).map(|a| a << k);
+        let [lo, _, _] = self.assign_op(
+            layouter,
+            "shl",
+            self.config.s_shl,
+            [a, a],
+            Some(F::from_u128(1 << k)),
+            [
+                shifted.map(|shifted| shifted & mask::<BITS>()),
+                shifted.map(|shifted| shifted >> BITS),
+                Value::known(0),
+            ],
+            &[0, 1],
+        )?;
+        Ok(UInt(lo))
+    }
+
+    /// Returns `a < b`, which is the borrow of `a - b`.
+    pub fn lt(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &UInt<F, BITS>,
+        b: &UInt<F, BITS>,
+    ) -> Result<Bit<F>, Error> {
+        self.sub(layouter, a, b).map(|(_, borrow)| borrow)
+    }
+
+    /// Assigns one operation row with the operands copied to `a` and `b` and
+    /// the results `c`, `d` and `e`, and range checks the results at the
+    /// given indices.
+    #[allow(clippy::too_many_arguments)]
+    fn assign_op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &str,
+        selector: Selector,
+        operands: [&UInt<F, BITS>; 2],
+        shift: Option<F>,
+        results: [Value<u128>; 3],
+        range_checked: &[usize],
+    ) -> Result<[AssignedCell<F, F>; 3], Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                selector.enable(&mut region, 0)?;
+                operands[0]
+                    .0
+                    .copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+                operands[1]
+                    .0
+                    .copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+                if let Some(shift) = shift {
+                    region.assign_fixed(|| "2^k", config.shift, 0, || Value::known(shift))?;
+                }
+
+                let mut cells = vec![];
+                for (column, result) in config.advice[2..].iter().zip(results) {
+                    let result = result.map(F::from_u128);
+                    cells.push(region.assign_advice(|| "result", *column, 0, || result)?);
+                }
+                if selector == config.s_mul {
+                    IsZeroChip::construct(config.hi_is_zero.clone()).assign(
+                        &mut region,
+                        0,
+                        cells[1].value().copied(),
+                    )?;
+                }
+
+                for (row, index) in range_checked.iter().enumerate() {
+                    let cell = cells[*index].copy_advice(
+                        || "range checked",
+                        &mut region,
+                        config.advice[0],
+                        row + 1,
+                    )?;
+                    self.range_check(&mut region, row + 1, &cell)?;
+                }
+
+                Ok(cells.try_into().unwrap())
+            },
+        )
+    }
+
+    fn range_check(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        cell: &AssignedCell<F, F>,
+    ) -> Result<(), Error> {
+        let config = self.config();
+        config.s_range.enable(region, offset)?;
+
+        let value = cell.value().map(|value| value.get_lower_128());
+        for (i, column) in config.bytes.iter().enumerate() {
+            let byte = value.map(|value| F::from(((value >> (8 * i)) & 0xff) as u64));
+            region.assign_advice(|| format!("byte {}", i), *column, offset, || byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: FieldExt, const BITS: usize> Chip<F> for UIntChip<F, BITS> {
+    type Config = UIntConfig<F, BITS>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+fn to_u128<F: FieldExt, const BITS: usize>(a: &UInt<F, BITS>) -> Value<u128> {
+    a.0.value().map(|a| a.get_lower_128())
+}
+
+fn mask<const BITS: usize>() -> u128 {
+    (1 << BITS) - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_constraint_failure;
+    use crate::soundness::mutation::unconstrained_cells;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+    use proptest::prelude::*;
+
+    #[derive(Clone, Debug)]
+    struct UIntTestConfig<const BITS: usize> {
+        uint: UIntConfig<Fp, BITS>,
+        instance: Column<Instance>,
+    }
+
+    /// Applies every operation to `a` and `b` and exposes the results in the
+    /// order of `native_results`.
+    #[derive(Default)]
+    struct UIntTestCircuit<const BITS: usize> {
+        a: u128,
+        b: u128,
+        k: usize,
+    }
+
+    impl<const BITS: usize> Circuit<Fp> for UIntTestCircuit<BITS> {
+        type Config = UIntTestConfig<BITS>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 5].map(|_| meta.advice_column());
+            let shift = meta.fixed_column();
+            let u8_table = meta.lookup_table_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            UIntTestConfig {
+                uint: UIntChip::configure(meta, advice, shift, u8_table),
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = UIntChip::<Fp, BITS>::construct(config.uint);
+            chip.load(&mut layouter)?;
+
+            let a = chip.assign(
+                layouter.namespace(|| "a"),
+                Value::known(Fp::from_u128(self.a)),
+            )?;
+            let b = chip.assign(
+                layouter.namespace(|| "b"),
+                Value::known(Fp::from_u128(self.b)),
+            )?;
+
+            let (sum, carry) = chip.add(layouter.namespace(|| "a + b"), &a, &b)?;
+            let (diff, borrow) = chip.sub(layouter.namespace(|| "a - b"), &a, &b)?;
+            let (product, overflow) = chip.mul(layouter.namespace(|| "a * b"), &a, &b)?;
+            let (quotient, remainder) = chip.div_rem(layouter.namespace(|| "a / b"), &a, &b)?;
+            let shr = chip.shr(layouter.namespace(|| "a >> k"), &a, self.k)?;
+            let shl = chip.shl(layouter.namespace(|| "a << k"), &a, self.k)?;
+            let lt = chip.lt(layouter.namespace(|| "a < b"), &a, &b)?;
+
+            let outputs = [
+                sum.0,
+                carry.0,
+                diff.0,
+                borrow.0,
+                product.0,
+                overflow.0,
+                quotient.0,
+                remainder.0,
+                shr.0,
+                shl.0,
+                lt.0,
+            ];
+            for (row, output) in outputs.iter().enumerate() {
+                layouter.constrain_instance(output.cell(), config.instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    macro_rules! native_results {
+        ($name:ident, $uint:ty) => {
+            fn $name(a: $uint, b: $uint, k: u32) -> Vec<Fp> {
+                let (sum, carry) = a.overflowing_add(b);
+                let (diff, borrow) = a.overflowing_sub(b);
+                let (product, overflow) = a.overflowing_mul(b);
+                let shl = a.checked_shl(k).unwrap_or(0);
+                let shr = a.checked_shr(k).unwrap_or(0);
+                vec![
+                    Fp::from(sum as u64),
+                    Fp::from(carry),
+                    Fp::from(diff as u64),
+                    Fp::from(borrow),
+                    Fp::from(product as u64),
+                    Fp::from(overflow),
+                    Fp::from((a / b) as u64),
+                    Fp::from((a % b) as u64),
+                    Fp::from(shr as u64),
+                    Fp::from(shl as u64),
+                    Fp::from(a < b),
+                ]
+            }
+        };
+    }
+
+    native_results!(native_u8, u8);
+    native_results!(native_u16, u16);
+    native_results!(native_u32, u32);
+    native_results!(native_u64, u64);
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_uint8_matches_native(a: u8, b in 1u8.., k in 0u32..=8) {
+            let circuit = UIntTestCircuit::<8> { a: a as u128, b: b as u128, k: k as usize };
+            let prover = MockProver::run(minimal_k(&circuit), &circuit, vec![native_u8(a, b, k)]).unwrap();
+            prop_assert_eq!(prover.verify(), Ok(()));
+        }
+
+        #[test]
+        fn test_uint16_matches_native(a: u16, b in 1u16.., k in 0u32..=16) {
+            let circuit = UIntTestCircuit::<16> { a: a as u128, b: b as u128, k: k as usize };
+            let prover = MockProver::run(minimal_k(&circuit), &circuit, vec![native_u16(a, b, k)]).unwrap();
+            prop_assert_eq!(prover.verify(), Ok(()));
+        }
+
+        #[test]
+        fn test_uint32_matches_native(a: u32, b in 1u32.., k in 0u32..=32) {
+            let circuit = UIntTestCircuit::<32> { a: a as u128, b: b as u128, k: k as usize };
+            let prover = MockProver::run(minimal_k(&circuit), &circuit, vec![native_u32(a, b, k)]).unwrap();
+            prop_assert_eq!(prover.verify(), Ok(()));
+        }
+
+        #[test]
+        fn test_uint64_matches_native(a: u64, b in 1u64.., k in 0u32..=64) {
+            let circuit = UIntTestCircuit::<64> { a: a as u128, b: b as u128, k: k as usize };
+            let prover = MockProver::run(minimal_k(&circuit), &circuit, vec![native_u64(a, b, k)]).unwrap();
+            prop_assert_eq!(prover.verify(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_uint_edge_cases() {
+        for (a, b, k) in [
+            (0, 1, 0),
+            (255, 255, 8),
+            (255, 1, 1),
+            (1, 255, 7),
+            (128, 2, 4),
+        ] {
+            let circuit = UIntTestCircuit::<8> {
+                a,
+                b,
+                k: k as usize,
+            };
+            let public_inputs = native_u8(a as u8, b as u8, k);
+            let prover =
+                MockProver::run(minimal_k(&circuit), &circuit, vec![public_inputs]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_uint_out_of_range() {
+        // 256 does not fit into a byte, even though the results are consistent with a = 0,
+        // so the range check of the input fails where it is assigned
+        let circuit = UIntTestCircuit::<8> { a: 256, b: 1, k: 0 };
+        let public_inputs = native_u8(0, 1, 0);
+        let prover = MockProver::run(minimal_k(&circuit), &circuit, vec![public_inputs]).unwrap();
+        assert_constraint_failure(&prover, "range check", "assign uint");
+    }
+
+    #[test]
+    fn test_uint_division_by_zero() {
+        let circuit = UIntTestCircuit::<8> { a: 7, b: 0, k: 0 };
+        let mut public_inputs = native_u8(7, 1, 0);
+        public_inputs[0] = Fp::from(7);
+        // Results of a + 0, a - 0, a * 0 and a < 0, with the witnessed a / 0 = 0 and a % 0 = a
+        public_inputs[2] = Fp::from(7);
+        public_inputs[4] = Fp::zero();
+        public_inputs[6] = Fp::zero();
+        public_inputs[7] = Fp::from(7);
+        let prover = MockProver::run(minimal_k(&circuit), &circuit, vec![public_inputs]).unwrap();
+        assert_constraint_failure(&prover, "div rem", "div rem");
+    }
+
+    #[test]
+    fn test_uint_mutations() {
+        let circuit = UIntTestCircuit::<8> { a: 200, b: 7, k: 3 };
+        // Only the unused third results of the operations are free
+        let unconstrained = unconstrained_cells(&circuit, vec![native_u8(200, 7, 3)]).unwrap();
+        let regions: Vec<&str> = unconstrained
+            .iter()
+            .flat_map(|cells| &cells.cells)
+            .inspect(|cell| assert_eq!(cell.name, "result"))
+            .map(|cell| cell.region.as_str())
+            .collect();
+        assert_eq!(regions, ["add", "sub", "shl", "sub"]);
+    }
+}
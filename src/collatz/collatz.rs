@@ -6,7 +6,7 @@ use halo2_proofs::{plonk::*, arithmetic::FieldExt, poly::Rotation, circuit::*};
 struct ACell<F: FieldExt>(AssignedCell<F, F>);
 
 #[derive(Debug, Clone)]
-struct CollatzConfig<const WIDTH: usize> {
+pub(crate) struct CollatzConfig<const WIDTH: usize> {
     // Option is to allow initialization with [None; WIDTH] in CollatzCircuit::configure
     pub advice: [Option<Column<Advice>>; WIDTH],
     pub s_all_rows: Selector,
@@ -173,7 +173,7 @@ impl<F: FieldExt, const WIDTH: usize> CollatzChip<F, WIDTH> {
 }
 
 #[derive(Default)]
-struct CollatzCircuit<F: FieldExt, const WIDTH: usize> {
+pub(crate) struct CollatzCircuit<F: FieldExt, const WIDTH: usize> {
     pub initial_value: F,
     pub nrows: usize,
 }
@@ -211,12 +211,15 @@ impl <F: FieldExt, const WIDTH: usize> Circuit<F> for CollatzCircuit<F, WIDTH> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_constraint_failure;
+    use halo2_proofs::pasta::Fq;
+    use proptest::prelude::*;
     use halo2_proofs::{dev::MockProver, pasta::Fp};
 
  
     #[test]
     fn test_collatz() {
-        let k = 16;
         const WIDTH: usize = 6;
 
         let circuit: CollatzCircuit<Fp, WIDTH> = CollatzCircuit {
@@ -224,6 +227,7 @@ mod tests {
             nrows: 12,
         };
 
+        let k = minimal_k(&circuit);
         let prover = MockProver::run(k, &circuit, vec![]).unwrap();
         prover.assert_satisfied();
     }
@@ -242,8 +246,56 @@ mod tests {
             nrows: 12
         };
         halo2_proofs::dev::CircuitLayout::default()
-            .render(4, &circuit, &root)
+            .render(minimal_k(&circuit), &circuit, &root)
             .unwrap();
     }
-}
 
+    /// The Collatz sequence from `n` down to 1.
+    fn collatz(mut n: u64) -> Vec<u64> {
+        let mut sequence = vec![n];
+        while n != 1 {
+            n = if n.is_multiple_of(2) { n / 2 } else { 3 * n + 1 };
+            sequence.push(n);
+        }
+        sequence
+    }
+
+    fn check_collatz<F: FieldExt>(initial_value: u64) -> Result<(), TestCaseError> {
+        // Every element of the sequences from values below 256 fits into 16 bits
+        const WIDTH: usize = 16;
+        let sequence = collatz(initial_value);
+        prop_assert!(sequence.iter().all(|&n| n < 1 << WIDTH));
+
+        let circuit = CollatzCircuit::<F, WIDTH> {
+            initial_value: F::from(initial_value),
+            nrows: sequence.len(),
+        };
+        let k = minimal_k(&circuit);
+        prop_assert_eq!(MockProver::run(k, &circuit, vec![]).unwrap().verify(), Ok(()));
+
+        // The sequence does not reach 1 one row earlier
+        if sequence.len() > 1 {
+            let circuit = CollatzCircuit::<F, WIDTH> {
+                initial_value: F::from(initial_value),
+                nrows: sequence.len() - 1,
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_constraint_failure(&prover, "last advice row equals a 1", "Entire Collatz sequence bits");
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_collatz_matches_native_pallas(initial_value in 1u64..256) {
+            check_collatz::<Fp>(initial_value)?;
+        }
+
+        #[test]
+        fn test_collatz_matches_native_vesta(initial_value in 1u64..256) {
+            check_collatz::<Fq>(initial_value)?;
+        }
+    }
+}
@@ -1 +1 @@
-mod collatz;
\ No newline at end of file
+pub(crate) mod collatz;
\ No newline at end of file
@@ -0,0 +1,247 @@
+use crate::is_zero_example::is_zero::{IsZeroChip, IsZeroConfig};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+};
+
+#[derive(Clone, Debug)]
+pub struct IsEqualConfig<F: FieldExt> {
+    pub is_zero: IsZeroConfig<F>,
+    pub output: Column<Advice>,
+}
+
+impl<F: FieldExt> IsEqualConfig<F> {
+    pub fn expr(&self) -> Expression<F> {
+        self.is_zero.expr()
+    }
+}
+
+/// Checks `lhs == rhs` by applying the `is_zero` gadget to `lhs - rhs`.
+/// The boolean result is also stored in the `output` column so that it
+/// can be copied to other parts of the circuit.
+pub struct IsEqualChip<F: FieldExt> {
+    config: IsEqualConfig<F>,
+}
+
+impl<F: FieldExt> IsEqualChip<F> {
+    pub fn construct(config: IsEqualConfig<F>) -> Self {
+        IsEqualChip { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        rhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value_inv: Column<Advice>,
+        output: Column<Advice>,
+    ) -> IsEqualConfig<F> {
+        let is_zero = IsZeroChip::configure_with_output(
+            meta,
+            q_enable,
+            |meta| lhs(meta) - rhs(meta),
+            value_inv,
+            output,
+        );
+
+        IsEqualConfig { is_zero, output }
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Value<F>,
+        rhs: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let is_zero_chip = IsZeroChip::construct(self.config.is_zero.clone());
+        let output = is_zero_chip.assign(region, offset, lhs - rhs)?;
+        Ok(output.expect("is_zero is configured with an output column"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_constraint_failure;
+    use crate::soundness::mutation::{unconstrained_cells, UnconstrainedCells};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone, Debug)]
+    struct IsEqualTestConfig {
+        selector: Selector,
+        lhs: Column<Advice>,
+        rhs: Column<Advice>,
+        instance: Column<Instance>,
+        is_equal: IsEqualConfig<Fp>,
+    }
+
+    #[derive(Default)]
+    struct IsEqualTestCircuit {
+        pairs: Vec<(Fp, Fp)>,
+        // Row at which a malicious prover replaces the `value_inv` and output witnesses
+        tampered_row: Option<(usize, Fp, Fp)>,
+    }
+
+    impl Circuit<Fp> for IsEqualTestCircuit {
+        type Config = IsEqualTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let selector = meta.selector();
+            let lhs = meta.advice_column();
+            let rhs = meta.advice_column();
+            let value_inv = meta.advice_column();
+            let output = meta.advice_column();
+            let instance = meta.instance_column();
+
+            meta.enable_equality(output);
+            meta.enable_equality(instance);
+
+            let is_equal = IsEqualChip::configure(
+                meta,
+                |meta| meta.query_selector(selector),
+                |meta| meta.query_advice(lhs, Rotation::cur()),
+                |meta| meta.query_advice(rhs, Rotation::cur()),
+                value_inv,
+                output,
+            );
+
+            IsEqualTestConfig {
+                selector,
+                lhs,
+                rhs,
+                instance,
+                is_equal,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = IsEqualChip::construct(config.is_equal.clone());
+
+            let outputs = layouter.assign_region(
+                || "is equal checks",
+                |mut region| {
+                    let mut outputs = vec![];
+                    for (row, &(lhs, rhs)) in self.pairs.iter().enumerate() {
+                        config.selector.enable(&mut region, row)?;
+                        let lhs = Value::known(lhs);
+                        let rhs = Value::known(rhs);
+                        region.assign_advice(|| "lhs", config.lhs, row, || lhs)?;
+                        region.assign_advice(|| "rhs", config.rhs, row, || rhs)?;
+                        let mut output = chip.assign(&mut region, row, lhs, rhs)?;
+
+                        if let Some((tampered_row, value_inv, claimed)) = self.tampered_row {
+                            if tampered_row == row {
+                                region.assign_advice(
+                                    || "tampered value inv",
+                                    config.is_equal.is_zero.value_inv,
+                                    row,
+                                    || Value::known(value_inv),
+                                )?;
+                                output = region.assign_advice(
+                                    || "tampered output",
+                                    config.is_equal.output,
+                                    row,
+                                    || Value::known(claimed),
+                                )?;
+                            }
+                        }
+                        outputs.push(output);
+                    }
+                    Ok(outputs)
+                },
+            )?;
+
+            for (row, cell) in outputs.iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), config.instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn all_pairs() -> Vec<(Fp, Fp)> {
+        let values: Vec<Fp> = (0..8)
+            .map(Fp::from)
+            .chain([-Fp::one(), -Fp::from(2), Fp::TWO_INV])
+            .collect();
+        values
+            .iter()
+            .flat_map(|&lhs| values.iter().map(move |&rhs| (lhs, rhs)))
+            .collect()
+    }
+
+    #[test]
+    fn test_is_equal() {
+        let pairs = all_pairs();
+        let public_input = pairs.iter().map(|(lhs, rhs)| Fp::from(lhs == rhs)).collect();
+
+        let circuit = IsEqualTestCircuit {
+            pairs,
+            tampered_row: None,
+        };
+
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_is_equal_tampered_witness() {
+        let cases = [
+            // Claim that 5 == 3 by zeroing value_inv
+            ((Fp::from(5), Fp::from(3)), Fp::zero(), Fp::one()),
+            // Claim that 5 == 3 with a non-inverse value_inv
+            ((Fp::from(5), Fp::from(3)), Fp::from(7), Fp::one()),
+            // Claim that 4 != 4 with an arbitrary value_inv
+            ((Fp::from(4), Fp::from(4)), Fp::from(9), Fp::zero()),
+            // Claim that 5 != 3 with the right value_inv but a non-boolean output
+            ((Fp::from(5), Fp::from(3)), Fp::TWO_INV, Fp::from(2)),
+        ];
+
+        for (pair, value_inv, claimed) in cases {
+            let circuit = IsEqualTestCircuit {
+                pairs: vec![pair],
+                tampered_row: Some((0, value_inv, claimed)),
+            };
+
+            let k = minimal_k(&circuit);
+            let prover = MockProver::run(k, &circuit, vec![vec![claimed]]).unwrap();
+            assert_constraint_failure(&prover, "is_zero", "is equal checks");
+        }
+    }
+
+    #[test]
+    fn test_is_equal_mutations() {
+        // The inverse of the difference is free when the values are equal
+        let pairs = vec![(Fp::from(5), Fp::from(3)), (Fp::from(4), Fp::from(4))];
+        let public_input = pairs.iter().map(|(lhs, rhs)| Fp::from(lhs == rhs)).collect();
+        let circuit = IsEqualTestCircuit {
+            pairs,
+            tampered_row: None,
+        };
+        let unconstrained = unconstrained_cells(&circuit, vec![public_input]).unwrap();
+        let names: Vec<&str> = unconstrained
+            .iter()
+            .flat_map(UnconstrainedCells::names)
+            .collect();
+        assert_eq!(names, ["value inv"]);
+    }
+}
@@ -0,0 +1,265 @@
+use super::less_than::{LessThanChip, LessThanColumns, LessThanConfig};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, TableColumn, VirtualCells},
+    poly::Rotation,
+};
+
+#[derive(Clone, Debug)]
+pub struct IsInRangeConfig<F: FieldExt, const N_BYTES: usize> {
+    pub below: LessThanConfig<F, N_BYTES>,
+    pub above: LessThanConfig<F, N_BYTES>,
+    pub output: Column<Advice>,
+    pub in_range_expr: Expression<F>,
+}
+
+impl<F: FieldExt, const N_BYTES: usize> IsInRangeConfig<F, N_BYTES> {
+    pub fn expr(&self) -> Expression<F> {
+        self.in_range_expr.clone()
+    }
+}
+
+/// Checks `lower <= value <= upper` for values, lower and upper bounds
+/// which are known to be less than 2^(8*N_BYTES).
+pub struct IsInRangeChip<F: FieldExt, const N_BYTES: usize> {
+    config: IsInRangeConfig<F, N_BYTES>,
+}
+
+impl<F: FieldExt, const N_BYTES: usize> IsInRangeChip<F, N_BYTES> {
+    pub fn construct(config: IsInRangeConfig<F, N_BYTES>) -> Self {
+        IsInRangeChip { config }
+    }
+
+    /// The `q_enable` expression is used in lookups, so it cannot contain
+    /// a simple selector. Use `meta.complex_selector()` instead. The `below`
+    /// and `above` columns hold the comparisons `value < lower` and
+    /// `upper < value`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lower: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        upper: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        below: LessThanColumns<N_BYTES>,
+        above: LessThanColumns<N_BYTES>,
+        output: Column<Advice>,
+        u8_table: TableColumn,
+    ) -> IsInRangeConfig<F, N_BYTES> {
+        let below = LessThanChip::configure(meta, &q_enable, &value, lower, below, u8_table);
+        let above = LessThanChip::configure(meta, &q_enable, upper, &value, above, u8_table);
+
+        let one = Expression::Constant(F::one());
+        let in_range_expr = (one.clone() - below.expr()) * (one - above.expr());
+
+        meta.create_gate("is in range output", |meta| {
+            let q_enable = q_enable(meta);
+            let output = meta.query_advice(output, Rotation::cur());
+            vec![q_enable * (output - in_range_expr.clone())]
+        });
+
+        IsInRangeConfig {
+            below,
+            above,
+            output,
+            in_range_expr,
+        }
+    }
+
+    /// Both comparisons share the same byte table, so it is loaded only once.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        LessThanChip::construct(self.config.below.clone()).load(layouter)
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+        lower: Value<F>,
+        upper: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let below = LessThanChip::construct(self.config.below.clone())
+            .assign(region, offset, value, lower)?;
+        let above = LessThanChip::construct(self.config.above.clone())
+            .assign(region, offset, upper, value)?;
+
+        let output = below.value().zip(above.value()).map(|(below, above)| {
+            (F::one() - below) * (F::one() - above)
+        });
+        region.assign_advice(|| "lower <= value <= upper", self.config.output, offset, || output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_constraint_failure;
+    use crate::soundness::mutation::unconstrained_cells;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance, Selector},
+    };
+
+    #[derive(Clone, Debug)]
+    struct IsInRangeTestConfig {
+        q_enable: Selector,
+        value: Column<Advice>,
+        instance: Column<Instance>,
+        is_in_range: IsInRangeConfig<Fp, 1>,
+    }
+
+    // Checks membership of each value in the range [LOWER, UPPER]
+    #[derive(Default)]
+    struct IsInRangeTestCircuit<const LOWER: u64, const UPPER: u64> {
+        values: Vec<u64>,
+        // Row at which a malicious prover overwrites the output
+        tampered_row: Option<(usize, Fp)>,
+    }
+
+    impl<const LOWER: u64, const UPPER: u64> Circuit<Fp> for IsInRangeTestCircuit<LOWER, UPPER> {
+        type Config = IsInRangeTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let q_enable = meta.complex_selector();
+            let value = meta.advice_column();
+            let below = LessThanColumns::new(meta);
+            let above = LessThanColumns::new(meta);
+            let output = meta.advice_column();
+            let u8_table = meta.lookup_table_column();
+            let instance = meta.instance_column();
+
+            meta.enable_equality(output);
+            meta.enable_equality(instance);
+
+            let is_in_range = IsInRangeChip::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(value, Rotation::cur()),
+                |_| Expression::Constant(Fp::from(LOWER)),
+                |_| Expression::Constant(Fp::from(UPPER)),
+                below,
+                above,
+                output,
+                u8_table,
+            );
+
+            IsInRangeTestConfig {
+                q_enable,
+                value,
+                instance,
+                is_in_range,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = IsInRangeChip::construct(config.is_in_range.clone());
+            chip.load(&mut layouter)?;
+
+            let outputs = layouter.assign_region(
+                || "is in range checks",
+                |mut region| {
+                    let mut outputs = vec![];
+                    for (row, &value) in self.values.iter().enumerate() {
+                        config.q_enable.enable(&mut region, row)?;
+                        let value = Value::known(Fp::from(value));
+                        region.assign_advice(|| "value", config.value, row, || value)?;
+                        let mut output = chip.assign(
+                            &mut region,
+                            row,
+                            value,
+                            Value::known(Fp::from(LOWER)),
+                            Value::known(Fp::from(UPPER)),
+                        )?;
+
+                        if let Some((tampered_row, claimed)) = self.tampered_row {
+                            if tampered_row == row {
+                                output = region.assign_advice(
+                                    || "tampered output",
+                                    config.is_in_range.output,
+                                    row,
+                                    || Value::known(claimed),
+                                )?;
+                            }
+                        }
+                        outputs.push(output);
+                    }
+                    Ok(outputs)
+                },
+            )?;
+
+            for (row, cell) in outputs.iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), config.instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_is_in_range() {
+        let values: Vec<u64> = (0..256).collect();
+        let public_input = values
+            .iter()
+            .map(|value| Fp::from((10..=20).contains(value)))
+            .collect();
+
+        let circuit = IsInRangeTestCircuit::<10, 20> {
+            values,
+            tampered_row: None,
+        };
+
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_is_in_range_tampered_output() {
+        for (value, claimed) in [(9, Fp::one()), (10, Fp::zero()), (21, Fp::one()), (15, Fp::from(2))] {
+            let circuit = IsInRangeTestCircuit::<10, 20> {
+                values: vec![value],
+                tampered_row: Some((0, claimed)),
+            };
+
+            let k = minimal_k(&circuit);
+            let prover = MockProver::run(k, &circuit, vec![vec![claimed]]).unwrap();
+            assert_constraint_failure(&prover, "is in range output", "is in range checks");
+        }
+    }
+
+    #[test]
+    fn test_is_in_range_mutations() {
+        // The inverse of a carry is free when the carry is zero, i.e. when the
+        // value is below the lower bound or above the upper bound
+        let values = vec![9, 10, 15, 20, 21];
+        let public_input = values
+            .iter()
+            .map(|value| Fp::from((10..=20).contains(value)))
+            .collect();
+        let circuit = IsInRangeTestCircuit::<10, 20> {
+            values,
+            tampered_row: None,
+        };
+        let unconstrained = unconstrained_cells(&circuit, vec![public_input]).unwrap();
+        let cells: Vec<(&str, usize)> = unconstrained
+            .iter()
+            .flat_map(|cells| &cells.cells)
+            .map(|cell| (cell.name.as_str(), cell.offset))
+            .collect();
+        assert_eq!(cells, [("value inv", 0), ("value inv", 4)]);
+    }
+}
@@ -0,0 +1,364 @@
+use crate::is_zero_example::is_zero::{IsZeroChip, IsZeroConfig};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, TableColumn, VirtualCells},
+    poly::Rotation,
+};
+
+/// Advice columns of one `LessThanChip` comparison, supplied by the caller.
+#[derive(Clone, Copy, Debug)]
+pub struct LessThanColumns<const N_BYTES: usize> {
+    pub lt: Column<Advice>,
+    pub diff: [Column<Advice>; N_BYTES],
+    pub carry: Column<Advice>,
+    pub carry_inv: Column<Advice>,
+}
+
+impl<const N_BYTES: usize> LessThanColumns<N_BYTES> {
+    pub fn new<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        LessThanColumns {
+            lt: meta.advice_column(),
+            diff: [(); N_BYTES].map(|_| meta.advice_column()),
+            carry: meta.advice_column(),
+            carry_inv: meta.advice_column(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LessThanConfig<F: FieldExt, const N_BYTES: usize> {
+    pub lt: Column<Advice>,
+    pub diff: [Column<Advice>; N_BYTES],
+    pub carry: Column<Advice>,
+    pub is_zero: IsZeroConfig<F>,
+    pub u8_table: TableColumn,
+    pub range: F,
+}
+
+impl<F: FieldExt, const N_BYTES: usize> LessThanConfig<F, N_BYTES> {
+    pub fn expr(&self) -> Expression<F> {
+        self.is_zero.expr()
+    }
+}
+
+/// Checks `lhs < rhs` for values which are known to be less than 2^(8*N_BYTES).
+/// The shifted difference `lhs - rhs + 2^(8*N_BYTES)` is decomposed into
+/// `N_BYTES` bytes and a carry byte, all range checked using a lookup into a
+/// table of all bytes. The result is the `is_zero` gadget applied to the carry.
+pub struct LessThanChip<F: FieldExt, const N_BYTES: usize> {
+    config: LessThanConfig<F, N_BYTES>,
+}
+
+impl<F: FieldExt, const N_BYTES: usize> LessThanChip<F, N_BYTES> {
+    pub fn construct(config: LessThanConfig<F, N_BYTES>) -> Self {
+        LessThanChip { config }
+    }
+
+    /// The `q_enable` expression is used in lookups, so it cannot contain
+    /// a simple selector. Use `meta.complex_selector()` instead.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        rhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        columns: LessThanColumns<N_BYTES>,
+        u8_table: TableColumn,
+    ) -> LessThanConfig<F, N_BYTES> {
+        // The range 2^(8*N_BYTES) has to fit in the lower 128 bits for the witness computation
+        assert!(N_BYTES > 0 && N_BYTES <= 16);
+        let range = F::from(2).pow(&[8 * N_BYTES as u64, 0, 0, 0]);
+        let LessThanColumns {
+            lt,
+            diff,
+            carry,
+            carry_inv,
+        } = columns;
+
+        meta.create_gate("less than", |meta| {
+            //
+            // diff[0] ... diff[N_BYTES-1] | carry
+            // ----------------------------+-------
+            // bytes of lhs - rhs + 2^(8*N_BYTES), little-endian
+            //
+            // Both values are less than 2^(8*N_BYTES), so the shifted difference lies
+            // in [1, 2^(8*N_BYTES+1)) and its decomposition is unique: the carry is
+            // zero exactly when lhs < rhs.
+            let q_enable = q_enable(meta);
+            let lhs = lhs(meta);
+            let rhs = rhs(meta);
+            let carry = meta.query_advice(carry, Rotation::cur());
+            let shift = Expression::Constant(range);
+
+            let diff = diff.iter().rev().fold(Expression::Constant(F::zero()), |acc, col| {
+                acc * Expression::Constant(F::from(256)) + meta.query_advice(*col, Rotation::cur())
+            });
+
+            vec![q_enable * (lhs - rhs + shift.clone() - diff - carry * shift)]
+        });
+
+        for col in diff.into_iter().chain([carry]) {
+            meta.lookup(|meta| {
+                let q_enable = q_enable(meta);
+                let byte = meta.query_advice(col, Rotation::cur());
+                vec![(q_enable * byte, u8_table)]
+            });
+        }
+
+        let is_zero = IsZeroChip::configure_with_output(
+            meta,
+            &q_enable,
+            |meta| meta.query_advice(carry, Rotation::cur()),
+            carry_inv,
+            lt,
+        );
+
+        LessThanConfig {
+            lt,
+            diff,
+            carry,
+            is_zero,
+            u8_table,
+            range,
+        }
+    }
+
+    /// Loads the byte table. If several chips share the same `u8_table`,
+    /// only one of them should load it.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        load_u8_table(layouter, self.config.u8_table)
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Value<F>,
+        rhs: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let bytes = (lhs - rhs + Value::known(self.config.range)).map(|diff| diff.to_repr());
+        let byte = |idx: usize| bytes.as_ref().map(|bytes| F::from(bytes.as_ref()[idx] as u64));
+
+        for (idx, col) in self.config.diff.iter().enumerate() {
+            region.assign_advice(|| format!("diff byte {}", idx), *col, offset, || byte(idx))?;
+        }
+        let carry = byte(N_BYTES);
+        region.assign_advice(|| "carry", self.config.carry, offset, || carry)?;
+
+        let is_zero_chip = IsZeroChip::construct(self.config.is_zero.clone());
+        let lt = is_zero_chip.assign(region, offset, carry)?;
+        Ok(lt.expect("is_zero is configured with an output column"))
+    }
+}
+
+pub fn load_u8_table<F: FieldExt>(
+    layouter: &mut impl Layouter<F>,
+    u8_table: TableColumn,
+) -> Result<(), Error> {
+    layouter.assign_table(
+        || "u8 table",
+        |mut table| {
+            for byte in 0..256 {
+                table.assign_cell(
+                    || format!("byte {}", byte),
+                    u8_table,
+                    byte,
+                    || Value::known(F::from(byte as u64)),
+                )?;
+            }
+            Ok(())
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_constraint_failure;
+    use crate::soundness::mutation::unconstrained_cells;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance, Selector},
+    };
+
+    #[derive(Clone, Debug)]
+    struct LessThanTestConfig {
+        q_enable: Selector,
+        lhs: Column<Advice>,
+        rhs: Column<Advice>,
+        instance: Column<Instance>,
+        lt: LessThanConfig<Fp, 1>,
+    }
+
+    #[derive(Default)]
+    struct LessThanTestCircuit {
+        pairs: Vec<(u64, u64)>,
+        // Row at which a malicious prover flips the `lt` witness
+        flipped_row: Option<usize>,
+    }
+
+    impl Circuit<Fp> for LessThanTestCircuit {
+        type Config = LessThanTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let q_enable = meta.complex_selector();
+            let lhs = meta.advice_column();
+            let rhs = meta.advice_column();
+            let columns = LessThanColumns::new(meta);
+            let u8_table = meta.lookup_table_column();
+            let instance = meta.instance_column();
+
+            meta.enable_equality(columns.lt);
+            meta.enable_equality(instance);
+
+            let lt = LessThanChip::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(lhs, Rotation::cur()),
+                |meta| meta.query_advice(rhs, Rotation::cur()),
+                columns,
+                u8_table,
+            );
+
+            LessThanTestConfig {
+                q_enable,
+                lhs,
+                rhs,
+                instance,
+                lt,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = LessThanChip::construct(config.lt.clone());
+            chip.load(&mut layouter)?;
+
+            let lt_cells = layouter.assign_region(
+                || "less than checks",
+                |mut region| {
+                    let mut lt_cells = vec![];
+                    for (row, &(lhs, rhs)) in self.pairs.iter().enumerate() {
+                        config.q_enable.enable(&mut region, row)?;
+                        let lhs = Value::known(Fp::from(lhs));
+                        let rhs = Value::known(Fp::from(rhs));
+                        region.assign_advice(|| "lhs", config.lhs, row, || lhs)?;
+                        region.assign_advice(|| "rhs", config.rhs, row, || rhs)?;
+                        let mut lt_cell = chip.assign(&mut region, row, lhs, rhs)?;
+
+                        if self.flipped_row == Some(row) {
+                            // Overwrite the honest witness with the opposite result, the
+                            // carry matching it and the low byte of the remaining difference
+                            let lt = lt_cell.value().map(|lt| Fp::one() - lt);
+                            let carry = Value::known(Fp::one()) - lt;
+                            let diff = lhs - rhs + lt * Value::known(config.lt.range);
+                            region.assign_advice(
+                                || "flipped diff byte",
+                                config.lt.diff[0],
+                                row,
+                                || diff.map(|diff| Fp::from(diff.get_lower_128() as u8 as u64)),
+                            )?;
+                            region.assign_advice(|| "flipped carry", config.lt.carry, row, || carry)?;
+                            // The carry is boolean, so it is its own inverse
+                            region.assign_advice(
+                                || "flipped carry inv",
+                                config.lt.is_zero.value_inv,
+                                row,
+                                || carry,
+                            )?;
+                            lt_cell = region.assign_advice(|| "flipped lt", config.lt.lt, row, || lt)?;
+                        }
+                        lt_cells.push(lt_cell);
+                    }
+                    Ok(lt_cells)
+                },
+            )?;
+
+            for (row, cell) in lt_cells.iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), config.instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    // Small values together with values close to the top of the byte range
+    fn test_values() -> Vec<u64> {
+        (0..8).chain(248..256).collect()
+    }
+
+    fn all_pairs() -> Vec<(u64, u64)> {
+        let values = test_values();
+        values
+            .iter()
+            .flat_map(|&lhs| values.iter().map(move |&rhs| (lhs, rhs)))
+            .collect()
+    }
+
+    fn expected_output(pairs: &[(u64, u64)]) -> Vec<Fp> {
+        pairs.iter().map(|&(lhs, rhs)| Fp::from(lhs < rhs)).collect()
+    }
+
+    #[test]
+    fn test_less_than() {
+        let pairs = all_pairs();
+        let public_input = expected_output(&pairs);
+
+        let circuit = LessThanTestCircuit {
+            pairs,
+            flipped_row: None,
+        };
+
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_less_than_flipped_witness() {
+        // Rows where lhs < rhs, lhs == rhs and lhs > rhs
+        for pair in [(3, 250), (7, 7), (255, 0)] {
+            let pairs = vec![pair];
+            let mut public_input = expected_output(&pairs);
+            public_input[0] = Fp::one() - public_input[0];
+
+            let circuit = LessThanTestCircuit {
+                pairs,
+                flipped_row: Some(0),
+            };
+
+            let k = minimal_k(&circuit);
+            let prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+            assert_constraint_failure(&prover, "less than", "less than checks");
+        }
+    }
+
+    #[test]
+    fn test_less_than_mutations() {
+        // The inverse of the carry is free when the carry is zero, i.e. when lhs < rhs
+        let pairs = vec![(3, 5), (5, 3), (4, 4), (0, 255), (255, 0)];
+        let public_input = expected_output(&pairs);
+        let circuit = LessThanTestCircuit {
+            pairs,
+            flipped_row: None,
+        };
+        let unconstrained = unconstrained_cells(&circuit, vec![public_input]).unwrap();
+        let cells: Vec<(&str, usize)> = unconstrained
+            .iter()
+            .flat_map(|cells| &cells.cells)
+            .map(|cell| (cell.name.as_str(), cell.offset))
+            .collect();
+        assert_eq!(cells, [("value inv", 0), ("value inv", 3)]);
+    }
+}
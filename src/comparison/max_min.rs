@@ -0,0 +1,290 @@
+use super::less_than::{LessThanChip, LessThanColumns, LessThanConfig};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, TableColumn, VirtualCells},
+    poly::Rotation,
+};
+
+#[derive(Clone, Debug)]
+pub struct MaxMinConfig<F: FieldExt, const N_BYTES: usize> {
+    pub a_lt_b: LessThanConfig<F, N_BYTES>,
+    pub max: Column<Advice>,
+    pub min: Column<Advice>,
+    pub max_expr: Expression<F>,
+    pub min_expr: Expression<F>,
+}
+
+impl<F: FieldExt, const N_BYTES: usize> MaxMinConfig<F, N_BYTES> {
+    /// Boolean expression which is one when `a < b`
+    pub fn expr(&self) -> Expression<F> {
+        self.a_lt_b.expr()
+    }
+
+    pub fn max_expr(&self) -> Expression<F> {
+        self.max_expr.clone()
+    }
+
+    pub fn min_expr(&self) -> Expression<F> {
+        self.min_expr.clone()
+    }
+}
+
+/// Selects `max(a, b)` and `min(a, b)` for values which are known to be less
+/// than 2^(8*N_BYTES), using the result of the comparison `a < b`.
+pub struct MaxMinChip<F: FieldExt, const N_BYTES: usize> {
+    config: MaxMinConfig<F, N_BYTES>,
+}
+
+impl<F: FieldExt, const N_BYTES: usize> MaxMinChip<F, N_BYTES> {
+    pub fn construct(config: MaxMinConfig<F, N_BYTES>) -> Self {
+        MaxMinChip { config }
+    }
+
+    /// The `q_enable` expression is used in lookups, so it cannot contain
+    /// a simple selector. Use `meta.complex_selector()` instead.
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        a: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        b: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        a_lt_b: LessThanColumns<N_BYTES>,
+        max: Column<Advice>,
+        min: Column<Advice>,
+        u8_table: TableColumn,
+    ) -> MaxMinConfig<F, N_BYTES> {
+        let a_lt_b = LessThanChip::configure(meta, &q_enable, &a, &b, a_lt_b, u8_table);
+
+        let mut max_expr = Expression::Constant(F::zero());
+        let mut min_expr = Expression::Constant(F::zero());
+
+        meta.create_gate("max and min", |meta| {
+            //
+            // a < b | max | min
+            // ------+-----+-----
+            //   1   |  b  |  a
+            //   0   |  a  |  b
+            //
+            let q_enable = q_enable(meta);
+            let a = a(meta);
+            let b = b(meta);
+            let lt = a_lt_b.expr();
+            let not_lt = Expression::Constant(F::one()) - lt.clone();
+
+            max_expr = lt.clone() * b.clone() + not_lt.clone() * a.clone();
+            min_expr = lt * a + not_lt * b;
+
+            let max = meta.query_advice(max, Rotation::cur());
+            let min = meta.query_advice(min, Rotation::cur());
+            vec![
+                q_enable.clone() * (max - max_expr.clone()),
+                q_enable * (min - min_expr.clone()),
+            ]
+        });
+
+        MaxMinConfig {
+            a_lt_b,
+            max,
+            min,
+            max_expr,
+            min_expr,
+        }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        LessThanChip::construct(self.config.a_lt_b.clone()).load(layouter)
+    }
+
+    /// Returns the cells containing `max(a, b)` and `min(a, b)`.
+    #[allow(clippy::type_complexity)]
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        a: Value<F>,
+        b: Value<F>,
+    ) -> Result<(AssignedCell<F, F>, AssignedCell<F, F>), Error> {
+        let a_lt_b = LessThanChip::construct(self.config.a_lt_b.clone())
+            .assign(region, offset, a, b)?
+            .value()
+            .map(|lt| *lt == F::one());
+        let max = a_lt_b.zip(a.zip(b)).map(|(lt, (a, b))| if lt { b } else { a });
+        let min = a_lt_b.zip(a.zip(b)).map(|(lt, (a, b))| if lt { a } else { b });
+
+        let max = region.assign_advice(|| "max", self.config.max, offset, || max)?;
+        let min = region.assign_advice(|| "min", self.config.min, offset, || min)?;
+        Ok((max, min))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_constraint_failure;
+    use crate::soundness::mutation::unconstrained_cells;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance, Selector},
+    };
+
+    #[derive(Clone, Debug)]
+    struct MaxMinTestConfig {
+        q_enable: Selector,
+        a: Column<Advice>,
+        b: Column<Advice>,
+        instance: Column<Instance>,
+        max_min: MaxMinConfig<Fp, 1>,
+    }
+
+    #[derive(Default)]
+    struct MaxMinTestCircuit {
+        pairs: Vec<(u64, u64)>,
+        // Row at which a malicious prover swaps the max and min outputs
+        swapped_row: Option<usize>,
+    }
+
+    impl Circuit<Fp> for MaxMinTestCircuit {
+        type Config = MaxMinTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let q_enable = meta.complex_selector();
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let a_lt_b = LessThanColumns::new(meta);
+            let max = meta.advice_column();
+            let min = meta.advice_column();
+            let u8_table = meta.lookup_table_column();
+            let instance = meta.instance_column();
+
+            meta.enable_equality(max);
+            meta.enable_equality(min);
+            meta.enable_equality(instance);
+
+            let max_min = MaxMinChip::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(a, Rotation::cur()),
+                |meta| meta.query_advice(b, Rotation::cur()),
+                a_lt_b,
+                max,
+                min,
+                u8_table,
+            );
+
+            MaxMinTestConfig {
+                q_enable,
+                a,
+                b,
+                instance,
+                max_min,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = MaxMinChip::construct(config.max_min.clone());
+            chip.load(&mut layouter)?;
+
+            let outputs = layouter.assign_region(
+                || "max and min",
+                |mut region| {
+                    let mut outputs = vec![];
+                    for (row, &(a, b)) in self.pairs.iter().enumerate() {
+                        config.q_enable.enable(&mut region, row)?;
+                        let a = Value::known(Fp::from(a));
+                        let b = Value::known(Fp::from(b));
+                        region.assign_advice(|| "a", config.a, row, || a)?;
+                        region.assign_advice(|| "b", config.b, row, || b)?;
+                        let (mut max, mut min) = chip.assign(&mut region, row, a, b)?;
+
+                        if self.swapped_row == Some(row) {
+                            let (max_value, min_value) = (max.value().copied(), min.value().copied());
+                            max = region.assign_advice(|| "swapped max", config.max_min.max, row, || min_value)?;
+                            min = region.assign_advice(|| "swapped min", config.max_min.min, row, || max_value)?;
+                        }
+                        outputs.push(max);
+                        outputs.push(min);
+                    }
+                    Ok(outputs)
+                },
+            )?;
+
+            for (row, cell) in outputs.iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), config.instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_max_min() {
+        // Two public outputs per pair
+        let values: Vec<u64> = (0..8).chain(248..256).collect();
+        let pairs: Vec<(u64, u64)> = values
+            .iter()
+            .flat_map(|&a| values.iter().map(move |&b| (a, b)))
+            .collect();
+        let public_input = pairs
+            .iter()
+            .flat_map(|&(a, b)| [Fp::from(a.max(b)), Fp::from(a.min(b))])
+            .collect();
+
+        let circuit = MaxMinTestCircuit {
+            pairs,
+            swapped_row: None,
+        };
+
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_max_min_swapped_outputs() {
+        let (a, b) = (200, 17);
+
+        let circuit = MaxMinTestCircuit {
+            pairs: vec![(a, b)],
+            swapped_row: Some(0),
+        };
+
+        let public_input = vec![Fp::from(b), Fp::from(a)];
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+        assert_constraint_failure(&prover, "max and min", "max and min");
+    }
+
+    #[test]
+    fn test_max_min_mutations() {
+        // The inverse of the carry is free when the carry is zero, i.e. when a < b
+        let pairs = vec![(3, 5), (5, 3), (4, 4), (0, 255)];
+        let public_input = pairs
+            .iter()
+            .flat_map(|&(a, b)| [Fp::from(a.max(b)), Fp::from(a.min(b))])
+            .collect();
+        let circuit = MaxMinTestCircuit {
+            pairs,
+            swapped_row: None,
+        };
+        let unconstrained = unconstrained_cells(&circuit, vec![public_input]).unwrap();
+        let cells: Vec<(&str, usize)> = unconstrained
+            .iter()
+            .flat_map(|cells| &cells.cells)
+            .map(|cell| (cell.name.as_str(), cell.offset))
+            .collect();
+        assert_eq!(cells, [("value inv", 0), ("value inv", 3)]);
+    }
+}
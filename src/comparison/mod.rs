@@ -0,0 +1,4 @@
+pub mod is_equal;
+pub mod is_in_range;
+pub mod less_than;
+pub mod max_min;
@@ -0,0 +1,579 @@
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Circuit, ConstraintSystem},
+};
+use serde::{Deserialize, Serialize};
+
+use super::layout::{ColumnKind, LayoutColumn};
+use super::report::Counts;
+
+/// A value of the `Debug` output of a type deriving `Debug`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Node {
+    /// `Name { field: value, .. }`
+    Struct(String, Vec<(String, Node)>),
+    /// `Name(value, ..)`, or `(value, ..)` with an empty name
+    Tuple(String, Vec<Node>),
+    List(Vec<Node>),
+    Str(String),
+    /// A number, a field element, or a unit variant like `Advice` or `None`
+    Atom(String),
+}
+
+impl Node {
+    /// Parses the output of `{:?}`, which is the one of `{:#?}` without the
+    /// line breaks and trailing commas.
+    fn parse(debug: &str) -> Self {
+        let mut chars = debug.chars().peekable();
+        let node = Self::parse_value(&mut chars);
+        skip_whitespace(&mut chars);
+        assert_eq!(chars.next(), None, "trailing characters in {}", debug);
+        node
+    }
+
+    fn parse_value(chars: &mut Peekable<Chars<'_>>) -> Self {
+        skip_whitespace(chars);
+        match chars.peek() {
+            Some('"') => {
+                chars.next();
+                let mut string = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => return Node::Str(string),
+                        '\\' => string.push(chars.next().unwrap()),
+                        c => string.push(c),
+                    }
+                }
+                panic!("unterminated string");
+            }
+            Some('[') => {
+                chars.next();
+                Node::List(Self::parse_items(chars, ']'))
+            }
+            Some('(') => {
+                chars.next();
+                Node::Tuple(String::new(), Self::parse_items(chars, ')'))
+            }
+            _ => {
+                let mut atom = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_' || c == '-' || c == '.') {
+                        break;
+                    }
+                    atom.push(c);
+                    chars.next();
+                }
+                assert!(!atom.is_empty(), "unexpected {:?}", chars.peek());
+                skip_whitespace(chars);
+                match chars.peek() {
+                    Some('{') => {
+                        chars.next();
+                        Node::Struct(atom, Self::parse_fields(chars))
+                    }
+                    Some('(') => {
+                        chars.next();
+                        Node::Tuple(atom, Self::parse_items(chars, ')'))
+                    }
+                    _ => Node::Atom(atom),
+                }
+            }
+        }
+    }
+
+    fn parse_items(chars: &mut Peekable<Chars<'_>>, end: char) -> Vec<Self> {
+        let mut items = vec![];
+        loop {
+            skip_whitespace(chars);
+            if chars.peek() == Some(&end) {
+                chars.next();
+                return items;
+            }
+            items.push(Self::parse_value(chars));
+            skip_whitespace(chars);
+            if chars.peek() == Some(&',') {
+                chars.next();
+            }
+        }
+    }
+
+    fn parse_fields(chars: &mut Peekable<Chars<'_>>) -> Vec<(String, Self)> {
+        let mut fields = vec![];
+        loop {
+            skip_whitespace(chars);
+            if chars.peek() == Some(&'}') {
+                chars.next();
+                return fields;
+            }
+            let name: String = std::iter::from_fn(|| chars.next_if(|c| *c != ':')).collect();
+            assert_eq!(chars.next(), Some(':'));
+            fields.push((name.trim().to_string(), Self::parse_value(chars)));
+            skip_whitespace(chars);
+            if chars.peek() == Some(&',') {
+                chars.next();
+            }
+        }
+    }
+
+    fn field(&self, name: &str) -> &Self {
+        match self {
+            Node::Struct(_, fields) => fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value)
+                .unwrap_or_else(|| panic!("no field {} in {:?}", name, self)),
+            _ => panic!("{:?} is not a struct", self),
+        }
+    }
+
+    fn items(&self) -> &[Self] {
+        match self {
+            Node::List(items) | Node::Tuple(_, items) => items,
+            _ => panic!("{:?} is not a list", self),
+        }
+    }
+
+    fn atom(&self) -> &str {
+        match self {
+            Node::Atom(atom) => atom,
+            _ => panic!("{:?} is not an atom", self),
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&self) -> T {
+        self.atom()
+            .parse()
+            .unwrap_or_else(|_| panic!("{:?} is not a number", self))
+    }
+
+    fn string(&self) -> &str {
+        match self {
+            Node::Str(string) => string,
+            _ => panic!("{:?} is not a string", self),
+        }
+    }
+
+    /// `Column { index, column_type }`
+    fn column(&self) -> LayoutColumn {
+        let kind = match self.field("column_type").atom() {
+            "Advice" => ColumnKind::Advice,
+            "Fixed" => ColumnKind::Fixed,
+            "Instance" => ColumnKind::Instance,
+            column_type => panic!("unknown column type {}", column_type),
+        };
+        LayoutColumn {
+            kind,
+            index: self.field("index").number(),
+        }
+    }
+
+    /// `Selector(index, simple)`
+    fn selector(&self) -> usize {
+        self.items()[0].number()
+    }
+
+    /// `Rotation(rotation)`
+    fn rotation(&self) -> i32 {
+        self.items()[0].number()
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars<'_>>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+/// Formats a field element from its `Debug` output, i.e. its big-endian hex
+/// representation: the small values and their opposites in decimal, and the
+/// other values in hex.
+fn format_value<F: FieldExt>(hex: &str) -> String {
+    let digits = hex.strip_prefix("0x").unwrap_or(hex);
+    let value = digits.chars().fold(F::zero(), |value, digit| {
+        value * F::from(16) + F::from(digit.to_digit(16).unwrap() as u64)
+    });
+    let small = |value: F| {
+        let debug = format!("{:?}", value);
+        let digits = debug.trim_start_matches("0x").trim_start_matches('0');
+        match digits.len() {
+            0 => Some(0),
+            1..=16 => u64::from_str_radix(digits, 16).ok(),
+            _ => None,
+        }
+    };
+    match (small(value), small(-value)) {
+        (Some(value), _) => value.to_string(),
+        (None, Some(opposite)) => format!("-{}", opposite),
+        (None, None) => format!("0x{}", digits.trim_start_matches('0')),
+    }
+}
+
+/// A polynomial expression of a gate or a lookup.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Poly {
+    Constant(String),
+    Selector(usize),
+    Query { column: LayoutColumn, rotation: i32 },
+    Negated(Box<Poly>),
+    Sum(Box<Poly>, Box<Poly>),
+    Product(Box<Poly>, Box<Poly>),
+    Scaled(Box<Poly>, String),
+}
+
+impl Poly {
+    fn of<F: FieldExt>(node: &Node) -> Self {
+        let boxed = |node: &Node| Box::new(Self::of::<F>(node));
+        match node {
+            Node::Tuple(variant, items) => match variant.as_str() {
+                "Constant" => Poly::Constant(format_value::<F>(items[0].atom())),
+                "Selector" => Poly::Selector(items[0].selector()),
+                "Negated" => Poly::Negated(boxed(&items[0])),
+                "Sum" => Poly::Sum(boxed(&items[0]), boxed(&items[1])),
+                "Product" => Poly::Product(boxed(&items[0]), boxed(&items[1])),
+                "Scaled" => Poly::Scaled(boxed(&items[0]), format_value::<F>(items[1].atom())),
+                _ => panic!("unknown expression {:?}", node),
+            },
+            Node::Struct(variant, _) => {
+                let kind = match variant.as_str() {
+                    "Advice" => ColumnKind::Advice,
+                    "Fixed" => ColumnKind::Fixed,
+                    "Instance" => ColumnKind::Instance,
+                    _ => panic!("unknown expression {:?}", node),
+                };
+                Poly::Query {
+                    column: LayoutColumn {
+                        kind,
+                        index: node.field("column_index").number(),
+                    },
+                    rotation: node.field("rotation").rotation(),
+                }
+            }
+            _ => panic!("unknown expression {:?}", node),
+        }
+    }
+
+    /// The degree of the polynomial, with degree 1 for the selectors, as in
+    /// halo2_proofs.
+    pub fn degree(&self) -> usize {
+        match self {
+            Poly::Constant(_) => 0,
+            Poly::Selector(_) | Poly::Query { .. } => 1,
+            Poly::Negated(a) | Poly::Scaled(a, _) => a.degree(),
+            Poly::Sum(a, b) => a.degree().max(b.degree()),
+            Poly::Product(a, b) => a.degree() + b.degree(),
+        }
+    }
+
+    /// Writes the polynomial within an operation of the given precedence: 0
+    /// for the operands of a sum, 1 for those of a product and 2 for those
+    /// of a negation.
+    fn write(&self, f: &mut fmt::Formatter<'_>, precedence: u8) -> fmt::Result {
+        let parenthesized = match self {
+            Poly::Sum(..) | Poly::Negated(_) => precedence > 0,
+            Poly::Product(..) | Poly::Scaled(..) => precedence > 1,
+            Poly::Constant(value) => precedence > 0 && value.starts_with('-'),
+            _ => false,
+        };
+        if parenthesized {
+            write!(f, "(")?;
+        }
+        match self {
+            Poly::Constant(value) => write!(f, "{}", value)?,
+            Poly::Selector(index) => write!(f, "selector[{}]", index)?,
+            Poly::Query { column, rotation } => write!(f, "{}@{}", column, rotation)?,
+            Poly::Negated(a) => {
+                write!(f, "-")?;
+                a.write(f, 2)?;
+            }
+            Poly::Sum(a, b) => {
+                a.write(f, 0)?;
+                match b.as_ref() {
+                    Poly::Negated(b) => {
+                        write!(f, " - ")?;
+                        b.write(f, 1)?;
+                    }
+                    Poly::Constant(value) if value.starts_with('-') => {
+                        write!(f, " - {}", &value[1..])?;
+                    }
+                    b => {
+                        write!(f, " + ")?;
+                        b.write(f, 0)?;
+                    }
+                }
+            }
+            Poly::Product(a, b) => {
+                a.write(f, 1)?;
+                write!(f, " * ")?;
+                b.write(f, 1)?;
+            }
+            Poly::Scaled(a, factor) => {
+                a.write(f, 1)?;
+                if factor.starts_with('-') {
+                    write!(f, " * ({})", factor)?;
+                } else {
+                    write!(f, " * {}", factor)?;
+                }
+            }
+        }
+        if parenthesized {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Poly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+/// A cell queried by a gate, relative to the row where the gate is applied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Query {
+    pub column: LayoutColumn,
+    pub rotation: i32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GateConstraint {
+    /// The name of the constraint, which is empty when the gate does not
+    /// name its constraints
+    pub name: String,
+    pub poly: Poly,
+    pub degree: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Gate {
+    pub name: String,
+    pub constraints: Vec<GateConstraint>,
+    pub selectors: Vec<usize>,
+    pub queried_cells: Vec<Query>,
+}
+
+/// A lookup of the input expressions in the table expressions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lookup {
+    pub inputs: Vec<Poly>,
+    pub tables: Vec<Poly>,
+}
+
+/// The constraint system of a circuit: its columns, gates, lookups and
+/// equality constraints. halo2_proofs 0.2 keeps the fields of
+/// `ConstraintSystem` private, so they are read from its `Debug` output.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Constraints {
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    pub selectors: usize,
+    /// The maximum degree of the constraints, including the lookup and
+    /// permutation arguments
+    pub degree: usize,
+    pub gates: Vec<Gate>,
+    pub lookups: Vec<Lookup>,
+    /// The columns enabled for equality constraints
+    pub equality: Vec<LayoutColumn>,
+    /// The fixed columns enabled for constants
+    pub constants: Vec<LayoutColumn>,
+}
+
+impl Constraints {
+    pub fn of<F: FieldExt, ConcreteCircuit: Circuit<F>>() -> Self {
+        let mut cs = ConstraintSystem::<F>::default();
+        ConcreteCircuit::configure(&mut cs);
+        let counts = Counts::of(&cs);
+        let node = Node::parse(&format!("{:?}", cs));
+        let polys = |node: &Node| node.items().iter().map(Poly::of::<F>).collect();
+        let columns = |node: &Node| node.items().iter().map(Node::column).collect();
+
+        let gates = node
+            .field("gates")
+            .items()
+            .iter()
+            .map(|gate| Gate {
+                name: gate.field("name").string().to_string(),
+                constraints: gate
+                    .field("constraint_names")
+                    .items()
+                    .iter()
+                    .zip(gate.field("polys").items())
+                    .map(|(name, poly)| {
+                        let poly = Poly::of::<F>(poly);
+                        GateConstraint {
+                            name: name.string().to_string(),
+                            degree: poly.degree(),
+                            poly,
+                        }
+                    })
+                    .collect(),
+                selectors: gate
+                    .field("queried_selectors")
+                    .items()
+                    .iter()
+                    .map(Node::selector)
+                    .collect(),
+                // halo2_proofs records a cell each time it is queried
+                queried_cells: gate.field("queried_cells").items().iter().fold(
+                    vec![],
+                    |mut queries, cell| {
+                        let query = Query {
+                            column: cell.field("column").column(),
+                            rotation: cell.field("rotation").rotation(),
+                        };
+                        if !queries.contains(&query) {
+                            queries.push(query);
+                        }
+                        queries
+                    },
+                ),
+            })
+            .collect();
+        let lookups = node
+            .field("lookups")
+            .items()
+            .iter()
+            .map(|lookup| Lookup {
+                inputs: polys(lookup.field("input_expressions")),
+                tables: polys(lookup.field("table_expressions")),
+            })
+            .collect();
+
+        Self {
+            advice_columns: counts.advice_columns,
+            fixed_columns: counts.fixed_columns,
+            instance_columns: counts.instance_columns,
+            selectors: counts.selectors,
+            degree: cs.degree(),
+            gates,
+            lookups,
+            equality: columns(node.field("permutation").field("columns")),
+            constants: columns(node.field("constants")),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+fn join<T: fmt::Display>(items: impl IntoIterator<Item = T>) -> String {
+    let items: Vec<String> = items.into_iter().map(|item| item.to_string()).collect();
+    if items.is_empty() {
+        "none".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+impl fmt::Display for Constraints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "columns: {} advice, {} fixed, {} instance, {} selectors",
+            self.advice_columns, self.fixed_columns, self.instance_columns, self.selectors
+        )?;
+        writeln!(f, "degree: {}", self.degree)?;
+        for gate in &self.gates {
+            writeln!(f, "\ngate {:?}:", gate.name)?;
+            writeln!(
+                f,
+                "  selectors: {}",
+                join(
+                    gate.selectors
+                        .iter()
+                        .map(|index| format!("selector[{}]", index))
+                )
+            )?;
+            writeln!(
+                f,
+                "  queries: {}",
+                join(
+                    gate.queried_cells
+                        .iter()
+                        .map(|query| format!("{}@{}", query.column, query.rotation))
+                )
+            )?;
+            for constraint in &gate.constraints {
+                if constraint.name.is_empty() {
+                    writeln!(f, "  - (degree {}) {}", constraint.degree, constraint.poly)?;
+                } else {
+                    writeln!(
+                        f,
+                        "  - {} (degree {}): {}",
+                        constraint.name, constraint.degree, constraint.poly
+                    )?;
+                }
+            }
+        }
+        for (i, lookup) in self.lookups.iter().enumerate() {
+            writeln!(f, "\nlookup {}:", i)?;
+            for (input, table) in lookup.inputs.iter().zip(&lookup.tables) {
+                writeln!(f, "  - {} in {}", input, table)?;
+            }
+        }
+        writeln!(f, "\nequality: {}", join(&self.equality))?;
+        writeln!(f, "constants: {}", join(&self.constants))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_example::simple_example::MyCircuit;
+    use halo2_proofs::pasta::Fp;
+
+    #[test]
+    fn test_parse_debug() {
+        let node = Node::parse(r#"A { b: [1, Some("x \"y\"")], c: (Rotation(-1), None) }"#);
+        assert_eq!(node.field("b").items()[0].number::<usize>(), 1);
+        assert_eq!(node.field("b").items()[1].items()[0].string(), r#"x "y""#);
+        assert_eq!(node.field("c").items()[0].rotation(), -1);
+        assert_eq!(node.field("c").items()[1].atom(), "None");
+    }
+
+    #[test]
+    fn test_format_value() {
+        let hex = |value: Fp| format!("{:?}", value);
+        assert_eq!(format_value::<Fp>(&hex(Fp::zero())), "0");
+        assert_eq!(format_value::<Fp>(&hex(Fp::from(5))), "5");
+        assert_eq!(format_value::<Fp>(&hex(-Fp::from(5))), "-5");
+        assert_eq!(
+            format_value::<Fp>(&hex(Fp::from(1 << 40).pow(&[2, 0, 0, 0]))),
+            "0x100000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_simple_example_constraints() {
+        let constraints = Constraints::of::<Fp, MyCircuit<Fp>>();
+        assert_eq!(constraints.gates.len(), 1);
+
+        let gate = &constraints.gates[0];
+        assert_eq!(gate.name, "mul");
+        assert_eq!(gate.selectors, [0]);
+        assert_eq!(
+            gate.constraints[0].poly.to_string(),
+            "selector[0] * (advice[0]@0 * advice[1]@0 - advice[0]@1)"
+        );
+        assert_eq!(gate.constraints[0].degree, 3);
+        let rotations: Vec<_> = gate.queried_cells.iter().map(|q| q.rotation).collect();
+        assert_eq!(rotations, [0, 0, 1]);
+
+        assert_eq!(constraints.equality.len(), 4);
+        assert_eq!(
+            constraints.constants,
+            [LayoutColumn {
+                kind: ColumnKind::Fixed,
+                index: 0
+            }]
+        );
+        assert_eq!(
+            serde_json::from_str::<Constraints>(&constraints.to_json()).unwrap(),
+            constraints
+        );
+    }
+}
@@ -0,0 +1,575 @@
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    circuit::Value,
+    pasta::{pallas, vesta, EqAffine, Fp, Fq},
+    plonk::Circuit,
+};
+use pasta_curves::group::{prime::PrimeCurveAffine, Curve};
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+
+use super::constraints::Constraints;
+use super::layout::Layout;
+use super::report::{measure, CircuitReport};
+use crate::collatz::collatz::CollatzCircuit;
+use crate::ecc::schnorr::{keygen, sign, SchnorrCircuit};
+use crate::fibonacci::{fib_one_column, fib_three_column};
+use crate::fibonacci_squares::fib_squares_three_column;
+use crate::is_zero_example::conditional_calc::FunctionCircuit;
+use crate::merkle::nullifier::{leaf, NullifierCircuit};
+use crate::merkle::tree::MerkleTree;
+use crate::mimc::mimc_cipher::{MiMC5CipherPallasCircuit, MiMC5CipherVestaCircuit};
+use crate::mimc::mimc_hash::{MiMC5HashPallasCircuit, MiMC5HashVestaCircuit};
+use crate::mimc::primitives::{mimc5_encrypt, mimc5_hash};
+use crate::mimc::round_constants::{
+    MIMC_HASH_PALLAS_ROUND_CONSTANTS, MIMC_HASH_VESTA_ROUND_CONSTANTS,
+    NUM_ROUNDS as MIMC_NUM_ROUNDS,
+};
+use crate::mimc_pallas::mimc::MiMC5Circuit;
+use crate::mimc_pallas::mimc_hash::{MiMC5HashCircuit, MiMC5HashPublicInputs};
+use crate::mimc_pallas::pallas_round_constants::{NUM_ROUNDS, ROUND_CONSTANTS};
+use crate::proving::public_inputs::PublicInputs;
+use crate::recursion::cycle::FoldCircuit;
+use crate::simple_example::simple_example;
+
+/// Depth of the Merkle tree of the nullifier example.
+const NULLIFIER_DEPTH: usize = 8;
+
+/// Visits the example circuits with the witnesses of their tests, e.g. to
+/// measure them or to export their layout.
+pub trait ExampleVisitor {
+    /// Visits a circuit over the scalar field of `C`, which is the curve of
+    /// its commitment scheme.
+    fn visit<C, ConcreteCircuit>(&mut self, name: &str, circuit: &ConcreteCircuit)
+    where
+        C: CurveAffine,
+        ConcreteCircuit: Circuit<C::Scalar>;
+}
+
+/// Visits batches of circuits which share their proving key, e.g. to
+/// measure the throughput of `BatchProver`.
+pub trait BatchVisitor {
+    /// Visits circuits over the scalar field of `C` which differ only in
+    /// their witnesses, with the values of their instance columns.
+    fn visit_batch<C, ConcreteCircuit>(
+        &mut self,
+        name: &str,
+        batch: Vec<(ConcreteCircuit, Vec<Vec<C::Scalar>>)>,
+    ) where
+        C: CurveAffine,
+        ConcreteCircuit: Circuit<C::Scalar> + Sync;
+}
+
+/// Visits every example circuit. The circuits over `Fp` are visited with
+/// commitments over Vesta, and those over `Fq` with commitments over Pallas.
+/// The witnesses are the same on every call: the Schnorr key and signature
+/// are drawn from a seeded RNG.
+pub fn visit_examples(visitor: &mut impl ExampleVisitor) {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let (sk, pk) = keygen(&mut rng);
+    let message = Fp::from(42);
+    let tree = MerkleTree::new(NULLIFIER_DEPTH, &[leaf(Fp::from(7))]);
+
+    visitor.visit::<EqAffine, _>(
+        "fib_three_column",
+        &fib_three_column::MyCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        },
+    );
+    visitor.visit::<EqAffine, _>(
+        "fib_one_column",
+        &fib_one_column::MyCircuit::<Fp>::default(),
+    );
+    visitor.visit::<EqAffine, _>(
+        "fib_squares_three_column",
+        &fib_squares_three_column::MyCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        },
+    );
+    visitor.visit::<EqAffine, _>(
+        "collatz",
+        &CollatzCircuit::<Fp, 6> {
+            initial_value: Fp::from(52),
+            nrows: 12,
+        },
+    );
+    visitor.visit::<EqAffine, _>(
+        "simple_example",
+        &simple_example::MyCircuit {
+            constant: Fp::from(7),
+            a: Value::known(Fp::from(2)),
+            b: Value::known(Fp::from(3)),
+        },
+    );
+    visitor.visit::<EqAffine, _>(
+        "conditional_calc",
+        &FunctionCircuit {
+            a: Fp::from(12),
+            b: Fp::from(12),
+            c: Fp::from(15),
+        },
+    );
+    visitor.visit::<EqAffine, _>(
+        "mimc_pallas::mimc",
+        &MiMC5Circuit {
+            message: Fp::zero(),
+            key: Fp::zero(),
+            num_rounds: NUM_ROUNDS,
+            round_constants: ROUND_CONSTANTS.to_vec(),
+        },
+    );
+    visitor.visit::<EqAffine, _>(
+        "mimc_pallas::mimc_hash",
+        &MiMC5HashCircuit {
+            message: Fp::zero(),
+            num_rounds: NUM_ROUNDS,
+            round_constants: ROUND_CONSTANTS.to_vec(),
+        },
+    );
+    visit_mimc(visitor, |circuit, curve| {
+        format!("mimc::{}::{}", circuit, curve)
+    });
+    visitor.visit::<EqAffine, _>(
+        "schnorr",
+        &SchnorrCircuit {
+            pk: Value::known(pk),
+            message: Value::known(message),
+            signature: Value::known(sign(sk, message, &mut rng)),
+        },
+    );
+    visitor.visit::<EqAffine, _>(
+        "nullifier",
+        &NullifierCircuit::<NULLIFIER_DEPTH> {
+            secret: Value::known(Fp::from(7)),
+            epoch: Value::known(Fp::one()),
+            path: Value::known(tree.path(0)),
+        },
+    );
+    visitor.visit::<pallas::Affine, _>(
+        "recursion::cycle",
+        &FoldCircuit {
+            a: Value::known(vesta::Affine::generator()),
+            b: Value::known((vesta::Affine::generator() * vesta::Scalar::from(2)).to_affine()),
+        },
+    );
+}
+
+/// Visits the MiMC cipher and hash circuits of `mimc` over Pallas and Vesta,
+/// named by `name(circuit, curve)`.
+fn visit_mimc(visitor: &mut impl ExampleVisitor, name: impl Fn(&str, &str) -> String) {
+    let (message, key) = (Fp::from(42), Fp::from(7));
+    let (mut ciphertext, mut digest) = (message, message);
+    mimc5_encrypt::<_, MIMC_NUM_ROUNDS>(&mut ciphertext, key, MIMC_HASH_PALLAS_ROUND_CONSTANTS);
+    mimc5_hash::<_, MIMC_NUM_ROUNDS>(&mut digest, MIMC_HASH_PALLAS_ROUND_CONSTANTS);
+    visitor.visit::<EqAffine, _>(
+        &name("mimc_cipher", "pallas"),
+        &MiMC5CipherPallasCircuit {
+            message,
+            key,
+            ciphertext,
+        },
+    );
+    visitor.visit::<EqAffine, _>(
+        &name("mimc_hash", "pallas"),
+        &MiMC5HashPallasCircuit {
+            message,
+            message_hash: digest,
+        },
+    );
+
+    let (message, key) = (Fq::from(42), Fq::from(7));
+    let (mut ciphertext, mut digest) = (message, message);
+    mimc5_encrypt::<_, MIMC_NUM_ROUNDS>(&mut ciphertext, key, MIMC_HASH_VESTA_ROUND_CONSTANTS);
+    mimc5_hash::<_, MIMC_NUM_ROUNDS>(&mut digest, MIMC_HASH_VESTA_ROUND_CONSTANTS);
+    visitor.visit::<pallas::Affine, _>(
+        &name("mimc_cipher", "vesta"),
+        &MiMC5CipherVestaCircuit {
+            message,
+            key,
+            ciphertext,
+        },
+    );
+    visitor.visit::<pallas::Affine, _>(
+        &name("mimc_hash", "vesta"),
+        &MiMC5HashVestaCircuit {
+            message,
+            message_hash: digest,
+        },
+    );
+}
+
+/// Numbers of rows of the Fibonacci tables of the benchmarks.
+const FIBONACCI_ROWS: [usize; 3] = [16, 128, 1024];
+
+/// The length of the Collatz sequence from `n` down to 1.
+fn collatz_length(mut n: u64) -> usize {
+    let mut length = 1;
+    while n != 1 {
+        n = if n.is_multiple_of(2) {
+            n / 2
+        } else {
+            3 * n + 1
+        };
+        length += 1;
+    }
+    length
+}
+
+/// Visits the circuits of the benchmarks in `benches/`: the Fibonacci table
+/// with a growing number of rows, Collatz sequences of different bit widths
+/// and lengths, the MiMC cipher and hash over Pallas and Vesta, and the
+/// simple example. The names of the circuits give their parameters, e.g.
+/// `fib_one_column/1024`.
+pub fn visit_benchmarks(visitor: &mut impl ExampleVisitor) {
+    for nrows in FIBONACCI_ROWS {
+        visitor.visit::<EqAffine, _>(
+            &format!("fib_one_column/{}", nrows),
+            &fib_one_column::MyCircuit::<Fp>::new(nrows),
+        );
+    }
+
+    // The largest values of the sequences from 27 and 871 take 14 and 18 bits
+    fn collatz<const WIDTH: usize>(visitor: &mut impl ExampleVisitor, initial_value: u64) {
+        let nrows = collatz_length(initial_value);
+        visitor.visit::<EqAffine, _>(
+            &format!("collatz/width {}/length {}", WIDTH, nrows),
+            &CollatzCircuit::<Fp, WIDTH> {
+                initial_value: Fp::from(initial_value),
+                nrows,
+            },
+        );
+    }
+    collatz::<16>(visitor, 7);
+    collatz::<16>(visitor, 27);
+    collatz::<32>(visitor, 27);
+    collatz::<32>(visitor, 871);
+
+    visit_mimc(visitor, |circuit, curve| format!("{}/{}", circuit, curve));
+
+    visitor.visit::<EqAffine, _>(
+        "simple_example",
+        &simple_example::MyCircuit {
+            constant: Fp::from(7),
+            a: Value::known(Fp::from(2)),
+            b: Value::known(Fp::from(3)),
+        },
+    );
+}
+
+/// Number of circuits in the batches of the benchmarks.
+const BATCH_SIZE: u64 = 8;
+
+/// Visits the batches of the benchmarks in `benches/`: the hashes of
+/// different messages with `mimc_pallas::mimc_hash`, whose message and
+/// digest are public.
+pub fn visit_batch_benchmarks(visitor: &mut impl BatchVisitor) {
+    let batch: Vec<_> = (1..=BATCH_SIZE)
+        .map(|message| {
+            let message = Fp::from(message);
+            let mut output = message;
+            mimc5_hash::<_, NUM_ROUNDS>(&mut output, ROUND_CONSTANTS);
+            let circuit = MiMC5HashCircuit {
+                message,
+                num_rounds: NUM_ROUNDS,
+                round_constants: ROUND_CONSTANTS.to_vec(),
+            };
+            let public_inputs = MiMC5HashPublicInputs { message, output };
+            (circuit, public_inputs.to_instances())
+        })
+        .collect();
+    visitor.visit_batch::<EqAffine, _>("mimc_pallas::mimc_hash/batch", batch);
+}
+
+/// Measures the example circuits. With `prove`, a proof of each circuit is
+/// also created to measure the prover time.
+pub fn examples(prove: bool) -> Vec<CircuitReport> {
+    struct Measure {
+        prove: bool,
+        reports: Vec<CircuitReport>,
+    }
+
+    impl ExampleVisitor for Measure {
+        fn visit<C, ConcreteCircuit>(&mut self, name: &str, circuit: &ConcreteCircuit)
+        where
+            C: CurveAffine,
+            ConcreteCircuit: Circuit<C::Scalar>,
+        {
+            self.reports
+                .push(measure::<C, _>(name, circuit, self.prove));
+        }
+    }
+
+    let mut visitor = Measure {
+        prove,
+        reports: vec![],
+    };
+    visit_examples(&mut visitor);
+    visitor.reports
+}
+
+/// Returns the layouts of the example circuits with their names.
+pub fn layouts() -> Vec<(String, Layout)> {
+    struct Layouts(Vec<(String, Layout)>);
+
+    impl ExampleVisitor for Layouts {
+        fn visit<C, ConcreteCircuit>(&mut self, name: &str, circuit: &ConcreteCircuit)
+        where
+            C: CurveAffine,
+            ConcreteCircuit: Circuit<C::Scalar>,
+        {
+            self.0.push((name.to_string(), Layout::of(circuit)));
+        }
+    }
+
+    let mut visitor = Layouts(vec![]);
+    visit_examples(&mut visitor);
+    visitor.0
+}
+
+/// Returns the constraint systems of the example circuits with their names.
+pub fn constraint_systems() -> Vec<(String, Constraints)> {
+    struct ConstraintSystems(Vec<(String, Constraints)>);
+
+    impl ExampleVisitor for ConstraintSystems {
+        fn visit<C, ConcreteCircuit>(&mut self, name: &str, _: &ConcreteCircuit)
+        where
+            C: CurveAffine,
+            ConcreteCircuit: Circuit<C::Scalar>,
+        {
+            self.0.push((
+                name.to_string(),
+                Constraints::of::<C::Scalar, ConcreteCircuit>(),
+            ));
+        }
+    }
+
+    let mut visitor = ConstraintSystems(vec![]);
+    visit_examples(&mut visitor);
+    visitor.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::report::CostTable;
+    use crate::cost::rows::minimal_k;
+    use crate::proving::batch::verify;
+    use crate::proving::prover::{prove, ProofRng};
+    use crate::soundness::mutation::witness_instances;
+    use halo2_proofs::{
+        dev::MockProver,
+        plonk::{keygen_pk, keygen_vk},
+        poly::commitment::Params,
+    };
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn test_cost_report() {
+        let reports = examples(false);
+        let report = |name: &str| reports.iter().find(|report| report.name == name).unwrap();
+
+        let fib = report("fib_three_column");
+        assert_eq!(fib.k, 4);
+        assert_eq!(
+            (fib.advice_columns, fib.fixed_columns, fib.instance_columns),
+            (3, 0, 1)
+        );
+        assert_eq!(fib.lookups, 0);
+        assert_eq!(fib.permutation_columns, 4);
+
+        // The MiMC examples have 110 rounds, one per row
+        assert_eq!(report("mimc_pallas::mimc").k, 7);
+        assert_eq!(report("mimc_pallas::mimc_hash").k, 7);
+        assert_eq!(report("mimc::mimc_cipher::pallas").k, 7);
+        assert_eq!(report("mimc::mimc_hash::vesta").k, 7);
+        // ... and Collatz is much smaller than the k = 16 of its test
+        assert!(report("collatz").k < 16);
+
+        // The byte decomposition of the challenge uses a lookup
+        assert!(report("schnorr").lookups > 0);
+
+        for report in &reports {
+            assert!(report.degree >= 3, "{}", report.name);
+            assert_eq!(
+                report.proof_size.is_some(),
+                report.permutation_columns > 0,
+                "{}",
+                report.name
+            );
+            assert!(report.prover_time.is_none());
+        }
+    }
+
+    #[test]
+    fn test_cost_prover_time() {
+        let report = measure::<EqAffine, _>(
+            "fib_three_column",
+            &fib_three_column::MyCircuit {
+                a: Value::known(Fp::one()),
+                b: Value::known(Fp::one()),
+            },
+            true,
+        );
+        assert!(report.prover_time.is_some());
+        assert!(CostTable(&[report])
+            .to_string()
+            .contains("fib_three_column"));
+    }
+
+    #[test]
+    fn test_example_layouts() {
+        let layouts = layouts();
+        assert_eq!(layouts.len(), examples(false).len());
+
+        for (name, layout) in &layouts {
+            let cells = layout
+                .regions
+                .iter()
+                .flat_map(|region| &region.cells)
+                .chain(&layout.cells);
+            for cell in cells {
+                assert!(cell.row < layout.usable_rows, "{}", name);
+                assert!(layout.columns.contains(&cell.column), "{}", name);
+            }
+        }
+
+        let (_, mimc) = layouts
+            .iter()
+            .find(|(name, _)| name == "mimc_pallas::mimc")
+            .unwrap();
+        assert!(mimc
+            .regions
+            .iter()
+            .any(|region| region.name == "MiMC5 table"));
+    }
+
+    /// The benchmarks prove the circuits with the instance values of their
+    /// witnesses, which must satisfy them.
+    #[test]
+    fn test_benchmark_circuits() {
+        struct Check(Vec<String>);
+
+        impl BatchVisitor for Check {
+            fn visit_batch<C, ConcreteCircuit>(
+                &mut self,
+                name: &str,
+                batch: Vec<(ConcreteCircuit, Vec<Vec<C::Scalar>>)>,
+            ) where
+                C: CurveAffine,
+                ConcreteCircuit: Circuit<C::Scalar> + Sync,
+            {
+                for (circuit, instances) in &batch {
+                    let prover =
+                        MockProver::run(minimal_k(circuit), circuit, instances.clone()).unwrap();
+                    assert_eq!(prover.verify(), Ok(()), "{}", name);
+                }
+                // The circuits have different witnesses
+                assert!(batch[1..].iter().all(|(_, other)| *other != batch[0].1));
+                self.0.push(name.to_string());
+            }
+        }
+
+        impl ExampleVisitor for Check {
+            fn visit<C, ConcreteCircuit>(&mut self, name: &str, circuit: &ConcreteCircuit)
+            where
+                C: CurveAffine,
+                ConcreteCircuit: Circuit<C::Scalar>,
+            {
+                let instances = witness_instances(circuit).unwrap();
+                let prover = MockProver::run(minimal_k(circuit), circuit, instances).unwrap();
+                assert_eq!(prover.verify(), Ok(()), "{}", name);
+                self.0.push(name.to_string());
+            }
+        }
+
+        let mut visitor = Check(vec![]);
+        visit_benchmarks(&mut visitor);
+        assert!(visitor.0.contains(&"fib_one_column/1024".to_string()));
+        assert!(visitor
+            .0
+            .contains(&"collatz/width 32/length 179".to_string()));
+        assert!(visitor.0.contains(&"mimc_cipher/vesta".to_string()));
+
+        visit_batch_benchmarks(&mut visitor);
+        assert!(visitor
+            .0
+            .contains(&"mimc_pallas::mimc_hash/batch".to_string()));
+    }
+
+    /// Compares a proof of each example, created with `ProofRng::Seeded`, with
+    /// its golden file in `testcases/proofs`, in hex. The proofs change with
+    /// the circuits, their witnesses and the version of `halo2_proofs`; set
+    /// `UPDATE_SNAPSHOTS=1` to write them after such a change.
+    #[test]
+    fn test_proof_snapshots() {
+        struct Proofs(Vec<(String, Vec<u8>)>);
+
+        impl ExampleVisitor for Proofs {
+            fn visit<C, ConcreteCircuit>(&mut self, name: &str, circuit: &ConcreteCircuit)
+            where
+                C: CurveAffine,
+                ConcreteCircuit: Circuit<C::Scalar>,
+            {
+                let params: Params<C> = Params::new(minimal_k(circuit));
+                let vk = keygen_vk(&params, circuit).unwrap();
+                let pk = keygen_pk(&params, vk.clone(), circuit).unwrap();
+                let instances = witness_instances(circuit).unwrap();
+                let proof = prove(&params, &pk, circuit, &instances, ProofRng::Seeded(0)).unwrap();
+                assert!(verify(&params, &vk, &proof, &instances).is_ok(), "{}", name);
+                self.0.push((name.to_string(), proof));
+            }
+        }
+
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("testcases/proofs");
+        let update = std::env::var("UPDATE_SNAPSHOTS").is_ok();
+
+        let mut visitor = Proofs(vec![]);
+        visit_examples(&mut visitor);
+        for (name, proof) in visitor.0 {
+            let path = dir.join(format!("{}.hex", name.replace("::", "-")));
+            let golden = format!("{}\n", hex::encode(&proof));
+            if update {
+                fs::create_dir_all(&dir).unwrap();
+                fs::write(&path, &golden).unwrap();
+            }
+            let snapshot = fs::read_to_string(&path)
+                .unwrap_or_else(|_| panic!("no golden proof {}", path.display()));
+            assert!(
+                golden == snapshot,
+                "the proof of {} differs from {}",
+                name,
+                path.display()
+            );
+        }
+    }
+
+    /// Compares the constraint system of each example with its snapshot in
+    /// `testcases/constraints`. Set `UPDATE_SNAPSHOTS=1` to write the
+    /// snapshots after changing a circuit.
+    #[test]
+    fn test_constraint_snapshots() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("testcases/constraints");
+        let update = std::env::var("UPDATE_SNAPSHOTS").is_ok();
+
+        for (name, constraints) in constraint_systems() {
+            let path = dir.join(format!("{}.txt", name.replace("::", "-")));
+            let dump = constraints.to_string();
+            if update {
+                fs::create_dir_all(&dir).unwrap();
+                fs::write(&path, &dump).unwrap();
+            }
+            let snapshot = fs::read_to_string(&path)
+                .unwrap_or_else(|_| panic!("no snapshot {}", path.display()));
+            assert!(
+                dump == snapshot,
+                "the constraint system of {} differs from {}:\n{}",
+                name,
+                path.display(),
+                dump
+            );
+        }
+    }
+}
@@ -0,0 +1,497 @@
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Value,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Selector,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use super::report::Counts;
+use super::rows::{constant_columns, minimal_k};
+
+/// The kinds of columns, in the order in which they are drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnKind {
+    Instance,
+    Advice,
+    Fixed,
+    Selector,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct LayoutColumn {
+    pub kind: ColumnKind,
+    pub index: usize,
+}
+
+impl LayoutColumn {
+    /// Reads the index of a column from its `Debug` output, since
+    /// halo2_proofs 0.2 keeps it private.
+    pub(crate) fn of(column: Column<Any>) -> Self {
+        let debug = format!("{:?}", column);
+        let kind = match column.column_type() {
+            Any::Advice => ColumnKind::Advice,
+            Any::Fixed => ColumnKind::Fixed,
+            Any::Instance => ColumnKind::Instance,
+        };
+        Self {
+            kind,
+            index: parse_index(&debug, "index: "),
+        }
+    }
+
+    fn selector(selector: &Selector) -> Self {
+        Self {
+            kind: ColumnKind::Selector,
+            index: parse_index(&format!("{:?}", selector), "Selector("),
+        }
+    }
+}
+
+impl fmt::Display for LayoutColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            ColumnKind::Instance => "instance",
+            ColumnKind::Advice => "advice",
+            ColumnKind::Fixed => "fixed",
+            ColumnKind::Selector => "selector",
+        };
+        write!(f, "{}[{}]", kind, self.index)
+    }
+}
+
+fn parse_index(debug: &str, prefix: &str) -> usize {
+    debug
+        .split(prefix)
+        .nth(1)
+        .map(|rest| rest.chars().take_while(|c| c.is_ascii_digit()).collect())
+        .and_then(|digits: String| digits.parse().ok())
+        .unwrap_or_else(|| panic!("no index in {}", debug))
+}
+
+/// A cell which is assigned, or a row where a selector is enabled.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayoutCell {
+    pub column: LayoutColumn,
+    pub row: usize,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayoutRegion {
+    pub name: String,
+    /// The first row of the region and the row after its last one, or `None`
+    /// when nothing is assigned in the region
+    pub rows: Option<(usize, usize)>,
+    /// The columns used by the region, in the order in which they are drawn
+    pub columns: Vec<LayoutColumn>,
+    pub cells: Vec<LayoutCell>,
+}
+
+impl LayoutRegion {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            rows: None,
+            columns: vec![],
+            cells: vec![],
+        }
+    }
+
+    fn push(&mut self, cell: LayoutCell) {
+        self.rows = Some(match self.rows {
+            Some((start, end)) => (start.min(cell.row), end.max(cell.row + 1)),
+            None => (cell.row, cell.row + 1),
+        });
+        if let Err(position) = self.columns.binary_search(&cell.column) {
+            self.columns.insert(position, cell.column);
+        }
+        self.cells.push(cell);
+    }
+}
+
+/// The layout of a circuit: the regions assigned by its floor planner, the
+/// columns they use and the occupied cells. It is written as JSON, and drawn
+/// as SVG with a column per instance, advice and fixed column and per
+/// selector, and a row per row of the circuit.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Layout {
+    /// The minimal `k` of the circuit, i.e. it has `2^k` rows
+    pub k: u32,
+    /// The rows before the blinding rows
+    pub usable_rows: usize,
+    pub columns: Vec<LayoutColumn>,
+    pub regions: Vec<LayoutRegion>,
+    /// The cells assigned outside of the regions, i.e. the constants
+    pub cells: Vec<LayoutCell>,
+    /// The number of equality constraints between cells
+    pub copies: usize,
+}
+
+/// An `Assignment` which records the layout of the circuit, without
+/// computing the values of the cells.
+#[derive(Default)]
+struct LayoutRecorder {
+    regions: Vec<LayoutRegion>,
+    region: Option<LayoutRegion>,
+    cells: Vec<LayoutCell>,
+    copies: usize,
+}
+
+impl LayoutRecorder {
+    fn push(&mut self, column: LayoutColumn, row: usize, name: String) {
+        let cell = LayoutCell { column, row, name };
+        match &mut self.region {
+            Some(region) => region.push(cell),
+            None => self.cells.push(cell),
+        }
+    }
+}
+
+impl<F: FieldExt> Assignment<F> for LayoutRecorder {
+    fn enter_region<NR, N>(&mut self, name: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.region = Some(LayoutRegion::new(name().into()));
+    }
+
+    fn exit_region(&mut self) {
+        self.regions.extend(self.region.take());
+    }
+
+    fn enable_selector<A, AR>(
+        &mut self,
+        name: A,
+        selector: &Selector,
+        row: usize,
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.push(LayoutColumn::selector(selector), row, name().into());
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        name: A,
+        column: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.push(LayoutColumn::of(column.into()), row, name().into());
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        name: A,
+        column: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.push(LayoutColumn::of(column.into()), row, name().into());
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        self.copies += 1;
+        Ok(())
+    }
+
+    // Fills the unused rows of lookup tables, which are not drawn
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+impl Layout {
+    /// Lays out the circuit without computing its witness.
+    pub fn of<F: FieldExt, ConcreteCircuit: Circuit<F>>(circuit: &ConcreteCircuit) -> Self {
+        let mut cs = ConstraintSystem::default();
+        let config = ConcreteCircuit::configure(&mut cs);
+        let mut recorder = LayoutRecorder::default();
+        ConcreteCircuit::FloorPlanner::synthesize(
+            &mut recorder,
+            circuit,
+            config,
+            constant_columns(&cs),
+        )
+        .expect("failed to lay out the circuit");
+
+        let counts = Counts::of(&cs);
+        let columns = [
+            (ColumnKind::Instance, counts.instance_columns),
+            (ColumnKind::Advice, counts.advice_columns),
+            (ColumnKind::Fixed, counts.fixed_columns),
+            (ColumnKind::Selector, counts.selectors),
+        ]
+        .into_iter()
+        .flat_map(|(kind, count)| (0..count).map(move |index| LayoutColumn { kind, index }))
+        .collect();
+        let k = minimal_k(circuit);
+
+        Self {
+            k,
+            usable_rows: (1 << k) - cs.blinding_factors() - 1,
+            columns,
+            regions: recorder.regions,
+            cells: recorder.cells,
+            copies: recorder.copies,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Draws the layout in a `width` x `height` SVG image. The regions are
+    /// labelled with their names, and the names of the regions and cells are
+    /// shown as tooltips.
+    pub fn to_svg(&self, width: u32, height: u32) -> String {
+        let column_width = width as f64 / self.columns.len().max(1) as f64;
+        let row_height = height as f64 / (1u64 << self.k) as f64;
+        let x = |column: &LayoutColumn| {
+            self.columns.iter().position(|c| c == column).unwrap() as f64 * column_width
+        };
+        let y = |row: usize| row as f64 * row_height;
+        let font_size = row_height.clamp(6.0, 14.0);
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            width, height
+        )
+        .unwrap();
+        for column in &self.columns {
+            let fill = match column.kind {
+                ColumnKind::Instance => "#f0f0ff",
+                ColumnKind::Advice => "#fff0f0",
+                ColumnKind::Fixed => "#f0fff0",
+                ColumnKind::Selector => "#f8f8e0",
+            };
+            writeln!(
+                svg,
+                r##"<rect x="{:.2}" y="0" width="{:.2}" height="{}" fill="{}" stroke="#c0c0c0"><title>{}</title></rect>"##,
+                x(column),
+                column_width,
+                height,
+                fill,
+                column
+            )
+            .unwrap();
+        }
+        writeln!(
+            svg,
+            r##"<rect x="0" y="{:.2}" width="{}" height="{:.2}" fill="#808080" fill-opacity="0.5"><title>blinding rows</title></rect>"##,
+            y(self.usable_rows),
+            width,
+            height as f64 - y(self.usable_rows)
+        )
+        .unwrap();
+
+        for (i, region) in self.regions.iter().enumerate() {
+            let (start, end) = match region.rows {
+                Some(rows) => rows,
+                None => continue,
+            };
+            // Spreads the hues of consecutive regions with the golden angle
+            let hue = (i as f64 * 137.5) % 360.0;
+            let name = escape(&region.name);
+            for column in &region.columns {
+                writeln!(
+                    svg,
+                    r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="hsl({:.0},70%,60%)" fill-opacity="0.5" stroke="hsl({:.0},70%,40%)"><title>{}</title></rect>"#,
+                    x(column),
+                    y(start),
+                    column_width,
+                    y(end) - y(start),
+                    hue,
+                    hue,
+                    name
+                )
+                .unwrap();
+            }
+            writeln!(
+                svg,
+                r#"<text x="{:.2}" y="{:.2}" font-family="sans-serif" font-size="{:.2}">{}</text>"#,
+                x(&region.columns[0]) + 2.0,
+                y(start) + font_size,
+                font_size,
+                name
+            )
+            .unwrap();
+        }
+
+        let cells = self
+            .regions
+            .iter()
+            .flat_map(|region| &region.cells)
+            .chain(&self.cells);
+        for cell in cells {
+            writeln!(
+                svg,
+                r##"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="#303030" fill-opacity="0.6"><title>{} {}: {}</title></rect>"##,
+                x(&cell.column) + column_width * 0.1,
+                y(cell.row) + row_height * 0.1,
+                column_width * 0.8,
+                row_height * 0.8,
+                cell.column,
+                cell.row,
+                escape(&cell.name)
+            )
+            .unwrap();
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    pub fn write_json(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+
+    pub fn write_svg(&self, path: impl AsRef<Path>, width: u32, height: u32) -> io::Result<()> {
+        fs::write(path, self.to_svg(width, height))
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_example::simple_example::MyCircuit;
+    use halo2_proofs::pasta::Fp;
+    use serde_json::json;
+
+    #[test]
+    fn test_simple_example_layout() {
+        let circuit = MyCircuit {
+            constant: Fp::from(7),
+            a: Value::known(Fp::from(2)),
+            b: Value::known(Fp::from(3)),
+        };
+        let layout = Layout::of(&circuit);
+        let json: serde_json::Value = serde_json::from_str(&layout.to_json()).unwrap();
+
+        assert_eq!(json["k"], 4);
+        assert_eq!(json["columns"].as_array().unwrap().len(), 5);
+        assert_eq!(json["columns"][0], json!({"kind": "instance", "index": 0}));
+
+        let regions = json["regions"].as_array().unwrap();
+        let names: Vec<&str> = regions
+            .iter()
+            .map(|region| region["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "load private",
+                "load private",
+                "load constant",
+                "mul a*b",
+                "mul ab*ab",
+                "mul c*absq"
+            ]
+        );
+
+        // Each multiplication enables the selector and uses two rows of the two
+        // advice columns
+        let muls: Vec<_> = regions
+            .iter()
+            .filter(|region| region["name"].as_str().unwrap().starts_with("mul"))
+            .collect();
+        assert_eq!(muls.len(), 3);
+        for region in muls {
+            let (start, end) = (region["rows"][0].as_u64(), region["rows"][1].as_u64());
+            assert_eq!(end.unwrap() - start.unwrap(), 2);
+            assert_eq!(
+                region["columns"],
+                json!([
+                    {"kind": "advice", "index": 0},
+                    {"kind": "advice", "index": 1},
+                    {"kind": "selector", "index": 0},
+                ])
+            );
+            assert_eq!(region["cells"].as_array().unwrap().len(), 4);
+        }
+
+        // The constant is assigned in the fixed column outside of the regions
+        assert_eq!(
+            json["cells"][0]["column"],
+            json!({"kind": "fixed", "index": 0})
+        );
+
+        assert_eq!(serde_json::from_value::<Layout>(json).unwrap(), layout);
+    }
+
+    #[test]
+    fn test_layout_export() {
+        let circuit = MyCircuit::<Fp>::default();
+        let layout = Layout::of(&circuit);
+
+        let dir = std::env::temp_dir();
+        let (json, svg) = (
+            dir.join("simple-layout.json"),
+            dir.join("simple-layout.svg"),
+        );
+        layout.write_json(&json).unwrap();
+        layout.write_svg(&svg, 400, 800).unwrap();
+
+        let svg = fs::read_to_string(svg).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(r#"width="400" height="800""#));
+        assert!(svg.contains("mul ab*ab"));
+        assert_eq!(
+            serde_json::from_str::<Layout>(&fs::read_to_string(json).unwrap()).unwrap(),
+            layout
+        );
+    }
+}
@@ -0,0 +1,5 @@
+pub mod constraints;
+pub mod examples;
+pub mod layout;
+pub mod report;
+pub mod rows;
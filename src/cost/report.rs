@@ -0,0 +1,212 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use halo2_proofs::{
+    arithmetic::{CurveAffine, Field, FieldExt},
+    dev::CircuitCost,
+    plonk::{create_proof, keygen_pk, keygen_vk, Circuit, ConstraintSystem},
+    poly::commitment::Params,
+    transcript::{Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+
+use super::rows::{minimal_k, num_rows};
+
+/// The cost of a circuit, for the commitment scheme over the curve whose
+/// scalar field is the field of the circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitReport {
+    pub name: String,
+    /// The rows used by the regions of the circuit
+    pub rows: usize,
+    /// The minimal `k`, i.e. the circuit has `2^k` rows
+    pub k: u32,
+    pub advice_columns: usize,
+    /// The fixed columns declared by the circuit, without those of the
+    /// selectors
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    pub lookups: usize,
+    /// The columns enabled for equality constraints
+    pub permutation_columns: usize,
+    /// The maximum degree of the constraints, including the lookup and
+    /// permutation arguments
+    pub degree: usize,
+    /// The estimated size in bytes of a proof for one instance of the
+    /// circuit. `CircuitCost` of halo2_proofs 0.2 panics for circuits without
+    /// equality constraints, for which it is `None`.
+    pub proof_size: Option<usize>,
+    /// The time to create a proof, when it was measured
+    pub prover_time: Option<Duration>,
+}
+
+/// The counts of a constraint system which `CircuitCost` does not expose.
+/// halo2_proofs 0.2 keeps the fields of `ConstraintSystem` private, so they
+/// are read from its `Debug` output.
+pub(crate) struct Counts {
+    pub(crate) advice_columns: usize,
+    pub(crate) fixed_columns: usize,
+    pub(crate) instance_columns: usize,
+    pub(crate) selectors: usize,
+    pub(crate) lookups: usize,
+    pub(crate) permutation_columns: usize,
+}
+
+impl Counts {
+    pub(crate) fn of<F: FieldExt>(cs: &ConstraintSystem<F>) -> Self {
+        let debug = format!("{:?}", cs);
+        let number = |name: &str| -> usize {
+            let start = debug
+                .find(&format!("{}: ", name))
+                .unwrap_or_else(|| panic!("no field {} in the constraint system", name))
+                + name.len()
+                + 2;
+            debug[start..]
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .collect::<String>()
+                .parse()
+                .unwrap()
+        };
+        let permutation = debug
+            .split("permutation: Argument { columns: [")
+            .nth(1)
+            .and_then(|rest| rest.split(']').next())
+            .expect("no permutation in the constraint system");
+
+        Self {
+            advice_columns: number("num_advice_columns"),
+            fixed_columns: number("num_fixed_columns"),
+            instance_columns: number("num_instance_columns"),
+            selectors: number("num_selectors"),
+            lookups: debug.matches("input_expressions").count(),
+            permutation_columns: permutation.matches("Column {").count(),
+        }
+    }
+}
+
+/// Measures the cost of a circuit over the scalar field of `C`. When `prove`
+/// is set, a proof is also created with zero instance values to measure the
+/// prover time.
+pub fn measure<C, ConcreteCircuit>(
+    name: &str,
+    circuit: &ConcreteCircuit,
+    prove: bool,
+) -> CircuitReport
+where
+    C: CurveAffine,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    let k = minimal_k(circuit);
+
+    let mut cs = ConstraintSystem::default();
+    ConcreteCircuit::configure(&mut cs);
+    let counts = Counts::of(&cs);
+    let cost = CircuitCost::<C::CurveExt, ConcreteCircuit>::measure(k as usize, circuit);
+
+    CircuitReport {
+        name: name.to_string(),
+        rows: num_rows(circuit),
+        k,
+        advice_columns: counts.advice_columns,
+        fixed_columns: counts.fixed_columns,
+        instance_columns: counts.instance_columns,
+        lookups: counts.lookups,
+        permutation_columns: counts.permutation_columns,
+        degree: cs.degree(),
+        proof_size: (counts.permutation_columns > 0).then(|| cost.proof_size(1).into()),
+        prover_time: prove.then(|| prover_time::<C, _>(k, circuit, counts.instance_columns)),
+    }
+}
+
+/// Returns the time taken by `create_proof` with the Blake2b transcript, after
+/// generating the parameters and keys.
+fn prover_time<C, ConcreteCircuit>(
+    k: u32,
+    circuit: &ConcreteCircuit,
+    instance_columns: usize,
+) -> Duration
+where
+    C: CurveAffine,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    let params: Params<C> = Params::new(k);
+    let vk = keygen_vk(&params, circuit).unwrap();
+    let pk = keygen_pk(&params, vk, circuit).unwrap();
+
+    // The instance columns are filled up to the blinding rows, since the
+    // prover reads the values of the rows copied into advice cells
+    let mut cs = ConstraintSystem::default();
+    ConcreteCircuit::configure(&mut cs);
+    let usable_rows = (1 << k) - cs.blinding_factors() - 1;
+    let zeros = vec![C::Scalar::zero(); usable_rows];
+    let instances: Vec<&[C::Scalar]> = vec![&zeros; instance_columns];
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    let start = Instant::now();
+    create_proof(
+        &params,
+        &pk,
+        std::slice::from_ref(circuit),
+        &[&instances],
+        OsRng,
+        &mut transcript,
+    )
+    .unwrap();
+    start.elapsed()
+}
+
+/// Formats the reports as a table with a row per circuit.
+pub struct CostTable<'a>(pub &'a [CircuitReport]);
+
+impl fmt::Display for CostTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name_width = self
+            .0
+            .iter()
+            .map(|report| report.name.len())
+            .chain([7])
+            .max()
+            .unwrap();
+        writeln!(
+            f,
+            "{:<name_width$} | {:>5} | {:>2} | {:>6} | {:>5} | {:>8} | {:>7} | {:>11} | {:>6} | {:>10} | {:>11}",
+            "circuit",
+            "rows",
+            "k",
+            "advice",
+            "fixed",
+            "instance",
+            "lookups",
+            "permutation",
+            "degree",
+            "proof size",
+            "prover time",
+        )?;
+        writeln!(f, "{}", "-".repeat(name_width + 101))?;
+        for report in self.0 {
+            let prover_time = report
+                .prover_time
+                .map(|time| format!("{:.3}s", time.as_secs_f64()))
+                .unwrap_or_else(|| "-".to_string());
+            writeln!(
+                f,
+                "{:<name_width$} | {:>5} | {:>2} | {:>6} | {:>5} | {:>8} | {:>7} | {:>11} | {:>6} | {:>10} | {:>11}",
+                report.name,
+                report.rows,
+                report.k,
+                report.advice_columns,
+                report.fixed_columns,
+                report.instance_columns,
+                report.lookups,
+                report.permutation_columns,
+                report.degree,
+                report
+                    .proof_size
+                    .map(|size| size.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                prover_time,
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Value,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Selector,
+    },
+};
+
+use super::layout::LayoutColumn;
+use super::report::Counts;
+
+/// An `Assignment` which only records the rows which are used, without
+/// computing the values of the cells.
+#[derive(Default)]
+struct RowCounter {
+    rows: usize,
+}
+
+impl RowCounter {
+    fn use_row(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+}
+
+impl<F: FieldExt> Assignment<F> for RowCounter {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        _: Column<Any>,
+        left_row: usize,
+        _: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.use_row(left_row);
+        self.use_row(right_row);
+        Ok(())
+    }
+
+    // Fills the unused rows of lookup tables, which are not counted
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+/// An `Assignment` which computes the values of the advice cells, as the
+/// prover does before committing to them, without checking any constraint.
+struct WitnessCollector<'a, F: FieldExt> {
+    instances: &'a [Vec<F>],
+    advice: HashMap<Column<Advice>, Vec<Assigned<F>>>,
+}
+
+impl<F: FieldExt> Assignment<F> for WitnessCollector<'_, F> {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        self.instances
+            .get(LayoutColumn::of(column.into()).index)
+            .and_then(|column| column.get(row))
+            .map(|value| Value::known(*value))
+            .ok_or(Error::BoundsFailure)
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let values = self.advice.entry(column).or_default();
+        if values.len() <= row {
+            values.resize(row + 1, Assigned::Zero);
+        }
+        to().map(|value| values[row] = value.into());
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        _: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+/// Returns the fixed columns enabled for constants. halo2_proofs 0.2 keeps
+/// them private, so their indices are read from the `Debug` output of the
+/// constraint system, and the columns with these indices are allocated in
+/// another one.
+pub(super) fn constant_columns<F: FieldExt>(cs: &ConstraintSystem<F>) -> Vec<Column<Fixed>> {
+    let debug = format!("{:?}", cs);
+    let constants = debug
+        .split("constants: [")
+        .nth(1)
+        .and_then(|rest| rest.split(']').next())
+        .expect("no constants in the constraint system");
+
+    let mut columns = ConstraintSystem::<F>::default();
+    let columns: Vec<Column<Fixed>> = (0..cs_num_fixed_columns(&debug))
+        .map(|_| columns.fixed_column())
+        .collect();
+    constants
+        .split("index: ")
+        .skip(1)
+        .map(|rest| {
+            let index: usize = rest
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .collect::<String>()
+                .parse()
+                .unwrap();
+            columns[index]
+        })
+        .collect()
+}
+
+fn cs_num_fixed_columns(debug: &str) -> usize {
+    debug
+        .split("num_fixed_columns: ")
+        .nth(1)
+        .and_then(|rest| rest.split(',').next())
+        .and_then(|number| number.parse().ok())
+        .expect("no fixed columns in the constraint system")
+}
+
+/// Returns the number of rows used by the circuit, by laying it out without
+/// computing its witness.
+pub fn num_rows<F: FieldExt, ConcreteCircuit: Circuit<F>>(circuit: &ConcreteCircuit) -> usize {
+    let mut cs = ConstraintSystem::default();
+    let config = ConcreteCircuit::configure(&mut cs);
+    let mut counter = RowCounter::default();
+    ConcreteCircuit::FloorPlanner::synthesize(&mut counter, circuit, config, constant_columns(&cs))
+        .expect("failed to lay out the circuit");
+    counter.rows
+}
+
+/// Returns the values of the advice columns of the circuit with the given
+/// instance values, up to the last row each column uses. This is the witness
+/// generation of `create_proof`, which is done before the commitments.
+pub fn witness<F: FieldExt, ConcreteCircuit: Circuit<F>>(
+    circuit: &ConcreteCircuit,
+    instances: &[Vec<F>],
+) -> Result<Vec<Vec<F>>, Error> {
+    let mut cs = ConstraintSystem::default();
+    let config = ConcreteCircuit::configure(&mut cs);
+    let mut collector = WitnessCollector {
+        instances,
+        advice: HashMap::new(),
+    };
+    ConcreteCircuit::FloorPlanner::synthesize(
+        &mut collector,
+        circuit,
+        config,
+        constant_columns(&cs),
+    )?;
+    let mut advice = vec![vec![]; Counts::of(&cs).advice_columns];
+    for (column, values) in collector.advice {
+        advice[LayoutColumn::of(column.into()).index] =
+            values.into_iter().map(Assigned::evaluate).collect();
+    }
+    Ok(advice)
+}
+
+/// Returns the smallest `k` such that the `2^k` rows of the circuit hold the
+/// rows it uses and the blinding rows, which is the `k` needed by
+/// `MockProver::run`, `Params::new` and `CircuitCost::measure`.
+pub fn minimal_k<F: FieldExt, ConcreteCircuit: Circuit<F>>(circuit: &ConcreteCircuit) -> u32 {
+    let mut cs = ConstraintSystem::default();
+    ConcreteCircuit::configure(&mut cs);
+    // The last usable row is followed by the blinding rows
+    let rows = (num_rows(circuit) + cs.blinding_factors() + 1).max(cs.minimum_rows());
+    rows.next_power_of_two().trailing_zeros()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::fib_three_column::MyCircuit;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    #[test]
+    fn test_minimal_k() {
+        let circuit = MyCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        };
+        // The first row and the 7 rows of the next terms, as in the layout of
+        // `fib_three_column`
+        assert_eq!(num_rows(&circuit), 8);
+        let k = minimal_k(&circuit);
+        assert_eq!(k, 4);
+
+        let instances = vec![vec![Fp::zero(); 3]];
+        assert!(MockProver::run(k, &circuit, instances).is_ok());
+        // Without instance values, which would not fit either
+        assert!(matches!(
+            MockProver::run(k - 1, &circuit, vec![vec![]]),
+            Err(Error::NotEnoughRowsAvailable { .. })
+        ));
+    }
+
+    #[test]
+    fn test_witness() {
+        let circuit = MyCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        };
+        let advice = witness(&circuit, &[vec![Fp::zero(); 3]]).unwrap();
+        // The terms `c = a + b` of the 8 rows, up to `F[9] = 55`
+        let fib = [2, 3, 5, 8, 13, 21, 34, 55].map(Fp::from);
+        assert_eq!(advice.len(), 3);
+        assert_eq!(advice[2], fib);
+        // Each row copies `c` of the previous one to `b`
+        assert_eq!(advice[1][1..], fib[..7]);
+    }
+}
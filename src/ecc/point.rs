@@ -0,0 +1,898 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::{CurveAffine, Field, FieldExt},
+    circuit::{AssignedCell, Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+use pasta_curves::group::{ff::PrimeField, Curve};
+
+/// A point in affine coordinates, where the identity is represented by
+/// `(0, 0)`. Neither Pallas nor Vesta has a point with `x = 0`, since `5` is
+/// not a square in either field.
+#[derive(Clone, Debug)]
+pub struct EccPoint<F: FieldExt> {
+    pub x: AssignedCell<F, F>,
+    pub y: AssignedCell<F, F>,
+}
+
+impl<F: FieldExt> EccPoint<F> {
+    /// Returns the point as an element of the curve `C` over `F`.
+    pub fn value<C: CurveAffine<Base = F>>(&self) -> Value<C> {
+        self.x
+            .value()
+            .zip(self.y.value())
+            .map(|(x, y)| from_coordinates(*x, *y))
+    }
+}
+
+/// Returns the affine coordinates of `point`, with `(0, 0)` for the identity.
+pub fn coordinates<C: CurveAffine>(point: C) -> (C::Base, C::Base) {
+    Option::from(point.coordinates())
+        .map(|coordinates: halo2_proofs::arithmetic::Coordinates<C>| {
+            (*coordinates.x(), *coordinates.y())
+        })
+        .unwrap_or((C::Base::zero(), C::Base::zero()))
+}
+
+fn from_coordinates<C: CurveAffine>(x: C::Base, y: C::Base) -> C {
+    if x == C::Base::zero() && y == C::Base::zero() {
+        C::identity()
+    } else {
+        C::from_xy(x, y).unwrap()
+    }
+}
+
+/// A cell constrained to be 0 or 1 where it is used.
+pub type AssignedBit<F> = AssignedCell<F, F>;
+
+fn inv0<F: FieldExt>(value: F) -> F {
+    value.invert().unwrap_or(F::zero())
+}
+
+#[derive(Clone, Debug)]
+pub struct EccConfig {
+    /// `x_p, y_p, x_q, y_q` and the auxiliary values of each gate. The result
+    /// `x_r, y_r` is in the first two columns of the next row.
+    advice: [Column<Advice>; 9],
+    q_point: Selector,
+    q_add_incomplete: Selector,
+    q_add: Selector,
+    q_select: Selector,
+}
+
+impl EccConfig {
+    /// The column of `x_q`, to which `assert_equal` copies the x-coordinate
+    /// of the point constrained to equal the other one.
+    #[cfg(test)]
+    pub(crate) fn x_q(&self) -> Column<Advice> {
+        self.advice[2]
+    }
+}
+
+/// Arithmetic on the points of a curve `C` in a circuit over its base field,
+/// e.g. Pallas points in circuits over `Fp` and Vesta points in circuits over
+/// `Fq`.
+///
+/// Each operation is laid out in two rows:
+///
+/// | a0  | a1  | a2  | a3  | a4  | a5 | a6 | a7 | a8 |
+/// |-----|-----|-----|-----|-----|----|----|----|----|
+/// | x_p | y_p | x_q | y_q | λ   | α  | β  | γ  | δ  |
+/// | x_r | y_r |     |     |     |    |    |    |    |
+///
+/// The complete addition gate is the one described in the halo2 book, which
+/// handles the identity, doubling and `p + (-p)`. The incomplete addition
+/// gate only needs `α`, and requires `x_p != x_q`. The select gate computes
+/// `r = bit ? q : p` with the bit in `a4`.
+pub struct EccChip<C: CurveAffine> {
+    config: EccConfig,
+    _marker: PhantomData<C>,
+}
+
+impl<C: CurveAffine> EccChip<C> {
+    pub fn construct(config: EccConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<C::Base>,
+        advice: [Column<Advice>; 9],
+        constant: Column<Fixed>,
+    ) -> EccConfig {
+        meta.enable_constant(constant);
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        let q_point = meta.selector();
+        let q_add_incomplete = meta.selector();
+        let q_add = meta.selector();
+        let q_select = meta.selector();
+
+        let one = Expression::Constant(C::Base::one());
+        let b = Expression::Constant(C::b());
+
+        meta.create_gate("on curve", |meta| {
+            let q = meta.query_selector(q_point);
+            let x = meta.query_advice(advice[0], Rotation::cur());
+            let y = meta.query_advice(advice[1], Rotation::cur());
+            // Either y^2 = x^3 + b, or x = y = 0 for the identity
+            let on_curve = y.clone().square() - x.clone().square() * x.clone() - b;
+            vec![q.clone() * x * on_curve.clone(), q * y * on_curve]
+        });
+
+        meta.create_gate("incomplete addition", |meta| {
+            let q = meta.query_selector(q_add_incomplete);
+            let x_p = meta.query_advice(advice[0], Rotation::cur());
+            let y_p = meta.query_advice(advice[1], Rotation::cur());
+            let x_q = meta.query_advice(advice[2], Rotation::cur());
+            let y_q = meta.query_advice(advice[3], Rotation::cur());
+            let alpha = meta.query_advice(advice[4], Rotation::cur());
+            let x_r = meta.query_advice(advice[0], Rotation::next());
+            let y_r = meta.query_advice(advice[1], Rotation::next());
+
+            // (x_r + x_q + x_p) * (x_p - x_q)^2 - (y_p - y_q)^2 = 0
+            let x_r_check = (x_r.clone() + x_q.clone() + x_p.clone())
+                * (x_p.clone() - x_q.clone()).square()
+                - (y_p.clone() - y_q.clone()).square();
+            // (y_r + y_q) * (x_p - x_q) - (y_p - y_q) * (x_q - x_r) = 0
+            let y_r_check = (y_r + y_q.clone()) * (x_p.clone() - x_q.clone())
+                - (y_p - y_q) * (x_q.clone() - x_r);
+            // (x_q - x_p) * α = 1
+            let distinct = (x_q - x_p) * alpha - one.clone();
+
+            vec![q.clone() * x_r_check, q.clone() * y_r_check, q * distinct]
+        });
+
+        meta.create_gate("complete addition", |meta| {
+            let q = meta.query_selector(q_add);
+            let x_p = meta.query_advice(advice[0], Rotation::cur());
+            let y_p = meta.query_advice(advice[1], Rotation::cur());
+            let x_q = meta.query_advice(advice[2], Rotation::cur());
+            let y_q = meta.query_advice(advice[3], Rotation::cur());
+            let lambda = meta.query_advice(advice[4], Rotation::cur());
+            let alpha = meta.query_advice(advice[5], Rotation::cur());
+            let beta = meta.query_advice(advice[6], Rotation::cur());
+            let gamma = meta.query_advice(advice[7], Rotation::cur());
+            let delta = meta.query_advice(advice[8], Rotation::cur());
+            let x_r = meta.query_advice(advice[0], Rotation::next());
+            let y_r = meta.query_advice(advice[1], Rotation::next());
+
+            let dx = x_q.clone() - x_p.clone();
+            let sum_y = y_q.clone() + y_p.clone();
+            // λ^2 - x_p - x_q - x_r
+            let x_r_check = lambda.clone().square() - x_p.clone() - x_q.clone() - x_r.clone();
+            // λ * (x_p - x_r) - y_p - y_r
+            let y_r_check =
+                lambda.clone() * (x_p.clone() - x_r.clone()) - y_p.clone() - y_r.clone();
+            let both_non_identity = x_p.clone() * x_q.clone();
+            let p_is_identity = one.clone() - x_p.clone() * beta;
+            let q_is_identity = one.clone() - x_q.clone() * gamma;
+            let sum_is_identity = one.clone() - dx.clone() * alpha.clone() - sum_y.clone() * delta;
+
+            vec![
+                // λ is the slope of the line through p and q when x_p != x_q
+                q.clone()
+                    * dx.clone()
+                    * (dx.clone() * lambda.clone() - (y_q.clone() - y_p.clone())),
+                // ... and the slope of the tangent at p when x_p = x_q
+                q.clone()
+                    * (one.clone() - dx.clone() * alpha)
+                    * (Expression::Constant(C::Base::from(2)) * y_p.clone() * lambda
+                        - Expression::Constant(C::Base::from(3)) * x_p.clone().square()),
+                // The result for p, q != O and p != -q
+                q.clone() * both_non_identity.clone() * dx.clone() * x_r_check.clone(),
+                q.clone() * both_non_identity.clone() * dx * y_r_check.clone(),
+                q.clone() * both_non_identity.clone() * sum_y.clone() * x_r_check,
+                q.clone() * both_non_identity * sum_y * y_r_check,
+                // O + q = q
+                q.clone() * p_is_identity.clone() * (x_r.clone() - x_q),
+                q.clone() * p_is_identity * (y_r.clone() - y_q),
+                // p + O = p
+                q.clone() * q_is_identity.clone() * (x_r.clone() - x_p),
+                q.clone() * q_is_identity * (y_r.clone() - y_p),
+                // p + (-p) = O
+                q.clone() * sum_is_identity.clone() * x_r,
+                q * sum_is_identity * y_r,
+            ]
+        });
+
+        meta.create_gate("select", |meta| {
+            let q = meta.query_selector(q_select);
+            let x_p = meta.query_advice(advice[0], Rotation::cur());
+            let y_p = meta.query_advice(advice[1], Rotation::cur());
+            let x_q = meta.query_advice(advice[2], Rotation::cur());
+            let y_q = meta.query_advice(advice[3], Rotation::cur());
+            let bit = meta.query_advice(advice[4], Rotation::cur());
+            let x_r = meta.query_advice(advice[0], Rotation::next());
+            let y_r = meta.query_advice(advice[1], Rotation::next());
+
+            vec![
+                q.clone() * bit.clone() * (one.clone() - bit.clone()),
+                q.clone() * (x_r - x_p.clone() - bit.clone() * (x_q - x_p)),
+                q * (y_r - y_p.clone() - bit * (y_q - y_p)),
+            ]
+        });
+
+        EccConfig {
+            advice,
+            q_point,
+            q_add_incomplete,
+            q_add,
+            q_select,
+        }
+    }
+
+    /// Assigns a point, constrained to be on the curve or the identity.
+    pub fn witness_point(
+        &self,
+        layouter: impl Layouter<C::Base>,
+        point: Value<C>,
+    ) -> Result<EccPoint<C::Base>, Error> {
+        self.witness_coordinates(layouter, point.map(coordinates))
+    }
+
+    fn witness_coordinates(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        coordinates: Value<(C::Base, C::Base)>,
+    ) -> Result<EccPoint<C::Base>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "witness point",
+            |mut region| {
+                config.q_point.enable(&mut region, 0)?;
+                let x = region.assign_advice(
+                    || "x",
+                    config.advice[0],
+                    0,
+                    || coordinates.map(|(x, _)| x),
+                )?;
+                let y = region.assign_advice(
+                    || "y",
+                    config.advice[1],
+                    0,
+                    || coordinates.map(|(_, y)| y),
+                )?;
+                Ok(EccPoint { x, y })
+            },
+        )
+    }
+
+    /// Assigns a fixed point, e.g. a generator.
+    pub fn constant_point(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        point: C,
+    ) -> Result<EccPoint<C::Base>, Error> {
+        let config = self.config();
+        let (x, y) = coordinates(point);
+
+        layouter.assign_region(
+            || "constant point",
+            |mut region| {
+                Ok(EccPoint {
+                    x: region.assign_advice_from_constant(|| "x", config.advice[0], 0, x)?,
+                    y: region.assign_advice_from_constant(|| "y", config.advice[1], 0, y)?,
+                })
+            },
+        )
+    }
+
+    /// Returns `p + q`. The constraints cannot be satisfied when `p` and `q`
+    /// have the same x-coordinate, and the result is meaningless when either
+    /// is the identity.
+    pub fn add_incomplete(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        p: &EccPoint<C::Base>,
+        q: &EccPoint<C::Base>,
+    ) -> Result<EccPoint<C::Base>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "incomplete addition",
+            |mut region| {
+                config.q_add_incomplete.enable(&mut region, 0)?;
+                self.copy_inputs(&mut region, p, q)?;
+
+                let alpha =
+                    p.x.value()
+                        .zip(q.x.value())
+                        .map(|(x_p, x_q)| inv0(*x_q - x_p));
+                region.assign_advice(|| "alpha", config.advice[4], 0, || alpha)?;
+
+                // The formulas of the gate, which do not use the group law of C
+                let r =
+                    p.x.value()
+                        .zip(p.y.value())
+                        .zip(q.x.value().zip(q.y.value()))
+                        .map(|((x_p, y_p), (x_q, y_q))| {
+                            let lambda = (*y_q - y_p) * inv0(*x_q - x_p);
+                            let x_r = lambda.square() - x_p - x_q;
+                            let y_r = lambda * (*x_p - x_r) - y_p;
+                            (x_r, y_r)
+                        });
+                self.assign_result(&mut region, r)
+            },
+        )
+    }
+
+    /// Returns `p + q` for any points.
+    pub fn add(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        p: &EccPoint<C::Base>,
+        q: &EccPoint<C::Base>,
+    ) -> Result<EccPoint<C::Base>, Error> {
+        layouter.assign_region(
+            || "complete addition",
+            |mut region| {
+                let r = p
+                    .value::<C>()
+                    .zip(q.value::<C>())
+                    .map(|(p, q)| coordinates((p + q).to_affine()));
+                self.assign_add(&mut region, p, q, r)
+            },
+        )
+    }
+
+    /// Assigns the complete addition gate with the result `r`.
+    fn assign_add(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        p: &EccPoint<C::Base>,
+        q: &EccPoint<C::Base>,
+        r: Value<(C::Base, C::Base)>,
+    ) -> Result<EccPoint<C::Base>, Error> {
+        let config = self.config();
+
+        config.q_add.enable(region, 0)?;
+        self.copy_inputs(region, p, q)?;
+
+        let inputs =
+            p.x.value()
+                .zip(p.y.value())
+                .zip(q.x.value().zip(q.y.value()))
+                .map(|((x_p, y_p), (x_q, y_q))| (*x_p, *y_p, *x_q, *y_q));
+
+        let zero = C::Base::zero();
+        let lambda = inputs.map(|(x_p, y_p, x_q, y_q)| {
+            if x_q != x_p {
+                (y_q - y_p) * inv0(x_q - x_p)
+            } else if y_p != zero {
+                C::Base::from(3) * x_p.square() * inv0(C::Base::from(2) * y_p)
+            } else {
+                zero
+            }
+        });
+        let alpha = inputs.map(|(x_p, _, x_q, _)| inv0(x_q - x_p));
+        let beta = inputs.map(|(x_p, _, _, _)| inv0(x_p));
+        let gamma = inputs.map(|(_, _, x_q, _)| inv0(x_q));
+        let delta = inputs.map(
+            |(x_p, y_p, x_q, y_q)| {
+                if x_q == x_p {
+                    inv0(y_q + y_p)
+                } else {
+                    zero
+                }
+            },
+        );
+        for (i, (name, value)) in [
+            ("lambda", lambda),
+            ("alpha", alpha),
+            ("beta", beta),
+            ("gamma", gamma),
+            ("delta", delta),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            region.assign_advice(|| name, config.advice[4 + i], 0, || value)?;
+        }
+
+        self.assign_result(region, r)
+    }
+
+    /// Returns `2p`.
+    pub fn double(
+        &self,
+        layouter: impl Layouter<C::Base>,
+        p: &EccPoint<C::Base>,
+    ) -> Result<EccPoint<C::Base>, Error> {
+        self.add(layouter, p, p)
+    }
+
+    /// Returns `bit ? q : p`, where `bit` is constrained to be boolean.
+    pub fn select(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        bit: &AssignedBit<C::Base>,
+        p: &EccPoint<C::Base>,
+        q: &EccPoint<C::Base>,
+    ) -> Result<EccPoint<C::Base>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "select",
+            |mut region| {
+                config.q_select.enable(&mut region, 0)?;
+                self.copy_inputs(&mut region, p, q)?;
+                bit.copy_advice(|| "bit", &mut region, config.advice[4], 0)?;
+
+                let r = bit
+                    .value()
+                    .zip(p.x.value().zip(p.y.value()))
+                    .zip(q.x.value().zip(q.y.value()))
+                    .map(|((bit, p), q)| {
+                        let (x, y) = if *bit == C::Base::one() { q } else { p };
+                        (*x, *y)
+                    });
+                self.assign_result(&mut region, r)
+            },
+        )
+    }
+
+    /// Assigns the bits of a scalar, most significant bit first. The bits are
+    /// constrained to be boolean when they are used by `mul`.
+    pub fn witness_scalar_bits(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        scalar: Value<C::Scalar>,
+    ) -> Result<Vec<AssignedBit<C::Base>>, Error> {
+        let config = self.config();
+        let num_bits = C::Scalar::NUM_BITS as usize;
+
+        layouter.assign_region(
+            || "scalar bits",
+            |mut region| {
+                (0..num_bits)
+                    .map(|i| {
+                        let bit = scalar.map(|scalar| {
+                            let repr = scalar.to_repr();
+                            let index = num_bits - 1 - i;
+                            let bit = (repr.as_ref()[index / 8] >> (index % 8)) & 1;
+                            C::Base::from(bit as u64)
+                        });
+                        region.assign_advice(|| "bit", config.advice[4], i, || bit)
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    /// Returns `[k]p`, where `k` is the integer with the given bits, most
+    /// significant bit first, by double-and-add with complete addition.
+    pub fn mul(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        bits: &[AssignedBit<C::Base>],
+        p: &EccPoint<C::Base>,
+    ) -> Result<EccPoint<C::Base>, Error> {
+        let mut acc = self.constant_point(layouter.namespace(|| "identity"), C::identity())?;
+        for (i, bit) in bits.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("bit {}", bits.len() - 1 - i));
+            acc = self.double(layouter.namespace(|| "double"), &acc)?;
+            let sum = self.add(layouter.namespace(|| "add"), &acc, p)?;
+            acc = self.select(layouter.namespace(|| "select"), bit, &acc, &sum)?;
+        }
+        Ok(acc)
+    }
+
+    /// Constrains `p = q`.
+    pub fn assert_equal(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        p: &EccPoint<C::Base>,
+        q: &EccPoint<C::Base>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "assert equal",
+            |mut region| {
+                let EccPoint { x, y } = self.copy_inputs(&mut region, p, p)?;
+                region.constrain_equal(x.cell(), q.x.cell())?;
+                region.constrain_equal(y.cell(), q.y.cell())
+            },
+        )
+    }
+
+    /// Copies `p` and `q` into the first row of the region, and returns the
+    /// copy of `q`.
+    fn copy_inputs(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        p: &EccPoint<C::Base>,
+        q: &EccPoint<C::Base>,
+    ) -> Result<EccPoint<C::Base>, Error> {
+        let advice = self.config().advice;
+        p.x.copy_advice(|| "x_p", region, advice[0], 0)?;
+        p.y.copy_advice(|| "y_p", region, advice[1], 0)?;
+        Ok(EccPoint {
+            x: q.x.copy_advice(|| "x_q", region, advice[2], 0)?,
+            y: q.y.copy_advice(|| "y_q", region, advice[3], 0)?,
+        })
+    }
+
+    fn assign_result(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        r: Value<(C::Base, C::Base)>,
+    ) -> Result<EccPoint<C::Base>, Error> {
+        let advice = self.config().advice;
+        Ok(EccPoint {
+            x: region.assign_advice(|| "x_r", advice[0], 1, || r.map(|(x, _)| x))?,
+            y: region.assign_advice(|| "y_r", advice[1], 1, || r.map(|(_, y)| y))?,
+        })
+    }
+}
+
+impl<C: CurveAffine> Chip<C::Base> for EccChip<C> {
+    type Config = EccConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_constraint_failure;
+    use crate::soundness::mutation::{unconstrained_cells, UnconstrainedCells};
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::{MockProver, VerifyFailure},
+        pasta::{pallas, vesta},
+        plonk::{Circuit, Instance},
+    };
+    use pasta_curves::group::{prime::PrimeCurveAffine, Group};
+    use rand_core::OsRng;
+
+    #[derive(Clone, Debug)]
+    struct EccTestConfig {
+        ecc: EccConfig,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes the coordinates of `p + q` computed with incomplete addition,
+    /// then `p + q`, `2p`, `p + (-p)`, `O + p`, `p + O`, `O + O` and `[k]p`.
+    struct EccTestCircuit<C: CurveAffine> {
+        p: Value<C>,
+        q: Value<C>,
+        k: Value<C::Scalar>,
+        // Replaces the x-coordinate of the result of `add(p, q)` when set
+        malicious_x: Option<C::Base>,
+    }
+
+    impl<C: CurveAffine> Circuit<C::Base> for EccTestCircuit<C> {
+        type Config = EccTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                p: Value::unknown(),
+                q: Value::unknown(),
+                k: Value::unknown(),
+                malicious_x: None,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<C::Base>) -> Self::Config {
+            let advice = [(); 9].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            EccTestConfig {
+                ecc: EccChip::<C>::configure(meta, advice, constant),
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<C::Base>,
+        ) -> Result<(), Error> {
+            let chip = EccChip::<C>::construct(config.ecc.clone());
+
+            let p = chip.witness_point(layouter.namespace(|| "p"), self.p)?;
+            let q = chip.witness_point(layouter.namespace(|| "q"), self.q)?;
+            let neg_p = chip.witness_point(layouter.namespace(|| "-p"), self.p.map(|p| -p))?;
+            let identity = chip.constant_point(layouter.namespace(|| "O"), C::identity())?;
+
+            let mut results = vec![
+                chip.add_incomplete(layouter.namespace(|| "p + q incomplete"), &p, &q)?,
+                chip.add(layouter.namespace(|| "p + q"), &p, &q)?,
+                chip.double(layouter.namespace(|| "2p"), &p)?,
+                chip.add(layouter.namespace(|| "p + (-p)"), &p, &neg_p)?,
+                chip.add(layouter.namespace(|| "O + p"), &identity, &p)?,
+                chip.add(layouter.namespace(|| "p + O"), &p, &identity)?,
+                chip.add(layouter.namespace(|| "O + O"), &identity, &identity)?,
+            ];
+            let bits = chip.witness_scalar_bits(layouter.namespace(|| "k"), self.k)?;
+            results.push(chip.mul(layouter.namespace(|| "[k]p"), &bits, &p)?);
+
+            if let Some(x) = self.malicious_x {
+                let y = results[1].y.value().copied();
+                results[1] = layouter.assign_region(
+                    || "malicious p + q",
+                    |mut region| chip.assign_add(&mut region, &p, &q, y.map(|y| (x, y))),
+                )?;
+            }
+
+            for (i, r) in results.iter().enumerate() {
+                layouter.constrain_instance(r.x.cell(), config.instance, 2 * i)?;
+                layouter.constrain_instance(r.y.cell(), config.instance, 2 * i + 1)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn expected<C: CurveAffine>(p: C, q: C, k: C::Scalar) -> Vec<C::Base> {
+        let p_curve = p.to_curve();
+        [
+            p_curve + q,
+            p_curve + q,
+            p_curve.double(),
+            p_curve - p,
+            p_curve,
+            p_curve,
+            C::Curve::identity(),
+            p * k,
+        ]
+        .iter()
+        .flat_map(|r| {
+            let (x, y) = coordinates(r.to_affine());
+            [x, y]
+        })
+        .collect()
+    }
+
+    fn prover<C: CurveAffine>(
+        circuit: &EccTestCircuit<C>,
+        public_inputs: Vec<C::Base>,
+    ) -> MockProver<C::Base> {
+        MockProver::run(minimal_k(circuit), circuit, vec![public_inputs]).unwrap()
+    }
+
+    fn run<C: CurveAffine>(
+        circuit: &EccTestCircuit<C>,
+        public_inputs: Vec<C::Base>,
+    ) -> Result<(), Vec<VerifyFailure>> {
+        prover(circuit, public_inputs).verify()
+    }
+
+    fn test_ecc_chip<C: CurveAffine>() {
+        let p = C::Curve::random(OsRng).to_affine();
+        let q = C::Curve::random(OsRng).to_affine();
+        for k in [
+            C::Scalar::zero(),
+            C::Scalar::one(),
+            -C::Scalar::one(),
+            C::Scalar::random(OsRng),
+        ] {
+            let circuit = EccTestCircuit {
+                p: Value::known(p),
+                q: Value::known(q),
+                k: Value::known(k),
+                malicious_x: None,
+            };
+            assert_eq!(run(&circuit, expected(p, q, k)), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_ecc_chip_pallas() {
+        // There is no point with x = 0, so (0, 0) can represent the identity
+        assert!(bool::from(pallas::Base::from(5).sqrt().is_none()));
+        test_ecc_chip::<pallas::Affine>();
+    }
+
+    #[test]
+    fn test_ecc_chip_vesta() {
+        assert!(bool::from(vesta::Base::from(5).sqrt().is_none()));
+        test_ecc_chip::<vesta::Affine>();
+    }
+
+    #[test]
+    fn test_ecc_chip_wrong_sum() {
+        let p = pallas::Point::random(OsRng).to_affine();
+        let q = pallas::Point::random(OsRng).to_affine();
+        let k = pallas::Scalar::random(OsRng);
+        let mut public_inputs = expected(p, q, k);
+        let (x, _) = coordinates((p - q).to_affine());
+        public_inputs[2] = x;
+
+        let circuit = EccTestCircuit {
+            p: Value::known(p),
+            q: Value::known(q),
+            k: Value::known(k),
+            malicious_x: Some(x),
+        };
+        assert_constraint_failure(
+            &prover(&circuit, public_inputs),
+            "complete addition",
+            "malicious p + q",
+        );
+    }
+
+    #[test]
+    fn test_ecc_chip_equal_x() {
+        // Incomplete addition cannot add p and -p, while complete addition
+        // gives the identity
+        let p = vesta::Point::random(OsRng).to_affine();
+        let q = -p;
+        let k = vesta::Scalar::random(OsRng);
+        let mut public_inputs = expected(p, q, k);
+        // The witness of the incomplete addition, with λ = 0
+        let (x_p, y_p) = coordinates(p);
+        public_inputs[0] = -x_p - x_p;
+        public_inputs[1] = -y_p;
+
+        let circuit = EccTestCircuit {
+            p: Value::known(p),
+            q: Value::known(q),
+            k: Value::known(k),
+            malicious_x: None,
+        };
+        assert_constraint_failure(
+            &prover(&circuit, public_inputs),
+            "incomplete addition",
+            "incomplete addition",
+        );
+    }
+
+    /// Witnesses a point from its coordinates.
+    struct OnCurveTestCircuit<C: CurveAffine> {
+        x: C::Base,
+        y: C::Base,
+    }
+
+    impl<C: CurveAffine> Circuit<C::Base> for OnCurveTestCircuit<C> {
+        type Config = EccConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                x: C::Base::zero(),
+                y: C::Base::zero(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<C::Base>) -> Self::Config {
+            let advice = [(); 9].map(|_| meta.advice_column());
+            let constant = meta.fixed_column();
+            EccChip::<C>::configure(meta, advice, constant)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<C::Base>,
+        ) -> Result<(), Error> {
+            let chip = EccChip::<C>::construct(config);
+            chip.witness_coordinates(
+                layouter.namespace(|| "point"),
+                Value::known((self.x, self.y)),
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_ecc_chip_on_curve() {
+        let prover = |x: u64, y: u64| {
+            let circuit = OnCurveTestCircuit::<pallas::Affine> {
+                x: pallas::Base::from(x),
+                y: pallas::Base::from(y),
+            };
+            MockProver::run(minimal_k(&circuit), &circuit, vec![]).unwrap()
+        };
+
+        let (x, y) = coordinates(pallas::Affine::generator());
+        let circuit = OnCurveTestCircuit::<pallas::Affine> { x, y };
+        assert_eq!(
+            MockProver::run(minimal_k(&circuit), &circuit, vec![])
+                .unwrap()
+                .verify(),
+            Ok(())
+        );
+
+        // The identity
+        assert_eq!(prover(0, 0).verify(), Ok(()));
+        // (2, 0) and (0, 1) are neither on the curve nor the identity
+        for (x, y) in [(2, 0), (0, 1)] {
+            assert_constraint_failure(&prover(x, y), "on curve", "witness point");
+        }
+    }
+
+    /// Adds points like `EccTestCircuit`, without the scalar multiplication
+    /// which has too many cells to mutate each of them.
+    struct AddTestCircuit<C: CurveAffine> {
+        p: C,
+        q: C,
+    }
+
+    impl<C: CurveAffine> Circuit<C::Base> for AddTestCircuit<C> {
+        type Config = EccTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                p: C::identity(),
+                q: C::identity(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<C::Base>) -> Self::Config {
+            EccTestCircuit::<C>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<C::Base>,
+        ) -> Result<(), Error> {
+            let chip = EccChip::<C>::construct(config.ecc.clone());
+
+            let p = chip.witness_point(layouter.namespace(|| "p"), Value::known(self.p))?;
+            let q = chip.witness_point(layouter.namespace(|| "q"), Value::known(self.q))?;
+            let neg_p = chip.witness_point(layouter.namespace(|| "-p"), Value::known(-self.p))?;
+            let identity = chip.constant_point(layouter.namespace(|| "O"), C::identity())?;
+
+            let results = [
+                chip.add_incomplete(layouter.namespace(|| "p + q incomplete"), &p, &q)?,
+                chip.add(layouter.namespace(|| "p + q"), &p, &q)?,
+                chip.double(layouter.namespace(|| "2p"), &p)?,
+                chip.add(layouter.namespace(|| "p + (-p)"), &p, &neg_p)?,
+                chip.add(layouter.namespace(|| "O + p"), &identity, &p)?,
+                chip.add(layouter.namespace(|| "p + O"), &p, &identity)?,
+                chip.add(layouter.namespace(|| "O + O"), &identity, &identity)?,
+            ];
+            for (i, r) in results.iter().enumerate() {
+                layouter.constrain_instance(r.x.cell(), config.instance, 2 * i)?;
+                layouter.constrain_instance(r.y.cell(), config.instance, 2 * i + 1)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_ecc_chip_mutations() {
+        let p = pallas::Point::random(OsRng).to_affine();
+        let q = pallas::Point::random(OsRng).to_affine();
+        let mut public_inputs = expected(p, q, pallas::Scalar::zero());
+        public_inputs.truncate(14);
+
+        // The witnesses of the complete addition are free when they are not
+        // needed: α when x_p = x_q, β when p = O, γ when q = O and δ when
+        // p + q = O, and λ for O + O
+        let circuit = AddTestCircuit { p, q };
+        let unconstrained = unconstrained_cells(&circuit, vec![public_inputs]).unwrap();
+        let names: Vec<&str> = unconstrained
+            .iter()
+            .flat_map(UnconstrainedCells::names)
+            .collect();
+        assert_eq!(
+            names,
+            // 2p, p + (-p), O + p, p + O and O + O
+            [
+                "alpha", "alpha", "delta", "beta", "gamma", "lambda", "alpha", "beta", "gamma",
+                "delta"
+            ]
+        );
+    }
+}
@@ -0,0 +1,361 @@
+//! Schnorr signatures over Pallas with a MiMC challenge.
+//!
+//! A key pair is a secret scalar `sk` and the public key `pk = [sk]G`. A
+//! signature on a message `m` is a point `R = [k]G` for a random nonce `k`
+//! and the scalar `s = k + e * sk`, where the challenge is the one-way hash
+//! `e = H(R.x, pk.x, m)` of `mimc::compression`. It is valid when
+//! `[s]G = R + [e]pk`.
+//!
+//! The coordinates of Pallas points are elements of `Fp`, so the challenge
+//! and the point arithmetic are native in a circuit over `Fp`. The scalars
+//! are elements of `Fq`, which is larger than `Fp`, so the challenge is
+//! also a scalar.
+
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    pasta::{pallas, Fp, Fq},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use pasta_curves::group::{ff::PrimeField, prime::PrimeCurveAffine, Curve};
+use rand_core::RngCore;
+
+use super::point::{coordinates, AssignedBit, EccChip, EccConfig};
+use crate::arithmetic::standard_plonk::{
+    FieldInstructions, Number, StandardPlonkChip, StandardPlonkConfig,
+};
+use crate::encoding::field_bytes::{FieldBytesChip, FieldBytesConfig};
+use crate::logic::boolean::{BooleanChip, BooleanConfig};
+use crate::mimc::compression::{
+    hash_in_domain, Domain, MiMC5CompressionChip, MiMC5CompressionConfig,
+};
+use crate::mimc::mimc_cipher::MiMC5CipherPallasChip;
+use crate::mimc::round_constants::{MIMC_HASH_PALLAS_ROUND_CONSTANTS, NUM_ROUNDS};
+use crate::proving::public_inputs::public_inputs;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    pub r: pallas::Affine,
+    pub s: Fq,
+}
+
+/// Returns a secret key and its public key.
+pub fn keygen(mut rng: impl RngCore) -> (Fq, pallas::Affine) {
+    let sk = Fq::random(&mut rng);
+    (sk, (pallas::Affine::generator() * sk).to_affine())
+}
+
+/// Returns the challenge `H(R.x, R.y, pk.x, pk.y, m)`, the hash of the
+/// elements in the domain of Schnorr challenges. A hash which could be
+/// inverted would let anyone pick `s` and `e`, set `R = [s]G - [e]pk` and
+/// solve for the message. Both coordinates are hashed, since `-R` and `-pk`
+/// share the x-coordinates of `R` and `pk`.
+pub fn challenge(r: pallas::Affine, pk: pallas::Affine, message: Fp) -> Fp {
+    let ((r_x, r_y), (pk_x, pk_y)) = (coordinates(r), coordinates(pk));
+    hash_in_domain::<Fp, NUM_ROUNDS>(
+        Domain::SchnorrChallenge,
+        &[r_x, r_y, pk_x, pk_y, message],
+        MIMC_HASH_PALLAS_ROUND_CONSTANTS,
+    )
+}
+
+/// Returns the challenge as a scalar, which keeps its value since the modulus
+/// of `Fp` is less than the modulus of `Fq`.
+fn challenge_scalar(challenge: Fp) -> Fq {
+    Fq::from_repr(challenge.to_repr()).unwrap()
+}
+
+pub fn sign(sk: Fq, message: Fp, mut rng: impl RngCore) -> Signature {
+    let pk = (pallas::Affine::generator() * sk).to_affine();
+    let k = Fq::random(&mut rng);
+    let r = (pallas::Affine::generator() * k).to_affine();
+    let e = challenge_scalar(challenge(r, pk, message));
+    Signature { r, s: k + e * sk }
+}
+
+pub fn verify(pk: pallas::Affine, message: Fp, signature: &Signature) -> bool {
+    if bool::from(pk.is_identity()) {
+        return false;
+    }
+    let e = challenge_scalar(challenge(signature.r, pk, message));
+    pallas::Affine::generator() * signature.s == signature.r + pk * e
+}
+
+public_inputs! {
+    /// The public key and the message
+    pub struct SchnorrPublicInputs { pk_x, pk_y, message }
+}
+
+#[derive(Clone, Debug)]
+pub struct SchnorrConfig {
+    plonk: StandardPlonkConfig,
+    ecc: EccConfig,
+    hash: MiMC5CompressionConfig,
+    field_bytes: FieldBytesConfig<Fp>,
+    boolean: BooleanConfig<8>,
+}
+
+/// Proves knowledge of a valid signature on a public message under a public
+/// key other than the identity, by checking `[s]G = R + [e]pk` with the
+/// `EccChip`.
+///
+/// The challenge is computed with the `MiMC5CompressionChip`, and decomposed
+/// into its canonical bytes with the `FieldBytesChip` and the bytes into bits
+/// with the `BooleanChip`, so that its bits are unique. The bits of `s` need
+/// not be, since any of its representatives gives the same point `[s]G`.
+#[derive(Default)]
+pub struct SchnorrCircuit {
+    pub pk: Value<pallas::Affine>,
+    pub message: Value<Fp>,
+    pub signature: Value<Signature>,
+}
+
+impl SchnorrCircuit {
+    /// Returns the bits of the challenge, most significant bit first.
+    fn challenge_bits(
+        config: &SchnorrConfig,
+        mut layouter: impl Layouter<Fp>,
+        challenge: &Number<Fp>,
+    ) -> Result<Vec<AssignedBit<Fp>>, Error> {
+        let chip = StandardPlonkChip::construct(config.plonk.clone());
+        let field_bytes = FieldBytesChip::construct(config.field_bytes.clone());
+        let boolean = BooleanChip::<Fp, 8>::construct(config.boolean.clone());
+
+        let bytes = field_bytes.decompose(layouter.namespace(|| "bytes"), &challenge.0)?;
+        let mut bits = vec![];
+        for byte in bytes.bytes.iter().rev() {
+            let word =
+                boolean.assign_word(layouter.namespace(|| "byte bits"), byte.value().copied())?;
+            chip.assert_equal(
+                layouter.namespace(|| "bits of byte"),
+                Number(word.value),
+                Number(byte.clone()),
+            )?;
+            bits.extend(word.bits.into_iter().map(|bit| bit.0));
+        }
+        Ok(bits)
+    }
+}
+
+impl Circuit<Fp> for SchnorrCircuit {
+    type Config = SchnorrConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let plonk_advice = [(); 3].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+        let plonk = StandardPlonkChip::configure(meta, plonk_advice, instance, constant);
+
+        let state = meta.advice_column();
+        let key_column = meta.advice_column();
+        let round_constants = meta.fixed_column();
+        let hash = MiMC5CompressionChip::<Fp, MiMC5CipherPallasChip>::configure(
+            meta,
+            state,
+            key_column,
+            round_constants,
+        );
+
+        // The point arithmetic and the decomposition of the challenge share
+        // their advice columns
+        let advice = [(); 9].map(|_| meta.advice_column());
+        let ecc = EccChip::<pallas::Affine>::configure(meta, advice, constant);
+        let modulus = meta.fixed_column();
+        let u8_table = meta.lookup_table_column();
+        let field_bytes =
+            FieldBytesChip::configure(meta, advice[..6].try_into().unwrap(), modulus, u8_table);
+        let boolean = BooleanChip::configure(meta, advice[..8].try_into().unwrap(), advice[8]);
+
+        SchnorrConfig {
+            plonk,
+            ecc,
+            hash,
+            field_bytes,
+            boolean,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = StandardPlonkChip::construct(config.plonk.clone());
+        let ecc = EccChip::<pallas::Affine>::construct(config.ecc.clone());
+        let hash =
+            MiMC5CompressionChip::<Fp, MiMC5CipherPallasChip>::construct(config.hash.clone());
+        FieldBytesChip::construct(config.field_bytes.clone()).load(&mut layouter)?;
+
+        let pk = ecc.witness_point(layouter.namespace(|| "pk"), self.pk)?;
+        let message = chip.load_private(layouter.namespace(|| "message"), self.message)?;
+        let rows = SchnorrPublicInputs::ROWS;
+        for (name, num, row) in [
+            ("pk_x", Number(pk.x.clone()), rows.pk_x),
+            ("pk_y", Number(pk.y.clone()), rows.pk_y),
+            ("message", message.clone(), rows.message),
+        ] {
+            chip.expose_public(layouter.namespace(|| name), num, row)?;
+        }
+        // The identity would verify any signature `([s]G, s)`, and is the
+        // only point with `x = 0`
+        chip.inv(
+            layouter.namespace(|| "pk is not the identity"),
+            Number(pk.x.clone()),
+        )?;
+
+        let r = ecc.witness_point(
+            layouter.namespace(|| "R"),
+            self.signature.map(|signature| signature.r),
+        )?;
+        let s_bits = ecc.witness_scalar_bits(
+            layouter.namespace(|| "s"),
+            self.signature.map(|signature| signature.s),
+        )?;
+
+        let e = hash.hash(
+            layouter.namespace(|| "challenge"),
+            Domain::SchnorrChallenge,
+            &[r.x.clone(), r.y.clone(), pk.x.clone(), pk.y.clone(), message.0],
+        )?;
+        let e_bits =
+            Self::challenge_bits(&config, layouter.namespace(|| "challenge bits"), &Number(e))?;
+
+        let generator =
+            ecc.constant_point(layouter.namespace(|| "G"), pallas::Affine::generator())?;
+        let lhs = ecc.mul(layouter.namespace(|| "[s]G"), &s_bits, &generator)?;
+        let e_pk = ecc.mul(layouter.namespace(|| "[e]pk"), &e_bits, &pk)?;
+        let rhs = ecc.add(layouter.namespace(|| "R + [e]pk"), &r, &e_pk)?;
+        ecc.assert_equal(layouter.namespace(|| "[s]G = R + [e]pk"), &lhs, &rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::mimc::primitives::sponge::{permute_inverse, sponge};
+    use crate::proving::public_inputs::PublicInputs;
+    use crate::soundness::failures::{assert_constraint_failure, assert_permutation_failure};
+    use halo2_proofs::{
+        dev::MockProver,
+        plonk::{Any, Column},
+    };
+    use rand_core::OsRng;
+
+    /// Returns the column of the x-coordinate of `[s]G` which the region
+    /// "assert equal" copies to the one of `R + [e]pk`.
+    fn rhs_x() -> Column<Any> {
+        let config = SchnorrCircuit::configure(&mut ConstraintSystem::default());
+        config.ecc.x_q().into()
+    }
+
+    fn prover(pk: pallas::Affine, message: Fp, signature: Signature) -> MockProver<Fp> {
+        let (pk_x, pk_y) = coordinates(pk);
+        let public_inputs = SchnorrPublicInputs {
+            pk_x,
+            pk_y,
+            message,
+        };
+        let circuit = SchnorrCircuit {
+            pk: Value::known(pk),
+            message: Value::known(message),
+            signature: Value::known(signature),
+        };
+        MockProver::run(minimal_k(&circuit), &circuit, public_inputs.to_instances()).unwrap()
+    }
+
+    #[test]
+    fn test_schnorr_native() {
+        let (sk, pk) = keygen(OsRng);
+        let message = Fp::from(42);
+        let signature = sign(sk, message, OsRng);
+        assert!(verify(pk, message, &signature));
+
+        assert!(!verify(pk, Fp::from(43), &signature));
+        let (_, other_pk) = keygen(OsRng);
+        assert!(!verify(other_pk, message, &signature));
+        let forged = Signature {
+            s: signature.s + Fq::one(),
+            ..signature
+        };
+        assert!(!verify(pk, message, &forged));
+    }
+
+    #[test]
+    fn test_schnorr_inverse_forgery() {
+        let (_, pk) = keygen(OsRng);
+
+        // With the sponge challenge `e = P(h + m)`, where h is the sponge of
+        // the coordinates of R and pk, choose s and e, set R = [s]G - [e]pk
+        // and solve for the message
+        let s = Fq::random(OsRng);
+        let e = Fp::random(OsRng);
+        let r = (pallas::Affine::generator() * s - pk * challenge_scalar(e)).to_affine();
+        let ((r_x, r_y), (pk_x, pk_y)) = (coordinates(r), coordinates(pk));
+        let message = permute_inverse(e) - sponge(&[r_x, r_y, pk_x, pk_y]);
+        assert_eq!(sponge(&[r_x, r_y, pk_x, pk_y, message]), e);
+
+        // The one-way challenge differs, so the forgery is rejected
+        let forged = Signature { r, s };
+        assert_ne!(challenge(r, pk, message), e);
+        assert!(!verify(pk, message, &forged));
+        assert_permutation_failure(&prover(pk, message, forged), rhs_x());
+    }
+
+    #[test]
+    fn test_schnorr_challenge_binds_points() {
+        let (sk, pk) = keygen(OsRng);
+        let message = Fp::from(42);
+        let signature = sign(sk, message, OsRng);
+        let e = challenge(signature.r, pk, message);
+
+        // -R and -pk have the same x-coordinates as R and pk
+        assert_ne!(challenge(-signature.r, pk, message), e);
+        assert_ne!(challenge(signature.r, -pk, message), e);
+    }
+
+    #[test]
+    fn test_schnorr_circuit() {
+        let (sk, pk) = keygen(OsRng);
+        let message = Fp::from(42);
+        let signature = sign(sk, message, OsRng);
+        assert_eq!(prover(pk, message, signature).verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_schnorr_circuit_forged() {
+        let (sk, pk) = keygen(OsRng);
+        let message = Fp::from(42);
+        let signature = sign(sk, message, OsRng);
+
+        // A signature on another message
+        assert_permutation_failure(&prover(pk, Fp::from(43), signature), rhs_x());
+
+        // A signature under another key
+        let (_, other_pk) = keygen(OsRng);
+        assert_permutation_failure(&prover(other_pk, message, signature), rhs_x());
+
+        // A forgery without the secret key: choose s and e' at random and set
+        // R = [s]G - [e']pk, which only passes if e' is the challenge for R
+        let s = Fq::random(OsRng);
+        let r = (pallas::Affine::generator() * s - pk * Fq::random(OsRng)).to_affine();
+        let forged = Signature { r, s };
+        assert!(!verify(pk, message, &forged));
+        assert_permutation_failure(&prover(pk, message, forged), rhs_x());
+
+        // Any signature under the identity
+        let identity = pallas::Affine::identity();
+        let forged = Signature {
+            r: (pallas::Affine::generator() * s).to_affine(),
+            s,
+        };
+        assert!(!verify(identity, message, &forged));
+        let prover = prover(identity, message, forged);
+        assert_constraint_failure(&prover, "standard plonk", "inv");
+    }
+}
@@ -0,0 +1,447 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use crate::comparison::less_than::load_u8_table;
+use crate::is_zero_example::is_zero::{IsZeroChip, IsZeroConfig};
+
+pub const NUM_BYTES: usize = 32;
+
+/// A field element together with its canonical little endian encoding.
+#[derive(Clone, Debug)]
+pub struct FieldBytes<F: FieldExt> {
+    pub value: AssignedCell<F, F>,
+    pub bytes: Vec<AssignedCell<F, F>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FieldBytesConfig<F> {
+    byte: Column<Advice>,
+    /// `byte - modulus_byte + 256 * lt_byte`, which is a byte
+    diff: Column<Advice>,
+    lt_byte: Column<Advice>,
+    /// Horner accumulator of the bytes, most significant byte first
+    acc: Column<Advice>,
+    /// The bytes so far are less than the bytes of `p - 1`
+    lt: Column<Advice>,
+    /// The bytes so far are equal to the bytes of `p - 1`
+    eq: Column<Advice>,
+    /// The bytes of `p - 1`
+    modulus: Column<Fixed>,
+    u8_table: TableColumn,
+    byte_eq: IsZeroConfig<F>,
+    s_init: Selector,
+    s_byte: Selector,
+    s_last: Selector,
+}
+
+/// Constrains a field element to equal the little endian interpretation of
+/// 32 bytes, and the bytes to be the canonical encoding, i.e. less than the
+/// modulus `p`.
+///
+/// The bytes are laid out from the most significant one, after an initial
+/// row for the accumulators. Canonicity is checked by comparing the bytes
+/// with those of `p - 1`, keeping track of whether the prefix so far is
+/// less than or equal to the prefix of `p - 1`:
+///
+/// | byte | modulus | lt_byte | diff | acc  | lt | eq |
+/// |------|---------|---------|------|------|----|----|
+/// |      |         |         |      | 0    | 0  | 1  |
+/// | b_31 | m_31    | b < m   |      | b_31 |    |    |
+/// | ...  |         |         |      |      |    |    |
+/// | b_0  | m_0     |         |      | x    | lt + eq = 1 |
+pub struct FieldBytesChip<F: FieldExt> {
+    config: FieldBytesConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> FieldBytesChip<F> {
+    pub fn construct(config: FieldBytesConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The inverse column used to compare bytes for equality is allocated
+    /// by the chip.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 6],
+        modulus: Column<Fixed>,
+        u8_table: TableColumn,
+    ) -> FieldBytesConfig<F> {
+        let [byte, diff, lt_byte, acc, lt, eq] = advice;
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let value_inv = meta.advice_column();
+
+        let s_init = meta.selector();
+        let s_byte = meta.complex_selector();
+        let s_last = meta.selector();
+
+        let one = Expression::Constant(F::one());
+        let byte_range = Expression::Constant(F::from(256));
+
+        for column in [byte, diff] {
+            meta.lookup(|meta| {
+                let s = meta.query_selector(s_byte);
+                let value = meta.query_advice(column, Rotation::cur());
+                vec![(s * value, u8_table)]
+            });
+        }
+
+        let byte_eq = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(s_byte),
+            |meta| {
+                meta.query_advice(byte, Rotation::cur())
+                    - meta.query_fixed(modulus, Rotation::cur())
+            },
+            value_inv,
+        );
+
+        meta.create_gate("init accumulators", |meta| {
+            let s = meta.query_selector(s_init);
+            let acc = meta.query_advice(acc, Rotation::cur());
+            let lt = meta.query_advice(lt, Rotation::cur());
+            let eq = meta.query_advice(eq, Rotation::cur());
+            vec![s.clone() * acc, s.clone() * lt, s * (one.clone() - eq)]
+        });
+
+        meta.create_gate("byte", |meta| {
+            let s = meta.query_selector(s_byte);
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let modulus = meta.query_fixed(modulus, Rotation::cur());
+            let diff = meta.query_advice(diff, Rotation::cur());
+            let lt_byte = meta.query_advice(lt_byte, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            let lt_prev = meta.query_advice(lt, Rotation::prev());
+            let lt = meta.query_advice(lt, Rotation::cur());
+            let eq_prev = meta.query_advice(eq, Rotation::prev());
+            let eq = meta.query_advice(eq, Rotation::cur());
+
+            // The prefix is less than that of p - 1 if it already was, or if
+            // it was equal and this byte is less. Both cannot hold at once.
+            vec![
+                s.clone() * (acc - acc_prev * byte_range.clone() - byte.clone()),
+                s.clone() * lt_byte.clone() * (one.clone() - lt_byte.clone()),
+                s.clone() * (byte - modulus + lt_byte.clone() * byte_range.clone() - diff),
+                s.clone() * (lt - lt_prev - eq_prev.clone() * lt_byte),
+                s * (eq - eq_prev * byte_eq.expr()),
+            ]
+        });
+
+        meta.create_gate("canonical", |meta| {
+            let s = meta.query_selector(s_last);
+            let lt = meta.query_advice(lt, Rotation::cur());
+            let eq = meta.query_advice(eq, Rotation::cur());
+            // bytes <= p - 1
+            vec![s * (lt + eq - one.clone())]
+        });
+
+        FieldBytesConfig {
+            byte,
+            diff,
+            lt_byte,
+            acc,
+            lt,
+            eq,
+            modulus,
+            u8_table,
+            byte_eq,
+            s_init,
+            s_byte,
+            s_last,
+        }
+    }
+
+    /// Loads the byte table. If several chips share the same `u8_table`,
+    /// it must be loaded only once.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        load_u8_table(layouter, self.config.u8_table)
+    }
+
+    /// Returns the canonical little endian bytes of `value`.
+    pub fn decompose(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+    ) -> Result<FieldBytes<F>, Error> {
+        let bytes = value.value().map(|value| {
+            let mut bytes = [0u8; NUM_BYTES];
+            bytes.copy_from_slice(value.to_repr().as_ref());
+            bytes
+        });
+        self.assign(layouter, bytes, Some(value))
+    }
+
+    /// Returns the field element whose little endian encoding is `bytes`.
+    /// The constraints cannot be satisfied when the encoding is not
+    /// canonical, i.e. when it is at least the modulus.
+    pub fn compose(
+        &self,
+        layouter: impl Layouter<F>,
+        bytes: Value<[u8; NUM_BYTES]>,
+    ) -> Result<FieldBytes<F>, Error> {
+        self.assign(layouter, bytes, None)
+    }
+
+    fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bytes: Value<[u8; NUM_BYTES]>,
+        value: Option<&AssignedCell<F, F>>,
+    ) -> Result<FieldBytes<F>, Error> {
+        let config = self.config();
+        let modulus_bytes = modulus_minus_one_bytes::<F>();
+
+        layouter.assign_region(
+            || "field bytes",
+            |mut region| {
+                config.s_init.enable(&mut region, 0)?;
+                let zero = Value::known(F::zero());
+                let mut acc = region.assign_advice(|| "acc", config.acc, 0, || zero)?;
+                let mut lt = region.assign_advice(|| "lt", config.lt, 0, || zero)?;
+                let mut eq =
+                    region.assign_advice(|| "eq", config.eq, 0, || Value::known(F::one()))?;
+
+                let byte_eq = IsZeroChip::construct(config.byte_eq.clone());
+                let mut byte_cells = vec![];
+                for (row, i) in (0..NUM_BYTES).rev().enumerate() {
+                    let row = row + 1;
+                    config.s_byte.enable(&mut region, row)?;
+
+                    let modulus = F::from(modulus_bytes[i] as u64);
+                    region.assign_fixed(
+                        || "modulus byte",
+                        config.modulus,
+                        row,
+                        || Value::known(modulus),
+                    )?;
+
+                    let byte = bytes.map(|bytes| F::from(bytes[i] as u64));
+                    byte_cells.push(region.assign_advice(|| "byte", config.byte, row, || byte)?);
+
+                    let lt_byte = bytes.map(|bytes| bytes[i] < modulus_bytes[i]);
+                    let diff = bytes.map(|bytes| {
+                        F::from((bytes[i] as u64 + 256 - modulus_bytes[i] as u64) % 256)
+                    });
+                    let lt_byte_value = lt_byte.map(F::from);
+                    region.assign_advice(|| "lt byte", config.lt_byte, row, || lt_byte_value)?;
+                    region.assign_advice(|| "diff", config.diff, row, || diff)?;
+                    byte_eq.assign(&mut region, row, byte - Value::known(modulus))?;
+
+                    let acc_value = acc.value().copied() * Value::known(F::from(256)) + byte;
+                    let lt_value = lt.value().copied() + eq.value().copied() * lt_byte_value;
+                    let eq_value = eq
+                        .value()
+                        .copied()
+                        .zip(bytes)
+                        .map(|(eq, bytes)| eq * F::from(bytes[i] == modulus_bytes[i]));
+                    acc = region.assign_advice(|| "acc", config.acc, row, || acc_value)?;
+                    lt = region.assign_advice(|| "lt", config.lt, row, || lt_value)?;
+                    eq = region.assign_advice(|| "eq", config.eq, row, || eq_value)?;
+                }
+                config.s_last.enable(&mut region, NUM_BYTES)?;
+
+                if let Some(value) = value {
+                    region.constrain_equal(value.cell(), acc.cell())?;
+                }
+                byte_cells.reverse();
+
+                Ok(FieldBytes {
+                    value: acc,
+                    bytes: byte_cells,
+                })
+            },
+        )
+    }
+}
+
+impl<F: FieldExt> Chip<F> for FieldBytesChip<F> {
+    type Config = FieldBytesConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// The little endian bytes of `p - 1`, the largest canonical encoding.
+fn modulus_minus_one_bytes<F: FieldExt>() -> [u8; NUM_BYTES] {
+    let mut bytes = [0u8; NUM_BYTES];
+    bytes.copy_from_slice((-F::one()).to_repr().as_ref());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_constraint_failure;
+    use crate::soundness::mutation::unconstrained_cells;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::{MockProver, VerifyFailure},
+        pasta::{Fp, Fq},
+        plonk::{Circuit, Instance},
+    };
+
+    #[derive(Clone, Debug)]
+    struct FieldBytesTestConfig<F> {
+        field_bytes: FieldBytesConfig<F>,
+        advice: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Converts `bytes` to a field element and back, and exposes the field
+    /// element followed by the bytes.
+    #[derive(Default)]
+    struct FieldBytesTestCircuit<F: FieldExt> {
+        bytes: [u8; NUM_BYTES],
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for FieldBytesTestCircuit<F> {
+        type Config = FieldBytesTestConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = [(); 6].map(|_| meta.advice_column());
+            let modulus = meta.fixed_column();
+            let u8_table = meta.lookup_table_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            FieldBytesTestConfig {
+                field_bytes: FieldBytesChip::configure(meta, advice, modulus, u8_table),
+                advice: advice[0],
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = FieldBytesChip::construct(config.field_bytes);
+            chip.load(&mut layouter)?;
+
+            let value = chip
+                .compose(
+                    layouter.namespace(|| "from bytes"),
+                    Value::known(self.bytes),
+                )?
+                .value;
+
+            let copy = layouter.assign_region(
+                || "copy value",
+                |mut region| value.copy_advice(|| "value", &mut region, config.advice, 0),
+            )?;
+            let bytes = chip
+                .decompose(layouter.namespace(|| "to bytes"), &copy)?
+                .bytes;
+
+            layouter.constrain_instance(value.cell(), config.instance, 0)?;
+            for (row, byte) in bytes.iter().enumerate() {
+                layouter.constrain_instance(byte.cell(), config.instance, row + 1)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn prover<F: FieldExt>(bytes: [u8; NUM_BYTES], value: F) -> MockProver<F> {
+        let circuit = FieldBytesTestCircuit::<F> {
+            bytes,
+            _marker: PhantomData,
+        };
+        let mut public_inputs = vec![value];
+        public_inputs.extend(bytes.iter().map(|byte| F::from(*byte as u64)));
+        MockProver::run(minimal_k(&circuit), &circuit, vec![public_inputs]).unwrap()
+    }
+
+    fn run<F: FieldExt>(bytes: [u8; NUM_BYTES], value: F) -> Result<(), Vec<VerifyFailure>> {
+        prover(bytes, value).verify()
+    }
+
+    fn assert_non_canonical<F: FieldExt>(bytes: [u8; NUM_BYTES], value: F) {
+        assert_constraint_failure(&prover(bytes, value), "canonical", "field bytes");
+    }
+
+    fn modulus_bytes<F: FieldExt>() -> [u8; NUM_BYTES] {
+        // p = (p - 1) + 1, where the least significant byte of p - 1 is even
+        let mut bytes = modulus_minus_one_bytes::<F>();
+        bytes[0] += 1;
+        bytes
+    }
+
+    fn test_field_bytes<F: FieldExt>() {
+        let mut bytes = [0u8; NUM_BYTES];
+        assert_eq!(run(bytes, F::zero()), Ok(()));
+
+        bytes[0] = 0x2a;
+        bytes[31] = 0x01;
+        let value = F::from(0x2a) + F::from_u128(1 << 124).square();
+        assert_eq!(run(bytes, value), Ok(()));
+
+        assert_eq!(run(modulus_minus_one_bytes::<F>(), -F::one()), Ok(()));
+
+        // p and p + 1 are non-canonical encodings of 0 and 1
+        assert_non_canonical(modulus_bytes::<F>(), F::zero());
+        let mut bytes = modulus_bytes::<F>();
+        bytes[0] += 1;
+        assert_non_canonical(bytes, F::one());
+
+        // The largest byte string is non-canonical as well
+        let max = F::from_u128(u128::MAX);
+        let value = max * F::from_u128(1 << 64).square() + max;
+        assert_non_canonical([0xff; NUM_BYTES], value);
+    }
+
+    #[test]
+    fn test_field_bytes_pallas() {
+        test_field_bytes::<Fp>();
+    }
+
+    #[test]
+    fn test_field_bytes_vesta() {
+        test_field_bytes::<Fq>();
+    }
+
+    #[test]
+    fn test_field_bytes_mutations() {
+        let mut bytes = [0u8; NUM_BYTES];
+        bytes[0] = 0x2a;
+        bytes[31] = 0x01;
+        let value = Fp::from(0x2a) + Fp::from_u128(1 << 124).square();
+        let circuit = FieldBytesTestCircuit::<Fp> {
+            bytes,
+            _marker: PhantomData,
+        };
+        let mut public_inputs = vec![value];
+        public_inputs.extend(bytes.iter().map(|byte| Fp::from(*byte as u64)));
+        // Only the inverses at the bytes equal to those of the modulus are free
+        let unconstrained = unconstrained_cells(&circuit, vec![public_inputs]).unwrap();
+        assert!(!unconstrained.is_empty());
+        for cells in &unconstrained {
+            assert_eq!(cells.names().collect::<Vec<_>>(), ["value inv"]);
+        }
+    }
+}
@@ -1,11 +1,13 @@
 use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
 use std::marker::PhantomData;
 
+use super::fib_three_column::FibPublicInputs;
+
 #[derive(Debug, Clone)]
 struct ACell<F: FieldExt>(AssignedCell<F, F>);
 
 #[derive(Debug, Clone)]
-struct FiboConfig {
+pub(crate) struct FiboConfig {
     advice: Column<Advice>,
     selector: Selector,
     instance: Column<Instance>,
@@ -71,14 +73,14 @@ impl<F: FieldExt> FiboChip<F> {
                 let mut a_cell = region.assign_advice_from_instance(
                     || "1",
                     self.config.instance,
-                    0,
+                    FibPublicInputs::ROWS.a,
                     self.config.advice,
                     0,
                 )?;
                 let mut b_cell = region.assign_advice_from_instance(
                     || "1",
                     self.config.instance,
-                    1,
+                    FibPublicInputs::ROWS.b,
                     self.config.advice,
                     1,
                 )?;
@@ -114,15 +116,35 @@ impl<F: FieldExt> FiboChip<F> {
     }
 }
 
-#[derive(Default)]
-struct MyCircuit<F>(PhantomData<F>);
+/// The table of the first `nrows` Fibonacci numbers, of which the last one is
+/// the public output.
+pub(crate) struct MyCircuit<F> {
+    nrows: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F> MyCircuit<F> {
+    pub fn new(nrows: usize) -> Self {
+        Self {
+            nrows,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F> Default for MyCircuit<F> {
+    /// The table of F[0] to F[9].
+    fn default() -> Self {
+        Self::new(10)
+    }
+}
 
 impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     type Config = FiboConfig;
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        Self::new(self.nrows)
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -138,9 +160,13 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     ) -> Result<(), Error> {
         let chip = FiboChip::construct(config);
 
-        let out_cell = chip.assign(layouter.namespace(|| "entire table"), 10)?;
+        let out_cell = chip.assign(layouter.namespace(|| "entire table"), self.nrows)?;
 
-        chip.expose_public(layouter.namespace(|| "out"), out_cell, 2)?;
+        chip.expose_public(
+            layouter.namespace(|| "out"),
+            out_cell,
+            FibPublicInputs::ROWS.out,
+        )?;
 
         Ok(())
     }
@@ -150,30 +176,72 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::proving::public_inputs::PublicInputs;
+    use crate::soundness::failures::{assert_constraint_failure, assert_missing_instance};
+    use crate::soundness::mutation::tampered_prover;
+    use crate::strategies::field;
+    use halo2_proofs::pasta::Fq;
+    use proptest::prelude::*;
     use halo2_proofs::{dev::MockProver, pasta::Fp};
 
  
     #[test]
     fn test_fib1() {
-        let k = 4;
-
         let a = Fp::from(1); // F[0]
         let b = Fp::from(1); // F[1]
         let out = Fp::from(55); // F[9]
 
-        let circuit = MyCircuit(PhantomData);
+        let circuit = MyCircuit::default();
 
-        let mut public_input = vec![a, b, out];
+        let mut public_inputs = FibPublicInputs { a, b, out };
 
-        let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
         prover.assert_satisfied();
 
-        public_input[2] += Fp::one();
-        let _prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+        public_inputs.out += Fp::one();
+        let _prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
         // uncomment the following line and the assert will fail
         // _prover.assert_satisfied();
     }
 
+    #[test]
+    fn test_fib1_longer_table() {
+        // The table of F[0] to F[19]
+        let circuit = MyCircuit::new(20);
+        let public_inputs = FibPublicInputs {
+            a: Fp::one(),
+            b: Fp::one(),
+            out: Fp::from(6765),
+        };
+
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_fib1_tampered() {
+        let circuit = MyCircuit::default();
+        let public_inputs = FibPublicInputs {
+            a: Fp::one(),
+            b: Fp::one(),
+            out: Fp::from(55),
+        };
+
+        // The first computed row no longer is the sum of the previous two
+        let prover = tampered_prover(
+            &circuit,
+            public_inputs.to_instances(),
+            "entire fibonacci table",
+            "advice",
+            Fp::one(),
+        )
+        .unwrap();
+        assert_constraint_failure(&prover, "add", "entire fibonacci table");
+    }
+
     #[cfg(feature = "dev-graph")]
     #[test]
     fn plot_fibo1() {
@@ -182,9 +250,38 @@ mod tests {
         root.fill(&WHITE).unwrap();
         let root = root.titled("Fib 1 Layout", ("sans-serif", 60)).unwrap();
 
-        let circuit = MyCircuit::<Fp>(PhantomData);
+        let circuit = MyCircuit::<Fp>::default();
         halo2_proofs::dev::CircuitLayout::default()
-            .render(4, &circuit, &root)
+            .render(minimal_k(&circuit), &circuit, &root)
             .unwrap();
     }
-}
\ No newline at end of file
+
+    fn check_fib<F: FieldExt>(a: F, b: F) -> Result<(), TestCaseError> {
+        let circuit = MyCircuit::default();
+        let out = (2..10).fold((a, b), |(a, b), _| (b, a + b)).1;
+        let mut public_inputs = FibPublicInputs { a, b, out };
+
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+        prop_assert_eq!(prover.verify(), Ok(()));
+
+        public_inputs.out += F::one();
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+        assert_missing_instance(&prover, 0, FibPublicInputs::ROWS.out);
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_fib1_matches_native_pallas(a in field::<Fp>(), b in field::<Fp>()) {
+            check_fib(a, b)?;
+        }
+
+        #[test]
+        fn test_fib1_matches_native_vesta(a in field::<Fq>(), b in field::<Fq>()) {
+            check_fib(a, b)?;
+        }
+    }
+}
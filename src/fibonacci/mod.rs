@@ -1,2 +1,2 @@
-mod fib_three_column;
-mod fib_one_column;
+pub(crate) mod fib_three_column;
+pub(crate) mod fib_one_column;
@@ -2,11 +2,13 @@ use std::marker::PhantomData;
 
 use halo2_proofs::{plonk::*, arithmetic::FieldExt, poly::Rotation, circuit::*};
 
+use crate::proving::public_inputs::public_inputs;
+
 #[derive(Debug, Clone)]
 struct ACell<F: FieldExt>(AssignedCell<F, F>);
 
 #[derive(Debug, Clone)]
-struct FiboConfig {
+pub(crate) struct FiboConfig {
     pub advice: [Column<Advice>; 3],
     pub selector: Selector,
     pub instance: Column<Instance>,
@@ -134,11 +136,15 @@ impl<F: FieldExt> FiboChip<F> {
 
 
 #[derive(Default)]
-struct MyCircuit<F> {
+pub(crate) struct MyCircuit<F> {
     pub a: Value<F>,
     pub b: Value<F>,
 }
 
+public_inputs! {
+    /// The first two elements of the sequence and the tenth element F[9]
+    struct FibSquaresPublicInputs { a, b, out }
+}
 
 impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     type Config = FiboConfig;
@@ -166,8 +172,16 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
         let (prev_a, mut prev_b, mut prev_c) =
             chip.assign_first_row(layouter.namespace(|| "first row"), self.a, self.b)?;
 
-        chip.expose_public(layouter.namespace(|| "private a"), &prev_a, 0)?;
-        chip.expose_public(layouter.namespace(|| "private b"), &prev_b, 1)?;
+        chip.expose_public(
+            layouter.namespace(|| "private a"),
+            &prev_a,
+            FibSquaresPublicInputs::ROWS.a,
+        )?;
+        chip.expose_public(
+            layouter.namespace(|| "private b"),
+            &prev_b,
+            FibSquaresPublicInputs::ROWS.b,
+        )?;
 
         for _i in 3..10 {
             let c_cell = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
@@ -175,7 +189,11 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
             prev_c = c_cell;
         }
 
-        chip.expose_public(layouter.namespace(|| "out"), &prev_c, 2)?;
+        chip.expose_public(
+            layouter.namespace(|| "out"),
+            &prev_c,
+            FibSquaresPublicInputs::ROWS.out,
+        )?;
 
         Ok(())
     }
@@ -183,13 +201,18 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
 
 #[cfg(test)]
 mod tests {
-    use super::MyCircuit;
+    use super::{FibSquaresPublicInputs, MyCircuit};
+    use crate::cost::rows::minimal_k;
+    use crate::proving::public_inputs::PublicInputs;
+    use crate::soundness::failures::{assert_constraint_failure, assert_missing_instance};
+    use crate::soundness::mutation::tampered_prover;
+    use crate::strategies::field;
+    use halo2_proofs::{arithmetic::FieldExt, pasta::Fq};
+    use proptest::prelude::*;
     use halo2_proofs::{circuit::Value, dev::MockProver, pasta::Fp};
 
     #[test]
     fn test_fibsquares3() {
-        let k = 4;
-
         let a = Fp::from(1); // F[0]
         let b = Fp::from(1); // F[1]
         let out =  Fp::from_raw([  // F[9] 
@@ -204,10 +227,22 @@ mod tests {
             b: Value::known(b),
         };
 
-        let public_input = vec![a, b, out];
+        let public_inputs = FibSquaresPublicInputs { a, b, out };
 
-        let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
         prover.assert_satisfied();
+
+        // c is no longer a^2 + b^2 in the first "next row" region
+        let prover = tampered_prover(
+            &circuit,
+            public_inputs.to_instances(),
+            "next row",
+            "c",
+            Fp::one(),
+        )
+        .unwrap();
+        assert_constraint_failure(&prover, "add", "next row");
     }
 
 
@@ -225,7 +260,47 @@ mod tests {
             b: Value::unknown(),
         };
         halo2_proofs::dev::CircuitLayout::default()
-            .render(4, &circuit, &root)
+            .render(minimal_k(&circuit), &circuit, &root)
             .unwrap();
     }
-}
\ No newline at end of file
+
+    /// F[9] of the sequence F[i] = F[i-2]^2 + F[i-1]^2 starting with `a` and `b`.
+    fn fib_squares<F: FieldExt>(a: F, b: F) -> F {
+        (2..10).fold((a, b), |(a, b), _| (b, a.square() + b.square())).1
+    }
+
+    fn check_fib_squares<F: FieldExt>(a: F, b: F) -> Result<(), TestCaseError> {
+        let circuit = MyCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let mut public_inputs = FibSquaresPublicInputs {
+            a,
+            b,
+            out: fib_squares(a, b),
+        };
+
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+        prop_assert_eq!(prover.verify(), Ok(()));
+
+        public_inputs.out += F::one();
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+        assert_missing_instance(&prover, 0, FibSquaresPublicInputs::ROWS.out);
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_fibsquares3_matches_native_pallas(a in field::<Fp>(), b in field::<Fp>()) {
+            check_fib_squares(a, b)?;
+        }
+
+        #[test]
+        fn test_fibsquares3_matches_native_vesta(a in field::<Fq>(), b in field::<Fq>()) {
+            check_fib_squares(a, b)?;
+        }
+    }
+}
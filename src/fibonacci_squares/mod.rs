@@ -1 +1 @@
-mod fib_squares_three_column;
+pub(crate) mod fib_squares_three_column;
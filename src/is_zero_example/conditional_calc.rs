@@ -1,20 +1,21 @@
 use super::is_zero::{IsZeroChip, IsZeroConfig};
+use crate::multiplexer::select::{SelectChip, SelectConfig};
 
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
     poly::Rotation,
 };
 
 #[derive(Debug, Clone)]
-struct FunctionConfig<F: FieldExt> {
+pub(crate) struct FunctionConfig<F: FieldExt> {
     selector: Selector,
     a: Column<Advice>,
     b: Column<Advice>,
     c: Column<Advice>,
     a_equals_b: IsZeroConfig<F>,
-    output: Column<Advice>,
+    select: SelectConfig<F>,
 }
 
 #[derive(Debug, Clone)]
@@ -35,24 +36,24 @@ impl<F: FieldExt> FunctionChip<F> {
         let output = meta.advice_column();
 
         let is_zero_advice_column = meta.advice_column();
-        let a_equals_b = IsZeroChip::configure(
+        let is_zero_output = meta.advice_column();
+        let a_equals_b = IsZeroChip::configure_with_output(
             meta,
             |meta| meta.query_selector(selector),
             |meta| meta.query_advice(a, Rotation::cur()) - meta.query_advice(b, Rotation::cur()),
             is_zero_advice_column,
+            is_zero_output,
         );
 
-        meta.create_gate("f(a, b, c) = if a == b {c} else {a - b}", |meta| {
-            let s = meta.query_selector(selector);
-            let a = meta.query_advice(a, Rotation::cur());
-            let b = meta.query_advice(b, Rotation::cur());
-            let c = meta.query_advice(c, Rotation::cur());
-            let output = meta.query_advice(output, Rotation::cur());
-            vec![
-                s.clone() * (a_equals_b.expr() * (output.clone() - c)),
-                s * (Expression::Constant(F::one()) - a_equals_b.expr()) * (output - (a - b)),
-            ]
-        });
+        // f(a, b, c) = if a == b {c} else {a - b}
+        let select = SelectChip::configure(
+            meta,
+            |meta| meta.query_selector(selector),
+            |meta| meta.query_advice(is_zero_output, Rotation::cur()),
+            |meta| meta.query_advice(c, Rotation::cur()),
+            |meta| meta.query_advice(a, Rotation::cur()) - meta.query_advice(b, Rotation::cur()),
+            output,
+        );
 
         FunctionConfig {
             selector,
@@ -60,7 +61,7 @@ impl<F: FieldExt> FunctionChip<F> {
             b,
             c,
             a_equals_b,
-            output,
+            select,
         }
     }
 
@@ -72,6 +73,7 @@ impl<F: FieldExt> FunctionChip<F> {
         c: F,
     ) -> Result<AssignedCell<F, F>, Error> {
         let is_zero_chip = IsZeroChip::construct(self.config.a_equals_b.clone());
+        let select_chip = SelectChip::construct(self.config.select.clone());
 
         layouter.assign_region(
             || "f(a, b, c) = if a == b {c} else {a - b}",
@@ -80,20 +82,27 @@ impl<F: FieldExt> FunctionChip<F> {
                 region.assign_advice(|| "a", self.config.a, 0, || Value::known(a))?;
                 region.assign_advice(|| "b", self.config.b, 0, || Value::known(b))?;
                 region.assign_advice(|| "c", self.config.c, 0, || Value::known(c))?;
-                is_zero_chip.assign(&mut region, 0, Value::known(a - b))?;
-
-                let output = if a == b { c } else { a - b };
-                region.assign_advice(|| "output", self.config.output, 0, || Value::known(output))
+                let a_equals_b = is_zero_chip
+                    .assign(&mut region, 0, Value::known(a - b))?
+                    .expect("is_zero is configured with an output column");
+
+                select_chip.assign(
+                    &mut region,
+                    0,
+                    a_equals_b.value().copied(),
+                    Value::known(c),
+                    Value::known(a - b),
+                )
             },
         )
     }
 }
 
 #[derive(Default)]
-struct FunctionCircuit<F> {
-    a: F,
-    b: F,
-    c: F,
+pub(crate) struct FunctionCircuit<F> {
+    pub a: F,
+    pub b: F,
+    pub c: F,
 }
 
 impl<F: FieldExt> Circuit<F> for FunctionCircuit<F> {
@@ -118,6 +127,7 @@ impl<F: FieldExt> Circuit<F> for FunctionCircuit<F> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cost::rows::minimal_k;
     use halo2_proofs::{dev::MockProver, pasta::Fp};
 
     #[test]
@@ -132,6 +142,18 @@ mod tests {
         prover.assert_satisfied();
     }
 
+    #[test]
+    fn test_is_not_zero() {
+        let circuit = FunctionCircuit {
+            a: Fp::from(12),
+            b: Fp::from(5),
+            c: Fp::from(15),
+        };
+
+        let prover = MockProver::run(minimal_k(&circuit), &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
     #[cfg(feature = "dev-graph")]
     #[test]
     fn plot_is_zero() {
@@ -147,7 +169,7 @@ mod tests {
             c: Fp::default(),
         };
         halo2_proofs::dev::CircuitLayout::default()
-            .render(4, &circuit, &root)
+            .render(minimal_k(&circuit), &circuit, &root)
             .unwrap();
     }
 }
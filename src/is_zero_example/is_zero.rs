@@ -3,6 +3,7 @@ use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
 #[derive(Clone, Debug)]
 pub struct IsZeroConfig<F> {
     pub value_inv: Column<Advice>,
+    pub output: Option<Column<Advice>>,
     pub is_zero_expr: Expression<F>,
 }
 
@@ -26,6 +27,28 @@ impl<F: FieldExt> IsZeroChip<F> {
         q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
         value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
         value_inv: Column<Advice>,
+    ) -> IsZeroConfig<F> {
+        Self::configure_inner(meta, q_enable, value, value_inv, None)
+    }
+
+    /// Same as `configure`, but also constrains the `output` cell in the same
+    /// row to equal `is_zero_expr`, so that the result can be copied elsewhere.
+    pub fn configure_with_output(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value_inv: Column<Advice>,
+        output: Column<Advice>,
+    ) -> IsZeroConfig<F> {
+        Self::configure_inner(meta, q_enable, value, value_inv, Some(output))
+    }
+
+    fn configure_inner(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value_inv: Column<Advice>,
+        output: Option<Column<Advice>>,
     ) -> IsZeroConfig<F> {
         let mut is_zero_expr = Expression::Constant(F::zero());
 
@@ -43,23 +66,460 @@ impl<F: FieldExt> IsZeroChip<F> {
             let value_inv = meta.query_advice(value_inv, Rotation::cur());
 
             is_zero_expr = Expression::Constant(F::one()) - value.clone() * value_inv;
-            vec![q_enable * value * is_zero_expr.clone()]
+            let mut constraints = vec![q_enable.clone() * value * is_zero_expr.clone()];
+            if let Some(output) = output {
+                let output = meta.query_advice(output, Rotation::cur());
+                constraints.push(q_enable * (output - is_zero_expr.clone()));
+            }
+            constraints
         });
 
         IsZeroConfig {
             value_inv,
+            output,
             is_zero_expr,
         }
     }
 
+    /// Returns the output cell if the chip was configured with an output column.
     pub fn assign(
         &self,
         region: &mut Region<'_, F>,
         offset: usize,
         value: Value<F>,
-    ) -> Result<(), Error> {
+    ) -> Result<Option<AssignedCell<F, F>>, Error> {
         let value_inv = value.map(|value| value.invert().unwrap_or(F::zero()));
         region.assign_advice(|| "value inv", self.config.value_inv, offset, || value_inv)?;
-        Ok(())
+
+        self.config
+            .output
+            .map(|output| {
+                let is_zero = value.map(|value| F::from(value == F::zero()));
+                region.assign_advice(|| "is zero", output, offset, || is_zero)
+            })
+            .transpose()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AnyZeroConfig<F> {
+    pub value_inv: Column<Advice>,
+    pub output: Column<Advice>,
+    pub any_zero_expr: Expression<F>,
+}
+
+impl<F: FieldExt> AnyZeroConfig<F> {
+    pub fn expr(&self) -> Expression<F> {
+        self.any_zero_expr.clone()
+    }
+}
+
+/// Checks whether at least one of several values is zero, using a single
+/// inverse of their product.
+pub struct AnyZeroChip<F: FieldExt> {
+    config: AnyZeroConfig<F>,
+}
+
+impl<F: FieldExt> AnyZeroChip<F> {
+    pub fn construct(config: AnyZeroConfig<F>) -> Self {
+        AnyZeroChip { config }
+    }
+
+    /// The degree of the gate grows with the number of values, since
+    /// their product is checked for zero.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        values: impl FnOnce(&mut VirtualCells<'_, F>) -> Vec<Expression<F>>,
+        value_inv: Column<Advice>,
+        output: Column<Advice>,
+    ) -> AnyZeroConfig<F> {
+        let mut any_zero_expr = Expression::Constant(F::zero());
+
+        meta.create_gate("any_zero", |meta| {
+            let q_enable = q_enable(meta);
+            let product = values(meta)
+                .into_iter()
+                .reduce(|acc, value| acc * value)
+                .expect("at least one value");
+            let value_inv = meta.query_advice(value_inv, Rotation::cur());
+            let output = meta.query_advice(output, Rotation::cur());
+
+            any_zero_expr = Expression::Constant(F::one()) - product.clone() * value_inv;
+            vec![
+                q_enable.clone() * product * any_zero_expr.clone(),
+                q_enable * (output - any_zero_expr.clone()),
+            ]
+        });
+
+        AnyZeroConfig {
+            value_inv,
+            output,
+            any_zero_expr,
+        }
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        values: &[Value<F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let product = values
+            .iter()
+            .fold(Value::known(F::one()), |acc, value| acc * value);
+        let value_inv = product.map(|product| product.invert().unwrap_or(F::zero()));
+        region.assign_advice(|| "product inv", self.config.value_inv, offset, || value_inv)?;
+
+        let any_zero = product.map(|product| F::from(product == F::zero()));
+        region.assign_advice(|| "any zero", self.config.output, offset, || any_zero)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AllZeroConfig<F> {
+    pub value_inv: Column<Advice>,
+    pub output: Column<Advice>,
+    pub all_zero_expr: Expression<F>,
+}
+
+impl<F: FieldExt> AllZeroConfig<F> {
+    pub fn expr(&self) -> Expression<F> {
+        self.all_zero_expr.clone()
+    }
+}
+
+/// Checks whether all of several values are zero, using a single inverse
+/// of the linear combination `sum_i randomness^i * value_i`.
+///
+/// The output is constrained to annihilate every value, so claiming that all
+/// values are zero is sound for any choice of `randomness`. Claiming that some
+/// value is non-zero requires inverting the linear combination, which is only
+/// possible if some value is non-zero. The randomness therefore only affects
+/// completeness: for non-zero values whose linear combination vanishes the
+/// honest prover cannot assign a satisfying witness. halo2_proofs 0.2 has no
+/// verifier challenges, so the caller should supply randomness which the prover
+/// does not control when choosing the values.
+pub struct AllZeroChip<F: FieldExt> {
+    config: AllZeroConfig<F>,
+}
+
+impl<F: FieldExt> AllZeroChip<F> {
+    pub fn construct(config: AllZeroConfig<F>) -> Self {
+        AllZeroChip { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        values: impl FnOnce(&mut VirtualCells<'_, F>) -> Vec<Expression<F>>,
+        randomness: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value_inv: Column<Advice>,
+        output: Column<Advice>,
+    ) -> AllZeroConfig<F> {
+        let mut all_zero_expr = Expression::Constant(F::zero());
+
+        meta.create_gate("all_zero", |meta| {
+            let q_enable = q_enable(meta);
+            let values = values(meta);
+            let randomness = randomness(meta);
+            let value_inv = meta.query_advice(value_inv, Rotation::cur());
+            let output = meta.query_advice(output, Rotation::cur());
+
+            // Horner evaluation of sum_i randomness^i * value_i
+            let rlc = values
+                .iter()
+                .rev()
+                .cloned()
+                .reduce(|acc, value| acc * randomness.clone() + value)
+                .expect("at least one value");
+
+            all_zero_expr = Expression::Constant(F::one()) - rlc.clone() * value_inv;
+            let mut constraints = vec![
+                q_enable.clone() * rlc * all_zero_expr.clone(),
+                q_enable.clone() * (output.clone() - all_zero_expr.clone()),
+            ];
+            constraints.extend(
+                values
+                    .into_iter()
+                    .map(|value| q_enable.clone() * output.clone() * value),
+            );
+            constraints
+        });
+
+        AllZeroConfig {
+            value_inv,
+            output,
+            all_zero_expr,
+        }
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        values: &[Value<F>],
+        randomness: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let rlc = values
+            .iter()
+            .rev()
+            .fold(Value::known(F::zero()), |acc, value| acc * randomness + value);
+        let value_inv = rlc.map(|rlc| rlc.invert().unwrap_or(F::zero()));
+        region.assign_advice(|| "rlc inv", self.config.value_inv, offset, || value_inv)?;
+
+        let all_zero = rlc.map(|rlc| F::from(rlc == F::zero()));
+        region.assign_advice(|| "all zero", self.config.output, offset, || all_zero)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_constraint_failure;
+    use crate::soundness::mutation::{assert_constrained, unconstrained_cells, UnconstrainedCells};
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    const NUM_VALUES: usize = 3;
+    const RANDOMNESS: u64 = 0x1234_5678_9abc_def1;
+
+    #[derive(Clone, Debug)]
+    struct IsZeroTestConfig {
+        selector: Selector,
+        values: [Column<Advice>; NUM_VALUES],
+        instance: Column<Instance>,
+        is_zero: IsZeroConfig<Fp>,
+        any_zero: AnyZeroConfig<Fp>,
+        all_zero: AllZeroConfig<Fp>,
+    }
+
+    // Checks whether values[0] is zero, whether any of the values is zero and
+    // whether all values are zero. The three outputs are exposed as public inputs.
+    #[derive(Default)]
+    struct IsZeroTestCircuit {
+        values: [Fp; NUM_VALUES],
+        // Witnesses overriding the honest inverses for
+        // values[0], the product and the linear combination.
+        value_invs: Option<[Fp; 3]>,
+    }
+
+    impl Circuit<Fp> for IsZeroTestCircuit {
+        type Config = IsZeroTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let selector = meta.selector();
+            let values = [(); NUM_VALUES].map(|_| meta.advice_column());
+            let [value_inv, product_inv, rlc_inv] = [(); 3].map(|_| meta.advice_column());
+            let [is_zero, any_zero, all_zero] = [(); 3].map(|_| {
+                let output = meta.advice_column();
+                meta.enable_equality(output);
+                output
+            });
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let query_values = |meta: &mut VirtualCells<'_, Fp>| {
+                values
+                    .iter()
+                    .map(|value| meta.query_advice(*value, Rotation::cur()))
+                    .collect()
+            };
+
+            let is_zero = IsZeroChip::configure_with_output(
+                meta,
+                |meta| meta.query_selector(selector),
+                |meta| meta.query_advice(values[0], Rotation::cur()),
+                value_inv,
+                is_zero,
+            );
+            let any_zero = AnyZeroChip::configure(
+                meta,
+                |meta| meta.query_selector(selector),
+                query_values,
+                product_inv,
+                any_zero,
+            );
+            let all_zero = AllZeroChip::configure(
+                meta,
+                |meta| meta.query_selector(selector),
+                query_values,
+                |_| Expression::Constant(Fp::from(RANDOMNESS)),
+                rlc_inv,
+                all_zero,
+            );
+
+            IsZeroTestConfig {
+                selector,
+                values,
+                instance,
+                is_zero,
+                any_zero,
+                all_zero,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let outputs = layouter.assign_region(
+                || "is zero checks",
+                |mut region| {
+                    config.selector.enable(&mut region, 0)?;
+                    let values = self.values.map(Value::known);
+                    for (column, value) in config.values.iter().zip(values) {
+                        region.assign_advice(|| "value", *column, 0, || value)?;
+                    }
+
+                    let is_zero = IsZeroChip::construct(config.is_zero.clone())
+                        .assign(&mut region, 0, values[0])?
+                        .unwrap();
+                    let any_zero = AnyZeroChip::construct(config.any_zero.clone())
+                        .assign(&mut region, 0, &values)?;
+                    let all_zero = AllZeroChip::construct(config.all_zero.clone()).assign(
+                        &mut region,
+                        0,
+                        &values,
+                        Value::known(Fp::from(RANDOMNESS)),
+                    )?;
+
+                    if let Some(value_invs) = self.value_invs {
+                        let columns = [
+                            config.is_zero.value_inv,
+                            config.any_zero.value_inv,
+                            config.all_zero.value_inv,
+                        ];
+                        for (column, value_inv) in columns.iter().zip(value_invs) {
+                            region.assign_advice(
+                                || "overridden inv",
+                                *column,
+                                0,
+                                || Value::known(value_inv),
+                            )?;
+                        }
+                    }
+                    Ok([is_zero, any_zero, all_zero])
+                },
+            )?;
+
+            for (row, cell) in outputs.iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), config.instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn expected_output(values: [Fp; NUM_VALUES]) -> Vec<Fp> {
+        vec![
+            Fp::from(values[0] == Fp::zero()),
+            Fp::from(values.iter().any(|value| *value == Fp::zero())),
+            Fp::from(values.iter().all(|value| *value == Fp::zero())),
+        ]
+    }
+
+    #[test]
+    fn test_is_zero_outputs() {
+        let small_values = [Fp::zero(), Fp::one(), -Fp::one()];
+
+        for a in small_values {
+            for b in small_values {
+                for c in small_values {
+                    let values = [a, b, c];
+                    let circuit = IsZeroTestCircuit {
+                        values,
+                        value_invs: None,
+                    };
+                    let k = minimal_k(&circuit);
+                    let prover =
+                        MockProver::run(k, &circuit, vec![expected_output(values)]).unwrap();
+                    prover.assert_satisfied();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_zero_non_canonical_inverse() {
+        // When the checked value is zero any inverse witness is accepted,
+        // and the outputs are unaffected.
+        let values = [Fp::zero(); NUM_VALUES];
+        let circuit = IsZeroTestCircuit {
+            values,
+            value_invs: Some([Fp::from(5), Fp::from(6), Fp::from(7)]),
+        };
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![expected_output(values)]).unwrap();
+        prover.assert_satisfied();
+
+        // A zero inverse for a non-zero value would claim that the value is zero
+        let values = [Fp::from(3), Fp::from(4), Fp::from(5)];
+        let circuit = IsZeroTestCircuit {
+            values,
+            value_invs: Some([Fp::zero(); 3]),
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::one(); 3]]).unwrap();
+        assert_constraint_failure(&prover, "is_zero", "is zero checks");
+
+        // A wrong non-zero inverse for a non-zero value makes the output non-boolean
+        let circuit = IsZeroTestCircuit {
+            values,
+            value_invs: Some([Fp::from(2); 3]),
+        };
+        let outputs = vec![
+            Fp::one() - values[0] * Fp::from(2),
+            Fp::one() - values[0] * values[1] * values[2] * Fp::from(2),
+            Fp::zero(),
+        ];
+        let prover = MockProver::run(k, &circuit, vec![outputs]).unwrap();
+        assert_constraint_failure(&prover, "is_zero", "is zero checks");
+    }
+
+    #[test]
+    fn test_all_zero_vanishing_linear_combination() {
+        let r = Fp::from(RANDOMNESS);
+
+        // Non-zero values whose linear combination is zero. The honest prover
+        // cannot show that they are not all zero, and claiming that they are all
+        // zero is rejected by the constraints on the individual values.
+        let values = [r, -Fp::one(), Fp::zero()];
+        let circuit = IsZeroTestCircuit {
+            values,
+            value_invs: None,
+        };
+        let mut outputs = expected_output(values);
+        outputs[2] = Fp::one();
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![outputs]).unwrap();
+        assert_constraint_failure(&prover, "all_zero", "is zero checks");
+    }
+
+    #[test]
+    fn test_is_zero_mutations() {
+        let values = [Fp::from(3), Fp::from(4), Fp::from(5)];
+        let circuit = IsZeroTestCircuit {
+            values,
+            value_invs: None,
+        };
+        assert_constrained(&circuit, vec![expected_output(values)]);
+
+        // The inverses of zero values are free
+        let values = [Fp::zero(); NUM_VALUES];
+        let circuit = IsZeroTestCircuit {
+            values,
+            value_invs: None,
+        };
+        let unconstrained = unconstrained_cells(&circuit, vec![expected_output(values)]).unwrap();
+        let names: Vec<&str> = unconstrained
+            .iter()
+            .flat_map(UnconstrainedCells::names)
+            .collect();
+        assert_eq!(names, ["value inv", "product inv", "rlc inv"]);
     }
 }
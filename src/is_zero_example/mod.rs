@@ -1,2 +1,2 @@
-mod conditional_calc;
-mod is_zero;
+pub mod is_zero;
+pub(crate) mod conditional_calc;
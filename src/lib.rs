@@ -1,7 +1,20 @@
 mod fibonacci;
-mod is_zero_example;
+pub mod is_zero_example;
 mod fibonacci_squares;
 mod collatz;
 mod simple_example;
 mod mimc_pallas;
-mod mimc;
\ No newline at end of file
+pub mod mimc;
+pub mod comparison;
+pub mod multiplexer;
+pub mod arithmetic;
+pub mod logic;
+pub mod encoding;
+pub mod proving;
+pub mod recursion;
+pub mod ecc;
+pub mod merkle;
+pub mod cost;
+pub mod soundness;
+#[cfg(test)]
+mod strategies;
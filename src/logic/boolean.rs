@@ -0,0 +1,561 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+/// A variable constrained to be a bit.
+#[derive(Clone, Debug)]
+pub struct Bit<F: FieldExt>(pub AssignedCell<F, F>);
+
+/// An integer of `WIDTH` bits together with its bit decomposition, most
+/// significant bit first as in the Collatz example.
+#[derive(Clone, Debug)]
+pub struct Word<F: FieldExt, const WIDTH: usize> {
+    pub value: AssignedCell<F, F>,
+    pub bits: [Bit<F>; WIDTH],
+}
+
+#[derive(Clone, Copy, Debug)]
+enum BooleanOp {
+    And,
+    Or,
+    Xor,
+    Not,
+}
+
+impl BooleanOp {
+    const ALL: [BooleanOp; 4] = [
+        BooleanOp::And,
+        BooleanOp::Or,
+        BooleanOp::Xor,
+        BooleanOp::Not,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            BooleanOp::And => "and",
+            BooleanOp::Or => "or",
+            BooleanOp::Xor => "xor",
+            BooleanOp::Not => "not",
+        }
+    }
+
+    fn bitwise_name(&self) -> &'static str {
+        match self {
+            BooleanOp::And => "bitwise and",
+            BooleanOp::Or => "bitwise or",
+            BooleanOp::Xor => "bitwise xor",
+            BooleanOp::Not => "bitwise not",
+        }
+    }
+
+    /// The number of inputs, which is also the offset of the output row.
+    fn arity(&self) -> usize {
+        match self {
+            BooleanOp::Not => 1,
+            _ => 2,
+        }
+    }
+
+    /// The output of the operation on bits `lhs` and `rhs`.
+    fn expr<F: FieldExt>(&self, lhs: Expression<F>, rhs: Expression<F>) -> Expression<F> {
+        match self {
+            BooleanOp::And => lhs * rhs,
+            BooleanOp::Or => lhs.clone() + rhs.clone() - lhs * rhs,
+            BooleanOp::Xor => {
+                lhs.clone() + rhs.clone() - Expression::Constant(F::from(2)) * lhs * rhs
+            }
+            BooleanOp::Not => Expression::Constant(F::one()) - lhs,
+        }
+    }
+
+    fn eval<F: FieldExt>(&self, lhs: F, rhs: F) -> F {
+        match self {
+            BooleanOp::And => lhs * rhs,
+            BooleanOp::Or => lhs + rhs - lhs * rhs,
+            BooleanOp::Xor => lhs + rhs - F::from(2) * lhs * rhs,
+            BooleanOp::Not => F::one() - lhs,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BooleanConfig<const WIDTH: usize> {
+    /// The bits of a word, most significant bit first. Single bits are laid
+    /// out in the last column, which holds the least significant bit.
+    bits: [Column<Advice>; WIDTH],
+    /// The value of a word.
+    value: Column<Advice>,
+    s_bit: Selector,
+    s_word: Selector,
+    s_bit_op: [Selector; 4],
+    s_word_op: [Selector; 4],
+}
+
+/// Boolean logic over constrained bits, and the same operations applied
+/// bitwise to integers of `WIDTH` bits.
+///
+/// The inputs and output of an operation are laid out in consecutive rows:
+///
+/// | bits[0] | ... | bits[WIDTH-1] | value |
+/// |---------|-----|---------------|-------|
+/// |   lhs bits                    |       |
+/// |   rhs bits (not for `not`)    |       |
+/// |   output bits                 | out   |
+///
+/// Inputs are copied from previously constrained bits, so the output of each
+/// operation is a bit without any further constraint.
+pub struct BooleanChip<F: FieldExt, const WIDTH: usize> {
+    config: BooleanConfig<WIDTH>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const WIDTH: usize> BooleanChip<F, WIDTH> {
+    pub fn construct(config: BooleanConfig<WIDTH>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        bits: [Column<Advice>; WIDTH],
+        value: Column<Advice>,
+    ) -> BooleanConfig<WIDTH> {
+        // Words are decomposed from the lower 128 bits of the value
+        assert!(WIDTH > 0 && WIDTH <= 128);
+
+        for column in bits {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(value);
+
+        let s_bit = meta.selector();
+        let s_word = meta.selector();
+        let s_bit_op = [(); 4].map(|_| meta.selector());
+        let s_word_op = [(); 4].map(|_| meta.selector());
+
+        let one = Expression::Constant(F::one());
+
+        meta.create_gate("bit is boolean", |meta| {
+            let s = meta.query_selector(s_bit);
+            let bit = meta.query_advice(bits[WIDTH - 1], Rotation::cur());
+            // s * bit * (1-bit) = 0
+            vec![s * bit.clone() * (one.clone() - bit)]
+        });
+
+        meta.create_gate("word decomposition", |meta| {
+            let s = meta.query_selector(s_word);
+            let value = meta.query_advice(value, Rotation::cur());
+            let mut constraints = vec![];
+            let mut word = Expression::Constant(F::zero());
+
+            for column in bits {
+                let bit = meta.query_advice(column, Rotation::cur());
+                constraints.push(s.clone() * bit.clone() * (one.clone() - bit.clone()));
+                word = word * Expression::Constant(F::from(2)) + bit;
+            }
+            constraints.push(s * (value - word));
+            constraints
+        });
+
+        for op in BooleanOp::ALL {
+            let out = Rotation(op.arity() as i32);
+
+            meta.create_gate(op.name(), |meta| {
+                let s = meta.query_selector(s_bit_op[op as usize]);
+                let lhs = meta.query_advice(bits[WIDTH - 1], Rotation::cur());
+                let rhs = meta.query_advice(bits[WIDTH - 1], Rotation::next());
+                let out = meta.query_advice(bits[WIDTH - 1], out);
+                vec![s * (out - op.expr(lhs, rhs))]
+            });
+
+            meta.create_gate(op.bitwise_name(), |meta| {
+                let s = meta.query_selector(s_word_op[op as usize]);
+                bits.iter()
+                    .map(|column| {
+                        let lhs = meta.query_advice(*column, Rotation::cur());
+                        let rhs = meta.query_advice(*column, Rotation::next());
+                        let out = meta.query_advice(*column, out);
+                        s.clone() * (out - op.expr(lhs, rhs))
+                    })
+                    .collect::<Vec<_>>()
+            });
+        }
+
+        BooleanConfig {
+            bits,
+            value,
+            s_bit,
+            s_word,
+            s_bit_op,
+            s_word_op,
+        }
+    }
+
+    /// Assigns a bit, constrained by `bit * (1 - bit) = 0`.
+    pub fn assign_bit(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bit: Value<F>,
+    ) -> Result<Bit<F>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "assign bit",
+            |mut region| {
+                config.s_bit.enable(&mut region, 0)?;
+                region
+                    .assign_advice(|| "bit", config.bits[WIDTH - 1], 0, || bit)
+                    .map(Bit)
+            },
+        )
+    }
+
+    /// Decomposes a value into `WIDTH` bits. The constraints cannot be
+    /// satisfied when the value does not fit into `WIDTH` bits.
+    pub fn assign_word(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<Word<F, WIDTH>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "assign word",
+            |mut region| {
+                config.s_word.enable(&mut region, 0)?;
+                let value_cell = region.assign_advice(|| "word", config.value, 0, || value)?;
+
+                let lower = value.map(|value| value.get_lower_128());
+                let mut bits = vec![];
+                for (i, column) in config.bits.iter().enumerate() {
+                    let bit = lower.map(|lower| F::from(((lower >> (WIDTH - 1 - i)) & 1) as u64));
+                    bits.push(Bit(region.assign_advice(|| "bit", *column, 0, || bit)?));
+                }
+
+                Ok(Word {
+                    value: value_cell,
+                    bits: bits.try_into().unwrap(),
+                })
+            },
+        )
+    }
+
+    pub fn and(&self, layouter: impl Layouter<F>, a: &Bit<F>, b: &Bit<F>) -> Result<Bit<F>, Error> {
+        self.bit_op(layouter, BooleanOp::And, a, Some(b))
+    }
+
+    pub fn or(&self, layouter: impl Layouter<F>, a: &Bit<F>, b: &Bit<F>) -> Result<Bit<F>, Error> {
+        self.bit_op(layouter, BooleanOp::Or, a, Some(b))
+    }
+
+    pub fn xor(&self, layouter: impl Layouter<F>, a: &Bit<F>, b: &Bit<F>) -> Result<Bit<F>, Error> {
+        self.bit_op(layouter, BooleanOp::Xor, a, Some(b))
+    }
+
+    pub fn not(&self, layouter: impl Layouter<F>, a: &Bit<F>) -> Result<Bit<F>, Error> {
+        self.bit_op(layouter, BooleanOp::Not, a, None)
+    }
+
+    pub fn word_and(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Word<F, WIDTH>,
+        b: &Word<F, WIDTH>,
+    ) -> Result<Word<F, WIDTH>, Error> {
+        self.word_op(layouter, BooleanOp::And, a, Some(b))
+    }
+
+    pub fn word_or(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Word<F, WIDTH>,
+        b: &Word<F, WIDTH>,
+    ) -> Result<Word<F, WIDTH>, Error> {
+        self.word_op(layouter, BooleanOp::Or, a, Some(b))
+    }
+
+    pub fn word_xor(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Word<F, WIDTH>,
+        b: &Word<F, WIDTH>,
+    ) -> Result<Word<F, WIDTH>, Error> {
+        self.word_op(layouter, BooleanOp::Xor, a, Some(b))
+    }
+
+    pub fn word_not(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Word<F, WIDTH>,
+    ) -> Result<Word<F, WIDTH>, Error> {
+        self.word_op(layouter, BooleanOp::Not, a, None)
+    }
+
+    fn bit_op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        op: BooleanOp,
+        a: &Bit<F>,
+        b: Option<&Bit<F>>,
+    ) -> Result<Bit<F>, Error> {
+        let config = self.config();
+        let column = config.bits[WIDTH - 1];
+
+        layouter.assign_region(
+            || op.name(),
+            |mut region| {
+                config.s_bit_op[op as usize].enable(&mut region, 0)?;
+                a.0.copy_advice(|| "lhs", &mut region, column, 0)?;
+                let rhs = match b {
+                    Some(b) => {
+                        b.0.copy_advice(|| "rhs", &mut region, column, 1)?
+                            .value()
+                            .copied()
+                    }
+                    None => Value::known(F::zero()),
+                };
+
+                let out = a.0.value().copied().zip(rhs).map(|(a, b)| op.eval(a, b));
+                region
+                    .assign_advice(|| "output", column, op.arity(), || out)
+                    .map(Bit)
+            },
+        )
+    }
+
+    fn word_op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        op: BooleanOp,
+        a: &Word<F, WIDTH>,
+        b: Option<&Word<F, WIDTH>>,
+    ) -> Result<Word<F, WIDTH>, Error> {
+        let config = self.config();
+        let out_row = op.arity();
+
+        layouter.assign_region(
+            || op.bitwise_name(),
+            |mut region| {
+                config.s_word_op[op as usize].enable(&mut region, 0)?;
+                config.s_word.enable(&mut region, out_row)?;
+
+                let mut bits = vec![];
+                let mut out_value = Value::known(F::zero());
+                for (i, column) in config.bits.iter().enumerate() {
+                    let lhs = a.bits[i]
+                        .0
+                        .copy_advice(|| "lhs bit", &mut region, *column, 0)?;
+                    let rhs = match b {
+                        Some(b) => b.bits[i]
+                            .0
+                            .copy_advice(|| "rhs bit", &mut region, *column, 1)?
+                            .value()
+                            .copied(),
+                        None => Value::known(F::zero()),
+                    };
+
+                    let out = lhs.value().copied().zip(rhs).map(|(a, b)| op.eval(a, b));
+                    out_value = out_value.zip(out).map(|(acc, bit)| acc * F::from(2) + bit);
+                    bits.push(Bit(region.assign_advice(
+                        || "output bit",
+                        *column,
+                        out_row,
+                        || out,
+                    )?));
+                }
+
+                let value =
+                    region.assign_advice(|| "output", config.value, out_row, || out_value)?;
+                Ok(Word {
+                    value,
+                    bits: bits.try_into().unwrap(),
+                })
+            },
+        )
+    }
+}
+
+impl<F: FieldExt, const WIDTH: usize> Chip<F> for BooleanChip<F, WIDTH> {
+    type Config = BooleanConfig<WIDTH>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_constraint_failure;
+    use crate::soundness::mutation::assert_constrained;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    const WIDTH: usize = 3;
+
+    #[derive(Clone, Debug)]
+    struct BooleanTestConfig {
+        boolean: BooleanConfig<WIDTH>,
+        instance: Column<Instance>,
+    }
+
+    /// Applies `and`, `or`, `xor` and `not` (of the lhs) to every pair of
+    /// inputs and exposes the results in that order. The inputs are single
+    /// bits when `words` is false.
+    #[derive(Default)]
+    struct BooleanTestCircuit {
+        words: bool,
+        inputs: Vec<(u64, u64)>,
+    }
+
+    impl Circuit<Fp> for BooleanTestCircuit {
+        type Config = BooleanTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let bits = [(); WIDTH].map(|_| meta.advice_column());
+            let value = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            BooleanTestConfig {
+                boolean: BooleanChip::configure(meta, bits, value),
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = BooleanChip::<Fp, WIDTH>::construct(config.boolean);
+
+            let mut outputs = vec![];
+            for (a, b) in self.inputs.iter() {
+                let (a, b) = (Value::known(Fp::from(*a)), Value::known(Fp::from(*b)));
+                if self.words {
+                    let a = chip.assign_word(layouter.namespace(|| "a"), a)?;
+                    let b = chip.assign_word(layouter.namespace(|| "b"), b)?;
+                    outputs.push(chip.word_and(layouter.namespace(|| "a & b"), &a, &b)?.value);
+                    outputs.push(chip.word_or(layouter.namespace(|| "a | b"), &a, &b)?.value);
+                    outputs.push(chip.word_xor(layouter.namespace(|| "a ^ b"), &a, &b)?.value);
+                    outputs.push(chip.word_not(layouter.namespace(|| "!a"), &a)?.value);
+                } else {
+                    let a = chip.assign_bit(layouter.namespace(|| "a"), a)?;
+                    let b = chip.assign_bit(layouter.namespace(|| "b"), b)?;
+                    outputs.push(chip.and(layouter.namespace(|| "a & b"), &a, &b)?.0);
+                    outputs.push(chip.or(layouter.namespace(|| "a | b"), &a, &b)?.0);
+                    outputs.push(chip.xor(layouter.namespace(|| "a ^ b"), &a, &b)?.0);
+                    outputs.push(chip.not(layouter.namespace(|| "!a"), &a)?.0);
+                }
+            }
+
+            for (row, output) in outputs.iter().enumerate() {
+                layouter.constrain_instance(output.cell(), config.instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn expected_output(inputs: &[(u64, u64)], mask: u64) -> Vec<Fp> {
+        inputs
+            .iter()
+            .flat_map(|&(a, b)| [a & b, a | b, a ^ b, !a & mask])
+            .map(Fp::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_boolean_truth_tables() {
+        let inputs = vec![(0, 0), (0, 1), (1, 0), (1, 1)];
+        let public_inputs = expected_output(&inputs, 1);
+
+        let circuit = BooleanTestCircuit {
+            words: false,
+            inputs,
+        };
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_bitwise_truth_tables() {
+        let max = 1 << WIDTH;
+        let inputs: Vec<(u64, u64)> = (0..max)
+            .flat_map(|a| (0..max).map(move |b| (a, b)))
+            .collect();
+        let public_inputs = expected_output(&inputs, max - 1);
+
+        let circuit = BooleanTestCircuit {
+            words: true,
+            inputs,
+        };
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_non_boolean_inputs() {
+        // 2 is not a bit, even though 2 & 0 = 0 is consistent with the gates
+        let circuit = BooleanTestCircuit {
+            words: false,
+            inputs: vec![(2, 0)],
+        };
+        let public_inputs = vec![Fp::zero(), Fp::from(2), Fp::from(2), -Fp::one()];
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert_constraint_failure(&prover, "bit is boolean", "assign bit");
+
+        // 2^WIDTH does not fit into WIDTH bits
+        let circuit = BooleanTestCircuit {
+            words: true,
+            inputs: vec![(1 << WIDTH, 0)],
+        };
+        let public_inputs = expected_output(&[(0, 0)], (1 << WIDTH) - 1);
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert_constraint_failure(&prover, "word decomposition", "assign word");
+    }
+
+    #[test]
+    fn test_boolean_mutations() {
+        let inputs = vec![(0, 1), (1, 1)];
+        let public_inputs = expected_output(&inputs, 1);
+        let circuit = BooleanTestCircuit {
+            words: false,
+            inputs,
+        };
+        assert_constrained(&circuit, vec![public_inputs]);
+
+        let inputs = vec![(0b101, 0b011)];
+        let public_inputs = expected_output(&inputs, (1 << WIDTH) - 1);
+        let circuit = BooleanTestCircuit {
+            words: true,
+            inputs,
+        };
+        assert_constrained(&circuit, vec![public_inputs]);
+    }
+}
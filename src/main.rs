@@ -0,0 +1,89 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use halo2lg_experiments::cost::{
+    examples::{constraint_systems, examples, layouts},
+    report::CostTable,
+};
+
+const USAGE: &str = "usage: halo2lg-experiments cost [--prove]
+       halo2lg-experiments layout [--out DIR] [--width PX] [--height PX]
+       halo2lg-experiments constraints [--json] [NAME]
+
+commands:
+    cost    print the cost of each example circuit; with --prove, also
+            create a proof to measure the prover time
+    layout  write the layout of each example circuit as JSON and SVG to
+            DIR (default: the working directory), with SVG images of
+            1024 x 3096 pixels unless --width or --height is given
+    constraints
+            print the gates, queried cells, lookups and equality columns
+            of each example circuit, or of the example NAME; with --json,
+            print them as JSON";
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+/// Writes `<name>-layout.json` and `<name>-layout.svg` for each example.
+fn export_layouts(options: &[&str]) {
+    let (mut out, mut width, mut height) = (PathBuf::from("."), 1024, 3096);
+    for option in options.chunks(2) {
+        match option {
+            ["--out", dir] => out = PathBuf::from(dir),
+            ["--width", px] => width = px.parse().unwrap_or_else(|_| usage()),
+            ["--height", px] => height = px.parse().unwrap_or_else(|_| usage()),
+            _ => usage(),
+        }
+    }
+
+    fs::create_dir_all(&out).unwrap();
+    for (name, layout) in layouts() {
+        let file = name.replace("::", "-");
+        let json = out.join(format!("{}-layout.json", file));
+        let svg = out.join(format!("{}-layout.svg", file));
+        layout.write_json(&json).unwrap();
+        layout.write_svg(&svg, width, height).unwrap();
+        println!("{}: {} {}", name, json.display(), svg.display());
+    }
+}
+
+/// Prints the constraint systems of the examples, or of the named one.
+fn print_constraints(json: bool, name: Option<&str>) {
+    let constraint_systems: Vec<_> = constraint_systems()
+        .into_iter()
+        .filter(|(example, _)| name.is_none_or(|name| name == example))
+        .collect();
+    if constraint_systems.is_empty() {
+        usage();
+    }
+
+    if json {
+        let json: serde_json::Map<_, _> = constraint_systems
+            .into_iter()
+            .map(|(name, constraints)| (name, serde_json::to_value(constraints).unwrap()))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&json).unwrap());
+    } else {
+        for (name, constraints) in constraint_systems {
+            println!("# {}\n\n{}", name, constraints);
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["cost"] => print!("{}", CostTable(&examples(false))),
+        ["cost", "--prove"] => print!("{}", CostTable(&examples(true))),
+        ["layout", ref options @ ..] => export_layouts(options),
+        ["constraints"] => print_constraints(false, None),
+        ["constraints", "--json"] => print_constraints(true, None),
+        ["constraints", name] => print_constraints(false, Some(name)),
+        ["constraints", "--json", name] => print_constraints(true, Some(name)),
+        _ => usage(),
+    }
+}
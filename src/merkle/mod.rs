@@ -0,0 +1,2 @@
+pub mod nullifier;
+pub mod tree;
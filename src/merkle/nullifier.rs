@@ -0,0 +1,275 @@
+//! Anonymous membership with nullifiers.
+//!
+//! A member holds a secret `s` whose leaf `H(s)` is in a public Merkle tree.
+//! To act in an epoch, e.g. to vote once per round, the member proves that
+//! their leaf is in the tree without revealing it, and publishes the
+//! nullifier `H(s, epoch)`. The nullifier does not reveal the leaf, but is
+//! the same each time the member acts in the same epoch, so the verifier
+//! detects double spends by recording the nullifiers it has seen.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    pasta::Fp,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+use pasta_curves::group::ff::PrimeField;
+
+use super::tree::{hash, MerklePath, MerklePathChip, MerklePathConfig};
+use crate::arithmetic::standard_plonk::{
+    FieldInstructions, Number, StandardPlonkChip, StandardPlonkConfig,
+};
+use crate::mimc::compression::Domain;
+use crate::mimc::mimc_cipher::MiMC5CipherPallasChip;
+use crate::proving::public_inputs::public_inputs;
+
+/// Returns the leaf `H(s)` of a member with the secret `s`, in the domain of
+/// the leaves.
+pub fn leaf(secret: Fp) -> Fp {
+    hash(Domain::MerkleLeaf, &[secret])
+}
+
+/// Returns the nullifier `H(s, epoch)` of a member with the secret `s`, in
+/// the domain of the nullifiers, so that it is unrelated to the leaf and to
+/// the nodes of the tree.
+pub fn nullifier(secret: Fp, epoch: Fp) -> Fp {
+    hash(Domain::Nullifier, &[secret, epoch])
+}
+
+public_inputs! {
+    pub struct NullifierPublicInputs { root, epoch, nullifier }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SpendError {
+    UnknownRoot,
+    DoubleSpend,
+}
+
+impl fmt::Display for SpendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpendError::UnknownRoot => write!(f, "the root is not the root of the tree"),
+            SpendError::DoubleSpend => write!(f, "the nullifier has already been spent"),
+        }
+    }
+}
+
+impl std::error::Error for SpendError {}
+
+/// The verifier's record of the nullifiers spent under a root.
+#[derive(Clone, Debug)]
+pub struct NullifierSet {
+    root: Fp,
+    spent: HashSet<[u8; 32]>,
+}
+
+impl NullifierSet {
+    pub fn new(root: Fp) -> Self {
+        Self {
+            root,
+            spent: HashSet::new(),
+        }
+    }
+
+    pub fn is_spent(&self, nullifier: Fp) -> bool {
+        self.spent.contains(&nullifier.to_repr())
+    }
+
+    /// Records the nullifier of a proof, which must have been verified with
+    /// these public inputs, and rejects it if it has been spent before.
+    pub fn spend(&mut self, public_inputs: &NullifierPublicInputs<Fp>) -> Result<(), SpendError> {
+        if public_inputs.root != self.root {
+            return Err(SpendError::UnknownRoot);
+        }
+        if !self.spent.insert(public_inputs.nullifier.to_repr()) {
+            return Err(SpendError::DoubleSpend);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NullifierConfig {
+    plonk: StandardPlonkConfig,
+    merkle: MerklePathConfig<Fp>,
+}
+
+/// Proves knowledge of a secret `s` whose leaf `H(s)` is in the tree of
+/// depth `DEPTH` with the public root, and that the public nullifier is
+/// `H(s, epoch)` for the public epoch.
+#[derive(Default)]
+pub struct NullifierCircuit<const DEPTH: usize> {
+    pub secret: Value<Fp>,
+    pub epoch: Value<Fp>,
+    pub path: Value<MerklePath>,
+}
+
+impl<const DEPTH: usize> Circuit<Fp> for NullifierCircuit<DEPTH> {
+    type Config = NullifierConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let plonk_advice = [(); 3].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+        let plonk = StandardPlonkChip::configure(meta, plonk_advice, instance, constant);
+
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let state = meta.advice_column();
+        let key_column = meta.advice_column();
+        let round_constants = meta.fixed_column();
+        let merkle = MerklePathChip::<Fp, MiMC5CipherPallasChip>::configure(
+            meta,
+            advice,
+            state,
+            key_column,
+            round_constants,
+        );
+
+        NullifierConfig { plonk, merkle }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = StandardPlonkChip::construct(config.plonk);
+        let merkle = MerklePathChip::<Fp, MiMC5CipherPallasChip>::construct(config.merkle);
+
+        let secret = chip.load_private(layouter.namespace(|| "secret"), self.secret)?;
+        let epoch = chip.load_private(layouter.namespace(|| "epoch"), self.epoch)?;
+
+        let leaf = merkle.hash(
+            layouter.namespace(|| "leaf"),
+            Domain::MerkleLeaf,
+            std::slice::from_ref(&secret.0),
+        )?;
+        let siblings: Vec<Value<Fp>> = (0..DEPTH)
+            .map(|i| self.path.as_ref().map(|path| path.siblings[i]))
+            .collect();
+        let bits: Vec<Value<bool>> = (0..DEPTH)
+            .map(|i| self.path.as_ref().map(|path| path.bits()[i]))
+            .collect();
+        let root = merkle.root(layouter.namespace(|| "root"), &leaf, &siblings, &bits)?;
+
+        let nullifier = merkle.hash(
+            layouter.namespace(|| "nullifier"),
+            Domain::Nullifier,
+            &[secret.0, epoch.0.clone()],
+        )?;
+
+        let rows = NullifierPublicInputs::ROWS;
+        for (name, num, row) in [
+            ("root", Number(root), rows.root),
+            ("epoch", epoch, rows.epoch),
+            ("nullifier", Number(nullifier), rows.nullifier),
+        ] {
+            chip.expose_public(layouter.namespace(|| name), num, row)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::merkle::tree::MerkleTree;
+    use crate::proving::public_inputs::PublicInputs;
+    use crate::soundness::failures::assert_missing_instance;
+    use halo2_proofs::dev::MockProver;
+
+    const DEPTH: usize = 4;
+
+    fn secrets() -> Vec<Fp> {
+        (100..110).map(Fp::from).collect()
+    }
+
+    fn tree() -> MerkleTree {
+        let leaves: Vec<Fp> = secrets().into_iter().map(leaf).collect();
+        MerkleTree::new(DEPTH, &leaves)
+    }
+
+    fn circuit(secret: Fp, epoch: Fp, path: MerklePath) -> NullifierCircuit<DEPTH> {
+        NullifierCircuit {
+            secret: Value::known(secret),
+            epoch: Value::known(epoch),
+            path: Value::known(path),
+        }
+    }
+
+    #[test]
+    fn test_nullifier_circuit() {
+        let tree = tree();
+        let (index, secret) = (5, secrets()[5]);
+        let epoch = Fp::from(1);
+        let public_inputs = NullifierPublicInputs {
+            root: tree.root(),
+            epoch,
+            nullifier: nullifier(secret, epoch),
+        };
+
+        let circuit = circuit(secret, epoch, tree.path(index));
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+        prover.assert_satisfied();
+
+        // The nullifier of another epoch
+        let wrong_nullifier = NullifierPublicInputs {
+            nullifier: nullifier(secret, Fp::from(2)),
+            ..public_inputs
+        };
+        let prover = MockProver::run(k, &circuit, wrong_nullifier.to_instances()).unwrap();
+        assert_missing_instance(&prover, 0, NullifierPublicInputs::ROWS.nullifier);
+
+        // A secret which is not a member
+        let outsider = Fp::from(7);
+        let circuit = self::circuit(outsider, epoch, tree.path(index));
+        let public_inputs = NullifierPublicInputs {
+            nullifier: nullifier(outsider, epoch),
+            ..public_inputs
+        };
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+        assert_missing_instance(&prover, 0, NullifierPublicInputs::ROWS.root);
+    }
+
+    #[test]
+    fn test_double_spend() {
+        let tree = tree();
+        let mut spent = NullifierSet::new(tree.root());
+        let public_inputs = |secret: Fp, epoch: u64| NullifierPublicInputs {
+            root: tree.root(),
+            epoch: Fp::from(epoch),
+            nullifier: nullifier(secret, Fp::from(epoch)),
+        };
+
+        let (alice, bob) = (secrets()[0], secrets()[1]);
+        assert_eq!(spent.spend(&public_inputs(alice, 1)), Ok(()));
+        assert_eq!(spent.spend(&public_inputs(bob, 1)), Ok(()));
+        assert!(spent.is_spent(nullifier(alice, Fp::from(1))));
+
+        // The same member in the same epoch
+        assert_eq!(
+            spent.spend(&public_inputs(alice, 1)),
+            Err(SpendError::DoubleSpend)
+        );
+        // ... and in the next one
+        assert!(!spent.is_spent(nullifier(alice, Fp::from(2))));
+        assert_eq!(spent.spend(&public_inputs(alice, 2)), Ok(()));
+
+        // A proof for another tree
+        let other = NullifierPublicInputs {
+            root: Fp::zero(),
+            ..public_inputs(bob, 3)
+        };
+        assert_eq!(spent.spend(&other), Err(SpendError::UnknownRoot));
+    }
+}
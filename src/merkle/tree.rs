@@ -0,0 +1,415 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    pasta::Fp,
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector},
+    poly::Rotation,
+};
+
+use crate::mimc::compression::{
+    hash_in_domain, Domain, MiMC5CompressionChip, MiMC5CompressionConfig,
+};
+use crate::mimc::mimc_cipher::MiMC5CipherChip;
+use crate::mimc::round_constants::{MIMC_HASH_PALLAS_ROUND_CONSTANTS, NUM_ROUNDS};
+use crate::multiplexer::select::{SelectChip, SelectConfig};
+
+/// Hashes elements of `Fp` in a domain with the one-way hash of
+/// `mimc::compression`.
+pub fn hash(domain: Domain, elements: &[Fp]) -> Fp {
+    hash_in_domain::<Fp, NUM_ROUNDS>(domain, elements, MIMC_HASH_PALLAS_ROUND_CONSTANTS)
+}
+
+/// Returns the parent `H(left, right)` of two nodes, in the domain of the
+/// nodes so that no leaf hash equals a node.
+pub fn node(left: Fp, right: Fp) -> Fp {
+    hash(Domain::MerkleNode, &[left, right])
+}
+
+/// A Merkle tree of a fixed depth, whose nodes are `H(left, right)`. The
+/// leaves which are not set are zero.
+#[derive(Clone, Debug)]
+pub struct MerkleTree {
+    /// The nodes of each level, from the leaves to the root
+    levels: Vec<Vec<Fp>>,
+}
+
+/// The siblings of a leaf from the bottom of the tree, and the index of the
+/// leaf, whose bits tell whether the node at each level is a right child.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath {
+    pub index: usize,
+    pub siblings: Vec<Fp>,
+}
+
+impl MerkleTree {
+    pub fn new(depth: usize, leaves: &[Fp]) -> Self {
+        assert!(leaves.len() <= 1 << depth, "too many leaves");
+
+        let mut level = leaves.to_vec();
+        level.resize(1 << depth, Fp::zero());
+        let mut levels = vec![level];
+        for _ in 0..depth {
+            let level = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|children| node(children[0], children[1]))
+                .collect();
+            levels.push(level);
+        }
+        Self { levels }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    pub fn root(&self) -> Fp {
+        self.levels[self.depth()][0]
+    }
+
+    pub fn path(&self, index: usize) -> MerklePath {
+        assert!(index < 1 << self.depth(), "leaf index out of range");
+
+        let siblings = self.levels[..self.depth()]
+            .iter()
+            .enumerate()
+            .map(|(height, level)| level[(index >> height) ^ 1])
+            .collect();
+        MerklePath { index, siblings }
+    }
+}
+
+impl MerklePath {
+    /// Returns the bit of the index at each level, which is one when the node
+    /// is a right child.
+    pub fn bits(&self) -> Vec<bool> {
+        (0..self.siblings.len())
+            .map(|height| (self.index >> height) & 1 == 1)
+            .collect()
+    }
+
+    /// Returns the root of the tree containing `leaf` at this path.
+    pub fn root(&self, leaf: Fp) -> Fp {
+        self.siblings
+            .iter()
+            .zip(self.bits())
+            .fold(leaf, |child, (sibling, bit)| {
+                if bit {
+                    node(*sibling, child)
+                } else {
+                    node(child, *sibling)
+                }
+            })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MerklePathConfig<F> {
+    node: Column<Advice>,
+    sibling: Column<Advice>,
+    bit: Column<Advice>,
+    right: Column<Advice>,
+    select: SelectConfig<F>,
+    compression: MiMC5CompressionConfig,
+    s_level: Selector,
+}
+
+/// Computes the root of a Merkle tree from a leaf and its path, and hashes
+/// elements in a domain with the `MiMC5CompressionChip` used for the nodes.
+///
+/// At each level the node and its sibling are ordered by the bit of the index
+/// with the `SelectChip`, and then hashed:
+///
+/// ```text
+///  node  | sibling | bit | left               | right                   | selector
+///  ------------------------------------------------------------------------------------
+///  node  | sibling | b   | b ? sibling : node | node + sibling - left   | s_level
+/// ```
+pub struct MerklePathChip<F: FieldExt, C: MiMC5CipherChip<F>> {
+    config: MerklePathConfig<F>,
+    compression: MiMC5CompressionChip<F, C>,
+}
+
+impl<F: FieldExt, C: MiMC5CipherChip<F>> MerklePathChip<F, C> {
+    pub fn construct(config: MerklePathConfig<F>) -> Self {
+        let compression = MiMC5CompressionChip::construct(config.compression.clone());
+        Self {
+            config,
+            compression,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 5],
+        state: Column<Advice>,
+        key_column: Column<Advice>,
+        round_constants: Column<Fixed>,
+    ) -> MerklePathConfig<F> {
+        let compression =
+            MiMC5CompressionChip::<F, C>::configure(meta, state, key_column, round_constants);
+
+        let [node, sibling, bit, left, right] = advice;
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        let s_level = meta.selector();
+
+        let select = SelectChip::configure(
+            meta,
+            |meta| meta.query_selector(s_level),
+            |meta| meta.query_advice(bit, Rotation::cur()),
+            |meta| meta.query_advice(sibling, Rotation::cur()),
+            |meta| meta.query_advice(node, Rotation::cur()),
+            left,
+        );
+
+        meta.create_gate("right child", |meta| {
+            let s = meta.query_selector(s_level);
+            let node = meta.query_advice(node, Rotation::cur());
+            let sibling = meta.query_advice(sibling, Rotation::cur());
+            let left = meta.query_advice(left, Rotation::cur());
+            let right = meta.query_advice(right, Rotation::cur());
+            vec![s * (right - node - sibling + left)]
+        });
+
+        MerklePathConfig {
+            node,
+            sibling,
+            bit,
+            right,
+            select,
+            compression,
+            s_level,
+        }
+    }
+
+    /// Returns the hash of the elements in `domain`, which equals `hash` of
+    /// their values.
+    pub fn hash(
+        &self,
+        layouter: impl Layouter<F>,
+        domain: Domain,
+        elements: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.compression.hash(layouter, domain, elements)
+    }
+
+    /// Returns the root of the tree containing `leaf` at the path given by
+    /// the siblings and the bits of the index, from the bottom of the tree.
+    pub fn root(
+        &self,
+        mut layouter: impl Layouter<F>,
+        leaf: &AssignedCell<F, F>,
+        siblings: &[Value<F>],
+        bits: &[Value<bool>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert_eq!(siblings.len(), bits.len());
+
+        let mut node = leaf.clone();
+        for (height, (sibling, bit)) in siblings.iter().zip(bits).enumerate() {
+            let mut layouter = layouter.namespace(|| format!("level {}", height));
+            let children = self.order(layouter.namespace(|| "order"), &node, *sibling, *bit)?;
+            node = self.hash(layouter.namespace(|| "node"), Domain::MerkleNode, &children)?;
+        }
+        Ok(node)
+    }
+
+    /// Returns the node and its sibling as the left and right children.
+    fn order(
+        &self,
+        mut layouter: impl Layouter<F>,
+        node: &AssignedCell<F, F>,
+        sibling: Value<F>,
+        bit: Value<bool>,
+    ) -> Result<[AssignedCell<F, F>; 2], Error> {
+        let config = &self.config;
+        let select = SelectChip::construct(config.select.clone());
+
+        layouter.assign_region(
+            || "merkle level",
+            |mut region| {
+                config.s_level.enable(&mut region, 0)?;
+                let node = node.copy_advice(|| "node", &mut region, config.node, 0)?;
+                region.assign_advice(|| "sibling", config.sibling, 0, || sibling)?;
+                let bit = bit.map(|bit| F::from(bit as u64));
+                region.assign_advice(|| "bit", config.bit, 0, || bit)?;
+
+                let left = select.assign(&mut region, 0, bit, sibling, node.value().copied())?;
+                let right_value = node.value().copied() + sibling - left.value();
+                let right = region.assign_advice(|| "right", config.right, 0, || right_value)?;
+                Ok([left, right])
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::mimc::mimc_cipher::MiMC5CipherPallasChip;
+    use crate::mimc::primitives::sponge::{permute, sponge};
+    use crate::soundness::failures::assert_missing_instance;
+    use crate::soundness::mutation::assert_constrained;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        plonk::{Circuit, Column, Instance},
+    };
+
+    const DEPTH: usize = 3;
+
+    #[derive(Clone, Debug)]
+    struct MerkleTestConfig {
+        merkle: MerklePathConfig<Fp>,
+        leaf: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes the root of the tree containing `leaf` at `path`.
+    #[derive(Default)]
+    struct MerkleTestCircuit {
+        leaf: Value<Fp>,
+        path: Value<MerklePath>,
+    }
+
+    impl Circuit<Fp> for MerkleTestCircuit {
+        type Config = MerkleTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 5].map(|_| meta.advice_column());
+            let state = meta.advice_column();
+            let key_column = meta.advice_column();
+            let round_constants = meta.fixed_column();
+            let merkle = MerklePathChip::<Fp, MiMC5CipherPallasChip>::configure(
+                meta,
+                advice,
+                state,
+                key_column,
+                round_constants,
+            );
+            let leaf = meta.advice_column();
+            meta.enable_equality(leaf);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            MerkleTestConfig {
+                merkle,
+                leaf,
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = MerklePathChip::<Fp, MiMC5CipherPallasChip>::construct(config.merkle);
+
+            let leaf = layouter.assign_region(
+                || "leaf",
+                |mut region| region.assign_advice(|| "leaf", config.leaf, 0, || self.leaf),
+            )?;
+            let siblings: Vec<Value<Fp>> = (0..DEPTH)
+                .map(|i| self.path.as_ref().map(|path| path.siblings[i]))
+                .collect();
+            let bits: Vec<Value<bool>> = (0..DEPTH)
+                .map(|i| self.path.as_ref().map(|path| path.bits()[i]))
+                .collect();
+            let root = chip.root(layouter.namespace(|| "root"), &leaf, &siblings, &bits)?;
+
+            layouter.constrain_instance(root.cell(), config.instance, 0)
+        }
+    }
+
+    fn leaves() -> Vec<Fp> {
+        (1..=6).map(Fp::from).collect()
+    }
+
+    #[test]
+    fn test_merkle_tree_native() {
+        let tree = MerkleTree::new(DEPTH, &leaves());
+        assert_eq!(tree.depth(), DEPTH);
+        for (index, leaf) in leaves().into_iter().enumerate() {
+            let path = tree.path(index);
+            assert_eq!(path.root(leaf), tree.root());
+            assert_ne!(path.root(leaf + Fp::one()), tree.root());
+        }
+        // The unset leaves are zero
+        assert_eq!(tree.path(7).root(Fp::zero()), tree.root());
+    }
+
+    #[test]
+    fn test_merkle_path_chip() {
+        let tree = MerkleTree::new(DEPTH, &leaves());
+
+        for index in [0, 3, 4] {
+            let circuit = MerkleTestCircuit {
+                leaf: Value::known(leaves()[index]),
+                path: Value::known(tree.path(index)),
+            };
+            let prover =
+                MockProver::run(minimal_k(&circuit), &circuit, vec![vec![tree.root()]]).unwrap();
+            prover.assert_satisfied();
+        }
+
+        // A leaf which is not in the tree, and a leaf at another index
+        for (leaf, index) in [(Fp::from(7), 0), (leaves()[1], 2)] {
+            let circuit = MerkleTestCircuit {
+                leaf: Value::known(leaf),
+                path: Value::known(tree.path(index)),
+            };
+            let prover =
+                MockProver::run(minimal_k(&circuit), &circuit, vec![vec![tree.root()]]).unwrap();
+            assert_missing_instance(&prover, 0, 0);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "leaf index out of range")]
+    fn test_merkle_path_out_of_range() {
+        MerkleTree::new(DEPTH, &leaves()).path(1 << DEPTH);
+    }
+
+    #[test]
+    fn test_merkle_forged_sibling() {
+        let tree = MerkleTree::new(DEPTH, &leaves());
+
+        // With the sponge `H(l, r) = P(P(l) + r)` as the node hash, the
+        // sibling `P(l) + r - P(l')` of another leaf `l'` gives the same parent
+        let (leaf, forged_leaf) = (leaves()[0], Fp::from(7));
+        let mut path = tree.path(0);
+        let sibling = path.siblings[0];
+        path.siblings[0] = permute(leaf) + sibling - permute(forged_leaf);
+        assert_eq!(sponge(&[forged_leaf, path.siblings[0]]), sponge(&[leaf, sibling]));
+
+        // The one-way node hash rejects the forged path
+        assert_ne!(path.root(forged_leaf), tree.root());
+        let circuit = MerkleTestCircuit {
+            leaf: Value::known(forged_leaf),
+            path: Value::known(path),
+        };
+        let prover =
+            MockProver::run(minimal_k(&circuit), &circuit, vec![vec![tree.root()]]).unwrap();
+        assert_missing_instance(&prover, 0, 0);
+    }
+
+    #[test]
+    fn test_merkle_path_mutations() {
+        let tree = MerkleTree::new(DEPTH, &leaves());
+        let circuit = MerkleTestCircuit {
+            leaf: Value::known(leaves()[4]),
+            path: Value::known(tree.path(4)),
+        };
+        assert_constrained(&circuit, vec![vec![tree.root()]]);
+    }
+}
@@ -0,0 +1,344 @@
+//! A one-way hash of field elements built from the MiMC cipher in the
+//! Miyaguchi–Preneel mode.
+//!
+//! The compression of a chaining value `h` and an element `m` is
+//! `f(h, m) = E_h(m) + h + m`, where `E_h` is MiMC5 keyed with `h`. Unlike
+//! the keyless permutation `mimc5_hash`, which is inverted by taking fifth
+//! roots round by round, `f` cannot be inverted without breaking the cipher,
+//! so the hash is one-way and collision resistant if MiMC is a good cipher.
+//!
+//! The elements are hashed by chaining `f` from an initial value which
+//! encodes the domain of the hash and the number of elements, so that the
+//! hashes of different domains, e.g. the leaves and the nodes of a Merkle
+//! tree or the packed elements of byte strings, are unrelated.
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector},
+    poly::Rotation,
+};
+
+use super::mimc_cipher::{MiMC5CipherChip, MiMC5CipherConfig};
+use super::primitives::mimc5_encrypt;
+
+/// The uses of the hash, each of which starts from its own initial value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Domain {
+    SchnorrChallenge,
+    MerkleLeaf,
+    MerkleNode,
+    Nullifier,
+    Bytes,
+    FoldChallenge,
+}
+
+impl Domain {
+    /// Returns the initial chaining value of the hash of `len` elements in
+    /// this domain.
+    pub fn initial_value<F: FieldExt>(self, len: usize) -> F {
+        F::from(((self as u64 + 1) << 32) + len as u64)
+    }
+}
+
+/// Returns the Miyaguchi–Preneel compression `E_h(m) + h + m` of the chaining
+/// value `h` and the element `m`.
+pub fn mimc5_compress<F: FieldExt, const ROUNDS: usize>(
+    chaining: F,
+    message: F,
+    round_constants: [F; ROUNDS],
+) -> F {
+    let mut state = message;
+    mimc5_encrypt(&mut state, chaining, round_constants);
+    state + chaining + message
+}
+
+/// Hashes field elements in a domain by chaining `mimc5_compress` from the
+/// initial value of the domain.
+pub fn hash_in_domain<F: FieldExt, const ROUNDS: usize>(
+    domain: Domain,
+    elements: &[F],
+    round_constants: [F; ROUNDS],
+) -> F {
+    elements
+        .iter()
+        .fold(domain.initial_value(elements.len()), |chaining, element| {
+            mimc5_compress(chaining, *element, round_constants)
+        })
+}
+
+#[derive(Debug, Clone)]
+pub struct MiMC5CompressionConfig {
+    cipher: MiMC5CipherConfig,
+    s_feed_forward: Selector,
+}
+
+/// Computes `f(h, m)` by encrypting `m` under the key `h` with the cipher
+/// table of the `MiMC5CipherChip`, and then adding the key and the message
+/// to the ciphertext in the columns of the table:
+///
+/// ```text
+///  state          | key_column | selector
+///  -----------------------------------------------
+///  E_h(m)         | h          |
+///  E_h(m) + h + m | m          | s_feed_forward
+/// ```
+pub struct MiMC5CompressionChip<F: FieldExt, C: MiMC5CipherChip<F>> {
+    config: MiMC5CompressionConfig,
+    cipher: C,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, C: MiMC5CipherChip<F>> MiMC5CompressionChip<F, C> {
+    pub fn construct(config: MiMC5CompressionConfig) -> Self {
+        let cipher = C::construct(config.cipher.clone());
+        Self {
+            config,
+            cipher,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        state: Column<Advice>,
+        key_column: Column<Advice>,
+        round_constants: Column<Fixed>,
+    ) -> MiMC5CompressionConfig {
+        let cipher = C::configure(meta, state, key_column, round_constants);
+        let s_feed_forward = meta.selector();
+
+        meta.create_gate("feed forward", |meta| {
+            let s = meta.query_selector(s_feed_forward);
+            let ciphertext = meta.query_advice(state, Rotation::prev());
+            let key = meta.query_advice(key_column, Rotation::prev());
+            let message = meta.query_advice(key_column, Rotation::cur());
+            let output = meta.query_advice(state, Rotation::cur());
+            vec![s * (output - ciphertext - key - message)]
+        });
+
+        MiMC5CompressionConfig {
+            cipher,
+            s_feed_forward,
+        }
+    }
+
+    /// Returns `f(h, m)` of the chaining value `h` and the element `m`, which
+    /// equals `mimc5_compress` of their values.
+    pub fn compress(
+        &self,
+        mut layouter: impl Layouter<F>,
+        chaining: &AssignedCell<F, F>,
+        message: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        let ciphertext =
+            self.cipher
+                .encrypt_cells(layouter.namespace(|| "encrypt"), message, chaining)?;
+
+        layouter.assign_region(
+            || "feed forward",
+            |mut region| {
+                config.s_feed_forward.enable(&mut region, 1)?;
+                let state = config.cipher.state;
+                let key_column = config.cipher.key_column;
+                let ciphertext = ciphertext.copy_advice(|| "ciphertext", &mut region, state, 0)?;
+                let chaining = chaining.copy_advice(|| "key", &mut region, key_column, 0)?;
+                let message = message.copy_advice(|| "message", &mut region, key_column, 1)?;
+                let output = ciphertext.value().copied() + chaining.value() + message.value();
+                region.assign_advice(|| "output", state, 1, || output)
+            },
+        )
+    }
+
+    /// Returns the hash of the elements in `domain`, which equals
+    /// `hash_in_domain` of their values.
+    pub fn hash(
+        &self,
+        mut layouter: impl Layouter<F>,
+        domain: Domain,
+        elements: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let key_column = self.config.cipher.key_column;
+        let mut chaining = layouter.assign_region(
+            || "initial value",
+            |mut region| {
+                region.assign_advice_from_constant(
+                    || "initial value",
+                    key_column,
+                    0,
+                    domain.initial_value(elements.len()),
+                )
+            },
+        )?;
+        for element in elements {
+            chaining = self.compress(layouter.namespace(|| "compress"), &chaining, element)?;
+        }
+        Ok(chaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::mimc::mimc_cipher::MiMC5CipherPallasChip;
+    use crate::mimc::primitives::mimc5_hash;
+    use crate::mimc::round_constants::{MIMC_HASH_PALLAS_ROUND_CONSTANTS, NUM_ROUNDS};
+    use crate::soundness::failures::assert_missing_instance;
+    use crate::soundness::mutation::assert_constrained;
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    fn hash(domain: Domain, elements: &[Fp]) -> Fp {
+        hash_in_domain::<Fp, NUM_ROUNDS>(domain, elements, MIMC_HASH_PALLAS_ROUND_CONSTANTS)
+    }
+
+    #[derive(Clone, Debug)]
+    struct HashTestConfig {
+        compression: MiMC5CompressionConfig,
+        input: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    /// Exposes the hash of the elements in the domain.
+    #[derive(Default)]
+    struct HashTestCircuit {
+        domain: Option<Domain>,
+        elements: Vec<Value<Fp>>,
+    }
+
+    impl Circuit<Fp> for HashTestCircuit {
+        type Config = HashTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                domain: self.domain,
+                elements: vec![Value::unknown(); self.elements.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let state = meta.advice_column();
+            let key_column = meta.advice_column();
+            let round_constants = meta.fixed_column();
+            let compression = MiMC5CompressionChip::<Fp, MiMC5CipherPallasChip>::configure(
+                meta,
+                state,
+                key_column,
+                round_constants,
+            );
+            let input = meta.advice_column();
+            meta.enable_equality(input);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            HashTestConfig {
+                compression,
+                input,
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip =
+                MiMC5CompressionChip::<Fp, MiMC5CipherPallasChip>::construct(config.compression);
+
+            let elements = layouter.assign_region(
+                || "elements",
+                |mut region| {
+                    self.elements
+                        .iter()
+                        .enumerate()
+                        .map(|(row, element)| {
+                            region.assign_advice(|| "element", config.input, row, || *element)
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+            let digest = chip.hash(
+                layouter.namespace(|| "hash"),
+                self.domain.unwrap(),
+                &elements,
+            )?;
+
+            layouter.constrain_instance(digest.cell(), config.instance, 0)
+        }
+    }
+
+    fn circuit(domain: Domain, elements: &[Fp]) -> HashTestCircuit {
+        HashTestCircuit {
+            domain: Some(domain),
+            elements: elements.iter().copied().map(Value::known).collect(),
+        }
+    }
+
+    #[test]
+    fn test_hash_in_domain() {
+        let elements = [Fp::from(1), Fp::from(2)];
+        let digest = hash(Domain::SchnorrChallenge, &elements);
+
+        // The domains and the lengths are separated
+        assert_ne!(hash(Domain::MerkleNode, &elements), digest);
+        assert_ne!(hash(Domain::SchnorrChallenge, &[Fp::from(1)]), digest);
+        assert_ne!(
+            hash(
+                Domain::SchnorrChallenge,
+                &[Fp::from(1), Fp::from(2), Fp::zero()]
+            ),
+            digest
+        );
+
+        // The feed forward of the key and the message makes the hash differ
+        // from the ciphertext
+        let h = Domain::SchnorrChallenge.initial_value::<Fp>(2);
+        let mut ciphertext = Fp::from(1);
+        mimc5_encrypt::<Fp, NUM_ROUNDS>(&mut ciphertext, h, MIMC_HASH_PALLAS_ROUND_CONSTANTS);
+        assert_eq!(
+            mimc5_compress::<Fp, NUM_ROUNDS>(h, Fp::from(1), MIMC_HASH_PALLAS_ROUND_CONSTANTS),
+            ciphertext + h + Fp::from(1)
+        );
+
+        // Neither is the sponge of the permutation
+        let mut sponge = Fp::from(1);
+        mimc5_hash::<Fp, NUM_ROUNDS>(&mut sponge, MIMC_HASH_PALLAS_ROUND_CONSTANTS);
+        assert_ne!(hash(Domain::SchnorrChallenge, &[Fp::from(1)]), sponge);
+    }
+
+    #[test]
+    fn test_compression_chip() {
+        let elements = [Fp::from(3), Fp::from(5), Fp::from(7)];
+        let circuit = circuit(Domain::SchnorrChallenge, &elements);
+        let k = minimal_k(&circuit);
+
+        let digest = hash(Domain::SchnorrChallenge, &elements);
+        let prover = MockProver::run(k, &circuit, vec![vec![digest]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The hash of a prefix of the elements
+        let digest = hash(Domain::SchnorrChallenge, &elements[..2]);
+        let prover = MockProver::run(k, &circuit, vec![vec![digest]]).unwrap();
+        assert_missing_instance(&prover, 0, 0);
+    }
+
+    #[test]
+    fn test_compression_mutations() {
+        let elements = [Fp::from(3), Fp::from(5)];
+        let digest = hash(Domain::SchnorrChallenge, &elements);
+        assert_constrained(
+            &circuit(Domain::SchnorrChallenge, &elements),
+            vec![vec![digest]],
+        );
+    }
+}
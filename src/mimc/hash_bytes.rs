@@ -0,0 +1,452 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use super::compression::{hash_in_domain, Domain, MiMC5CompressionChip, MiMC5CompressionConfig};
+use super::mimc_cipher::MiMC5CipherChip;
+use crate::comparison::less_than::load_u8_table;
+
+/// Number of bytes packed into a field element. 31 bytes are less than the
+/// modulus of both Pallas and Vesta, so the packing is injective.
+pub const BYTES_PER_ELEMENT: usize = 31;
+
+/// Packs a byte string into field elements of 31 little endian bytes each,
+/// followed by an element holding the length of the byte string. Without the
+/// length, messages which differ in trailing zero bytes would collide.
+pub fn pack_bytes<F: FieldExt>(bytes: &[u8]) -> Vec<F> {
+    let mut elements: Vec<F> = bytes
+        .chunks(BYTES_PER_ELEMENT)
+        .map(|chunk| {
+            chunk.iter().rev().fold(F::zero(), |acc, byte| {
+                acc * F::from(256) + F::from(*byte as u64)
+            })
+        })
+        .collect();
+    elements.push(F::from(bytes.len() as u64));
+    elements
+}
+
+/// Hashes field elements with the one-way hash of `compression` in the
+/// `Bytes` domain.
+pub fn hash_elements<F: FieldExt, const ROUNDS: usize>(
+    elements: &[F],
+    round_constants: [F; ROUNDS],
+) -> F {
+    hash_in_domain(Domain::Bytes, elements, round_constants)
+}
+
+/// Hashes a byte string by hashing its packed elements with `hash_elements`.
+pub fn hash_bytes<F: FieldExt, const ROUNDS: usize>(
+    bytes: &[u8],
+    round_constants: [F; ROUNDS],
+) -> F {
+    hash_elements(&pack_bytes::<F>(bytes), round_constants)
+}
+
+#[derive(Debug, Clone)]
+pub struct HashBytesConfig {
+    byte: Column<Advice>,
+    acc: Column<Advice>,
+    u8_table: TableColumn,
+    compression: MiMC5CompressionConfig,
+    s_init: Selector,
+    s_pack: Selector,
+}
+
+/// The in-circuit counterpart of `hash_bytes`.
+///
+/// Each packed element is accumulated from its bytes, most significant byte
+/// first, and the elements are then hashed with the `MiMC5CompressionChip`:
+///
+/// ```text
+///  byte                | acc                   | selector
+///  ------------------------------------------------------------
+///                      | 0                     | s_init
+///  b_30                | b_30                  | s_pack
+///  b_29                | 256*b_30 + b_29       | s_pack
+///       :              |      :                |   :
+///  b_0                 | element               | s_pack
+/// ```
+///
+/// The length element is loaded as a constant instead of being packed.
+pub struct HashBytesChip<F: FieldExt, C: MiMC5CipherChip<F>> {
+    config: HashBytesConfig,
+    compression: MiMC5CompressionChip<F, C>,
+}
+
+impl<F: FieldExt, C: MiMC5CipherChip<F>> HashBytesChip<F, C> {
+    pub fn construct(config: HashBytesConfig) -> Self {
+        let compression = MiMC5CompressionChip::construct(config.compression.clone());
+        Self {
+            config,
+            compression,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        byte: Column<Advice>,
+        acc: Column<Advice>,
+        state: Column<Advice>,
+        key_column: Column<Advice>,
+        round_constants: Column<Fixed>,
+        u8_table: TableColumn,
+    ) -> HashBytesConfig {
+        // The round constants column is also enabled for constants
+        let compression =
+            MiMC5CompressionChip::<F, C>::configure(meta, state, key_column, round_constants);
+
+        meta.enable_equality(byte);
+        meta.enable_equality(acc);
+
+        let s_init = meta.selector();
+        let s_pack = meta.complex_selector();
+
+        meta.lookup(|meta| {
+            let s = meta.query_selector(s_pack);
+            let byte = meta.query_advice(byte, Rotation::cur());
+            vec![(s * byte, u8_table)]
+        });
+
+        meta.create_gate("init accumulator", |meta| {
+            let s = meta.query_selector(s_init);
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![s * acc]
+        });
+
+        meta.create_gate("pack byte", |meta| {
+            let s = meta.query_selector(s_pack);
+            let byte = meta.query_advice(byte, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            vec![s * (acc - acc_prev * Expression::Constant(F::from(256)) - byte)]
+        });
+
+        HashBytesConfig {
+            byte,
+            acc,
+            u8_table,
+            compression,
+            s_init,
+            s_pack,
+        }
+    }
+
+    /// Loads the byte table. If several chips share the same `u8_table`,
+    /// it must be loaded only once.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        load_u8_table(layouter, self.config.u8_table)
+    }
+
+    /// Returns the digest of the bytes, which equals `hash_bytes` of their
+    /// values. The number of bytes is part of the circuit.
+    pub fn hash_bytes(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bytes: &[Value<u8>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let mut elements = bytes
+            .chunks(BYTES_PER_ELEMENT)
+            .map(|chunk| self.pack_chunk(layouter.namespace(|| "pack chunk"), chunk))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let length = F::from(bytes.len() as u64);
+        let length = layouter.assign_region(
+            || "length",
+            |mut region| {
+                region.assign_advice_from_constant(|| "length", self.config.acc, 0, length)
+            },
+        )?;
+        elements.push(length);
+
+        self.hash_elements(layouter.namespace(|| "hash elements"), &elements)
+    }
+
+    /// Returns the digest of the elements, which equals `hash_elements` of
+    /// their values.
+    pub fn hash_elements(
+        &self,
+        layouter: impl Layouter<F>,
+        elements: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.compression.hash(layouter, Domain::Bytes, elements)
+    }
+
+    fn pack_chunk(
+        &self,
+        mut layouter: impl Layouter<F>,
+        chunk: &[Value<u8>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+
+        layouter.assign_region(
+            || "pack chunk",
+            |mut region| {
+                config.s_init.enable(&mut region, 0)?;
+                let mut acc =
+                    region.assign_advice(|| "acc", config.acc, 0, || Value::known(F::zero()))?;
+
+                for (row, byte) in chunk.iter().rev().enumerate() {
+                    let row = row + 1;
+                    config.s_pack.enable(&mut region, row)?;
+                    let byte = byte.map(|byte| F::from(byte as u64));
+                    region.assign_advice(|| "byte", config.byte, row, || byte)?;
+                    let acc_value = acc.value().copied() * Value::known(F::from(256)) + byte;
+                    acc = region.assign_advice(|| "acc", config.acc, row, || acc_value)?;
+                }
+                Ok(acc)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::mimc::mimc_cipher::{MiMC5CipherPallasChip, MiMC5CipherVestaChip};
+    use crate::mimc::primitives::sponge::{permute, permute_inverse, sponge};
+    use crate::mimc::round_constants::{
+        MIMC_HASH_PALLAS_ROUND_CONSTANTS, MIMC_HASH_VESTA_ROUND_CONSTANTS, NUM_ROUNDS,
+    };
+    use crate::soundness::failures::assert_missing_instance;
+    use crate::soundness::mutation::assert_constrained;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::{Fp, Fq},
+        plonk::{Circuit, Instance},
+    };
+    use proptest::prelude::*;
+    use std::marker::PhantomData;
+
+    #[derive(Debug, Clone)]
+    struct HashBytesTestConfig {
+        hash_bytes: HashBytesConfig,
+        instance: Column<Instance>,
+    }
+
+    #[derive(Default)]
+    struct HashBytesTestCircuit<F: FieldExt, C: MiMC5CipherChip<F>> {
+        bytes: Vec<u8>,
+        _marker: PhantomData<(F, C)>,
+    }
+
+    impl<F: FieldExt, C: MiMC5CipherChip<F>> Circuit<F> for HashBytesTestCircuit<F, C> {
+        type Config = HashBytesTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        // The length of the message is part of the circuit
+        fn without_witnesses(&self) -> Self {
+            Self {
+                bytes: vec![0; self.bytes.len()],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let byte = meta.advice_column();
+            let acc = meta.advice_column();
+            let state = meta.advice_column();
+            let key_column = meta.advice_column();
+            let round_constants = meta.fixed_column();
+            let u8_table = meta.lookup_table_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            HashBytesTestConfig {
+                hash_bytes: HashBytesChip::<F, C>::configure(
+                    meta,
+                    byte,
+                    acc,
+                    state,
+                    key_column,
+                    round_constants,
+                    u8_table,
+                ),
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = HashBytesChip::<F, C>::construct(config.hash_bytes);
+            chip.load(&mut layouter)?;
+
+            let bytes: Vec<Value<u8>> = self.bytes.iter().copied().map(Value::known).collect();
+            let digest = chip.hash_bytes(layouter.namespace(|| "hash bytes"), &bytes)?;
+            layouter.constrain_instance(digest.cell(), config.instance, 0)
+        }
+    }
+
+    /// Exposes the hash of arbitrary elements, which need not be packed bytes.
+    #[derive(Default)]
+    struct HashElementsTestCircuit {
+        elements: Vec<Value<Fp>>,
+    }
+
+    impl Circuit<Fp> for HashElementsTestCircuit {
+        type Config = HashBytesTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                elements: vec![Value::unknown(); self.elements.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            HashBytesTestCircuit::<Fp, MiMC5CipherPallasChip>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = HashBytesChip::<Fp, MiMC5CipherPallasChip>::construct(config.hash_bytes);
+            let acc = chip.config.acc;
+
+            let elements = layouter.assign_region(
+                || "elements",
+                |mut region| {
+                    self.elements
+                        .iter()
+                        .enumerate()
+                        .map(|(row, element)| {
+                            region.assign_advice(|| "element", acc, row, || *element)
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+            let digest = chip.hash_elements(layouter.namespace(|| "hash elements"), &elements)?;
+            layouter.constrain_instance(digest.cell(), config.instance, 0)
+        }
+    }
+
+    fn record() -> Vec<u8> {
+        (0..100u8)
+            .map(|i| i.wrapping_mul(37).wrapping_add(11))
+            .collect()
+    }
+
+    #[test]
+    fn test_hash_bytes_pallas() {
+        for bytes in [vec![], vec![0xff; 31], vec![0xff; 32], record()] {
+            let digest = hash_bytes(&bytes, MIMC_HASH_PALLAS_ROUND_CONSTANTS);
+            let circuit = HashBytesTestCircuit::<Fp, MiMC5CipherPallasChip> {
+                bytes,
+                _marker: PhantomData,
+            };
+            let k = minimal_k(&circuit);
+            let prover = MockProver::run(k, &circuit, vec![vec![digest]]).unwrap();
+            prover.assert_satisfied();
+
+            let prover = MockProver::run(k, &circuit, vec![vec![digest + Fp::one()]]).unwrap();
+            assert_missing_instance(&prover, 0, 0);
+        }
+    }
+
+    #[test]
+    fn test_hash_bytes_vesta() {
+        let bytes = record();
+
+        let digest = hash_bytes(&bytes, MIMC_HASH_VESTA_ROUND_CONSTANTS);
+        let circuit = HashBytesTestCircuit::<Fq, MiMC5CipherVestaChip> {
+            bytes,
+            _marker: PhantomData,
+        };
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![vec![digest]]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn test_hash_bytes_length_encoding() {
+        // These messages pack to the same elements apart from the length
+        let messages: [&[u8]; 4] = [&[], &[0], &[0, 0], &[0; 31]];
+        let digests: Vec<Fp> = messages
+            .iter()
+            .map(|bytes| hash_bytes(bytes, MIMC_HASH_PALLAS_ROUND_CONSTANTS))
+            .collect();
+
+        for i in 0..digests.len() {
+            for j in i + 1..digests.len() {
+                assert_ne!(digests[i], digests[j]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_bytes_inverse_collision() {
+        let digest = hash_bytes(&record(), MIMC_HASH_PALLAS_ROUND_CONSTANTS);
+
+        // With the sponge `P(P(m1) + m2)` as the hash of two elements, any
+        // element m1 followed by m2 = P^-1(digest) - P(m1) hits the digest
+        let m1 = pack_bytes::<Fp>(b"forged")[0];
+        let m2 = permute_inverse(digest) - permute(m1);
+        assert_eq!(sponge(&[m1, m2]), digest);
+
+        // The one-way hash of the same elements differs, so the collision
+        // is rejected
+        assert_ne!(
+            hash_elements::<Fp, NUM_ROUNDS>(&[m1, m2], MIMC_HASH_PALLAS_ROUND_CONSTANTS),
+            digest
+        );
+        let circuit = HashElementsTestCircuit {
+            elements: vec![Value::known(m1), Value::known(m2)],
+        };
+        let prover = MockProver::run(minimal_k(&circuit), &circuit, vec![vec![digest]]).unwrap();
+        assert_missing_instance(&prover, 0, 0);
+    }
+
+    #[test]
+    fn test_hash_bytes_mutations() {
+        let bytes = vec![0xff; 33];
+        let digest = hash_bytes(&bytes, MIMC_HASH_PALLAS_ROUND_CONSTANTS);
+        let circuit = HashBytesTestCircuit::<Fp, MiMC5CipherPallasChip> {
+            bytes,
+            _marker: PhantomData,
+        };
+        assert_constrained(&circuit, vec![vec![digest]]);
+    }
+
+    fn check_hash_bytes<F: FieldExt, C: MiMC5CipherChip<F>>(
+        bytes: Vec<u8>,
+        round_constants: [F; NUM_ROUNDS],
+    ) -> Result<(), TestCaseError> {
+        let digest = hash_bytes(&bytes, round_constants);
+        let circuit = HashBytesTestCircuit::<F, C> {
+            bytes,
+            _marker: PhantomData,
+        };
+
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![vec![digest]]).unwrap();
+        prop_assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(k, &circuit, vec![vec![digest + F::one()]]).unwrap();
+        assert_missing_instance(&prover, 0, 0);
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(8))]
+
+        #[test]
+        fn test_hash_bytes_matches_native_pallas(bytes in prop::collection::vec(any::<u8>(), 0..100)) {
+            check_hash_bytes::<Fp, MiMC5CipherPallasChip>(bytes, MIMC_HASH_PALLAS_ROUND_CONSTANTS)?;
+        }
+
+        #[test]
+        fn test_hash_bytes_matches_native_vesta(bytes in prop::collection::vec(any::<u8>(), 0..100)) {
+            check_hash_bytes::<Fq, MiMC5CipherVestaChip>(bytes, MIMC_HASH_VESTA_ROUND_CONSTANTS)?;
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use halo2_proofs::{
     arithmetic::FieldExt,
     plonk::{
-        Column, Advice, Fixed, Selector, ConstraintSystem, Expression, Error,
+        Circuit, Column, Advice, Fixed, Selector, ConstraintSystem, Expression, Error,
     },
     poly::Rotation,
     circuit::{
-        Layouter, AssignedCell, Value,
+        Layouter, AssignedCell, Region, SimpleFloorPlanner, Value,
     },
 };
 use pasta_curves::{Fp, Fq};
@@ -16,8 +16,8 @@ use super::round_constants::{MIMC_HASH_PALLAS_ROUND_CONSTANTS, MIMC_HASH_VESTA_R
 #[allow(unused_variables, dead_code)]
 #[derive(Debug, Clone)]
 pub struct MiMC5CipherConfig {
-    state: Column<Advice>,
-    key_column: Column<Advice>,
+    pub(crate) state: Column<Advice>,
+    pub(crate) key_column: Column<Advice>,
     round_constants: Column<Fixed>,
     s_in_rounds: Selector,
     s_post_rounds: Selector,
@@ -61,7 +61,7 @@ pub trait MiMC5CipherChip<F: FieldExt> {
             };
             let prev_state = meta.query_advice(state, Rotation::prev());
             let key = meta.query_advice(key_column, Rotation::cur());
-            let prev_key = meta.query_advice(key_column, Rotation::cur());
+            let prev_key = meta.query_advice(key_column, Rotation::prev());
             let rc = meta.query_fixed(round_constants, Rotation::prev());
             let current_state = meta.query_advice(state, Rotation::cur());
             vec![
@@ -95,69 +95,114 @@ pub trait MiMC5CipherChip<F: FieldExt> {
     ) -> Result<AssignedCell<F,F>, Error> {
         let config = self.get_config();
 
-        let round_constant_values = Self::get_round_constants();
-
         layouter.assign_region(
             || "MiMC5 table",
             |mut region| {
-
-                region.assign_advice(
+                let message = region.assign_advice(
                     || "message to be hashed",
                     config.state,
                     0,
                     || Value::known(message),
                 )?;
 
-                region.assign_advice(
-                    || format!("key in row 0"),
+                let key = region.assign_advice(
+                    || "key in row 0",
                     config.key_column,
                     0,
                     || Value::known(key)
                 )?;
 
+                self.assign_rounds(&mut region, &message, &key)
+            }
+        )
+    }
 
-                let pow_5 = |v: F| { v*v*v*v*v };
-
-                let mut current_state = message;
-
-                for i in 1..=round_constant_values.len() {
-                    config.s_in_rounds.enable(&mut region, i)?;
-                    region.assign_fixed(
-                        || format!("round constant {:?}", i),
-                        config.round_constants,
-                        i-1,
-                        || Value::known(round_constant_values[i-1]) // i starts at 1
-                    )?;
-
-                    region.assign_advice(
-                        || format!("key in row {:?} ", i),
-                        config.key_column,
-                        i,
-                        || Value::known(key)
-                    )?;
-
-                    current_state = pow_5(current_state + key + round_constant_values[i-1]);
-                    region.assign_advice(
-                        || format!("round {:?} output", i),
-                        config.state,
-                        i,
-                        || Value::known(current_state)
-                    )?;
-                }
-
-                current_state = current_state + key;
-
-                let ciphertext =
-                region.assign_advice(
-                    || "final state",
+    // Encrypts a message with a key which have already been assigned, e.g.
+    // by another chip or by a previous encryption
+    fn encrypt_cells(
+        &self,
+        mut layouter: impl Layouter<F>,
+        message: &AssignedCell<F,F>,
+        key: &AssignedCell<F,F>,
+    ) -> Result<AssignedCell<F,F>, Error> {
+        let config = self.get_config();
+
+        layouter.assign_region(
+            || "MiMC5 table",
+            |mut region| {
+                let message = message.copy_advice(
+                    || "message to be hashed",
+                    &mut region,
                     config.state,
-                    round_constant_values.len()+1,
-                    || Value::known(current_state)
+                    0,
                 )?;
-                Ok(ciphertext)
+
+                let key = key.copy_advice(
+                    || "key in row 0",
+                    &mut region,
+                    config.key_column,
+                    0,
+                )?;
+
+                self.assign_rounds(&mut region, &message, &key)
             }
         )
     }
+
+    // Assigns the rounds and the post-round key addition following the
+    // message and the key in row 0 of the region
+    fn assign_rounds(
+        &self,
+        region: &mut Region<'_, F>,
+        message: &AssignedCell<F,F>,
+        key: &AssignedCell<F,F>,
+    ) -> Result<AssignedCell<F,F>, Error> {
+        let config = self.get_config();
+
+        let round_constant_values = Self::get_round_constants();
+
+        config.s_post_rounds.enable(region, round_constant_values.len() + 1)?;
+
+        let pow_5 = |v: F| { v*v*v*v*v };
+
+        let key = key.value().copied();
+        let mut current_state = message.value().copied();
+
+        for i in 1..=round_constant_values.len() {
+            config.s_in_rounds.enable(region, i)?;
+            region.assign_fixed(
+                || format!("round constant {:?}", i),
+                config.round_constants,
+                i-1,
+                || Value::known(round_constant_values[i-1]) // i starts at 1
+            )?;
+
+            region.assign_advice(
+                || format!("key in row {:?} ", i),
+                config.key_column,
+                i,
+                || key
+            )?;
+
+            current_state = (current_state + key)
+                .map(|state| pow_5(state + round_constant_values[i-1]));
+            region.assign_advice(
+                || format!("round {:?} output", i),
+                config.state,
+                i,
+                || current_state
+            )?;
+        }
+
+        current_state = current_state + key;
+
+        region.assign_advice(
+            || "final state",
+            config.state,
+            round_constant_values.len()+1,
+            || current_state
+        )
+    }
 }
 
 pub struct MiMC5CipherPallasChip {
@@ -201,71 +246,131 @@ impl MiMC5CipherChip<Fq> for MiMC5CipherVestaChip {
 }
 
 
-#[cfg(test)]
-mod tests {
-    use crate::mimc::primitives::mimc5_encrypt;
+/// Encrypts `message` with `key` over Pallas and constrains the result to
+/// equal `ciphertext`.
+#[derive(Default)]
+pub(crate) struct MiMC5CipherPallasCircuit {
+    pub message: Fp,
+    pub key: Fp,
+    pub ciphertext: Fp,
+}
 
-    use super::*;
-    use halo2_proofs::{dev::MockProver, pasta::Fp, plonk::Circuit, circuit::SimpleFloorPlanner};
-    use crate::mimc::round_constants::{NUM_ROUNDS, MIMC_HASH_PALLAS_ROUND_CONSTANTS, MIMC_HASH_VESTA_ROUND_CONSTANTS};
+impl Circuit<Fp> for MiMC5CipherPallasCircuit {
+    type Config = MiMC5CipherConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
 
-    #[derive(Default)]
-    struct MiMC5CipherPallasCircuit {
-        pub message: Fp,
-        pub key: Fp,
-        pub ciphertext: Fp,
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let state = meta.advice_column();
+        let key_column = meta.advice_column();
+        let round_constants = meta.fixed_column();
+        MiMC5CipherPallasChip::configure(meta, state, key_column, round_constants)
     }
 
-    impl Circuit<Fp> for MiMC5CipherPallasCircuit {
-        type Config = MiMC5CipherConfig;
-        type FloorPlanner = SimpleFloorPlanner;
-        
-        fn without_witnesses(&self) -> Self {
-            Self::default()
-        }
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MiMC5CipherPallasChip::construct(config.clone());
 
-        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
-            let state = meta.advice_column();
-            let key_column = meta.advice_column();
-            let round_constants = meta.fixed_column();
-            MiMC5CipherPallasChip::configure(meta, state, key_column, round_constants)
-        }
+        let ciphertext = chip.encrypt_message(
+            layouter.namespace(|| "entire table"),
+            self.message,
+            self.key,
+        )?;
 
-        fn synthesize(
-            &self,
-            config: Self::Config,
-            mut layouter: impl Layouter<Fp>,
-        ) -> Result<(), Error> {
-            let chip = MiMC5CipherPallasChip::construct(config.clone());
-
-            let ciphertext = chip.encrypt_message(
-                layouter.namespace(|| "entire table"),
-                self.message,
-                self.key,
-            )?;
+        layouter.assign_region(
+            || "constrain output", 
+            |mut region| {
+                let expected_output = region.assign_advice(
+                    || "load output", 
+                    config.state,
+                    0,
+                    || Value::known(self.ciphertext),
+                )?;
+                region.constrain_equal(ciphertext.cell(), expected_output.cell())
+            }
+        )?;
 
-            layouter.assign_region(
-                || "constrain output", 
-                |mut region| {
-                    let expected_output = region.assign_advice(
-                        || "load output", 
-                        config.state,
-                        0,
-                        || Value::known(self.ciphertext),
-                    )?;
-                    region.constrain_equal(ciphertext.cell(), expected_output.cell())
-                }
-            )?;
+        Ok(())
+    }
+}
 
-            Ok(())
-        }
+/// Encrypts `message` with `key` over Vesta and constrains the result to
+/// equal `ciphertext`.
+#[derive(Default)]
+pub(crate) struct MiMC5CipherVestaCircuit {
+    pub message: Fq,
+    pub key: Fq,
+    pub ciphertext: Fq,
+}
+
+impl Circuit<Fq> for MiMC5CipherVestaCircuit {
+    type Config = MiMC5CipherConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fq>) -> Self::Config {
+        let state = meta.advice_column();
+        let round_constants = meta.fixed_column();
+        let key_column = meta.advice_column();
+        MiMC5CipherVestaChip::configure(meta, state, key_column, round_constants)
     }
 
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fq>,
+    ) -> Result<(), Error> {
+        let chip = MiMC5CipherVestaChip::construct(config.clone());
+
+        let ciphertext = chip.encrypt_message(
+            layouter.namespace(|| "entire table"),
+            self.message,
+            self.key,
+        )?;
+
+        layouter.assign_region(
+            || "constrain output", 
+            |mut region| {
+                let expected_output = region.assign_advice(
+                    || "load output", 
+                    config.state,
+                    0,
+                    || Value::known(self.ciphertext),
+                )?;
+                region.constrain_equal(ciphertext.cell(), expected_output.cell())
+            }
+        )?;
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::mimc::primitives::mimc5_encrypt;
+
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_permutation_failure;
+    use crate::soundness::mutation::assert_constrained;
+    use crate::strategies::field;
+    use proptest::prelude::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp, plonk::Any};
+    use crate::mimc::round_constants::{NUM_ROUNDS, MIMC_HASH_PALLAS_ROUND_CONSTANTS, MIMC_HASH_VESTA_ROUND_CONSTANTS};
+
  
     #[test]
     fn test_mimc5_pallas_cipher() {
-        let k = 7;
-
         let msg = Fp::from(0);
         let key = Fp::from(0);
         let mut output = msg;
@@ -277,68 +382,15 @@ mod tests {
             ciphertext: output,
         };
 
+        let k = minimal_k(&circuit);
         let prover = MockProver::run(k, &circuit, vec![]).unwrap();
         prover.assert_satisfied();
 
     }
 
-    #[derive(Default)]
-    struct MiMC5CipherVestaCircuit {
-        pub message: Fq,
-        pub key: Fq,
-        pub ciphertext: Fq,
-    }
-
-    impl Circuit<Fq> for MiMC5CipherVestaCircuit {
-        type Config = MiMC5CipherConfig;
-        type FloorPlanner = SimpleFloorPlanner;
-        
-        fn without_witnesses(&self) -> Self {
-            Self::default()
-        }
-
-        fn configure(meta: &mut ConstraintSystem<Fq>) -> Self::Config {
-            let state = meta.advice_column();
-            let round_constants = meta.fixed_column();
-            let key_column = meta.advice_column();
-            MiMC5CipherVestaChip::configure(meta, state, key_column, round_constants)
-        }
-
-        fn synthesize(
-            &self,
-            config: Self::Config,
-            mut layouter: impl Layouter<Fq>,
-        ) -> Result<(), Error> {
-            let chip = MiMC5CipherVestaChip::construct(config.clone());
-
-            let ciphertext = chip.encrypt_message(
-                layouter.namespace(|| "entire table"),
-                self.message,
-                self.key,
-            )?;
-
-            layouter.assign_region(
-                || "constrain output", 
-                |mut region| {
-                    let expected_output = region.assign_advice(
-                        || "load output", 
-                        config.state,
-                        0,
-                        || Value::known(self.ciphertext),
-                    )?;
-                    region.constrain_equal(ciphertext.cell(), expected_output.cell())
-                }
-            )?;
-
-            Ok(())
-        }
-    }
-
      
     #[test]
     fn test_mimc5_vesta_cipher() {
-        let k = 7;
-
         let msg = Fq::from(0);
         let key = Fq::from(0);
         let mut output = msg;
@@ -350,6 +402,7 @@ mod tests {
             ciphertext: output,
         };
 
+        let k = minimal_k(&circuit);
         let prover = MockProver::run(k, &circuit, vec![]).unwrap();
         prover.assert_satisfied();
 
@@ -360,7 +413,6 @@ mod tests {
     #[test]
     fn plot_mimc5_pallas_cipher() {
         use plotters::prelude::*;
-        let k = 7;
         let root = BitMapBackend::new("mimc5-pallas-cipher-layout.png", (1024, 3096)).into_drawing_area();
         root.fill(&WHITE).unwrap();
         let root = root.titled("MiMC Cipher Layout", ("sans-serif", 60)).unwrap();
@@ -371,8 +423,65 @@ mod tests {
             ciphertext: Fp::zero(),
         };
 
+        let k = minimal_k(&circuit);
         halo2_proofs::dev::CircuitLayout::default()
             .render(k, &circuit, &root)
             .unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_mimc5_cipher_mutations() {
+        // The key of the first round and the output of the last one used to be free
+        let (msg, key) = (Fp::from(7), Fp::from(11));
+        let mut output = msg;
+        mimc5_encrypt::<Fp, { NUM_ROUNDS }>(&mut output, key, MIMC_HASH_PALLAS_ROUND_CONSTANTS);
+        let circuit = MiMC5CipherPallasCircuit {
+            message: msg,
+            key,
+            ciphertext: output,
+        };
+        assert_constrained(&circuit, vec![]);
+
+        let (msg, key) = (Fq::from(7), Fq::from(11));
+        let mut output = msg;
+        mimc5_encrypt::<Fq, { NUM_ROUNDS }>(&mut output, key, MIMC_HASH_VESTA_ROUND_CONSTANTS);
+        let circuit = MiMC5CipherVestaCircuit {
+            message: msg,
+            key,
+            ciphertext: output,
+        };
+        assert_constrained(&circuit, vec![]);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_mimc5_pallas_cipher_matches_native(message in field::<Fp>(), key in field::<Fp>()) {
+            let mut ciphertext = message;
+            mimc5_encrypt::<Fp, { NUM_ROUNDS }>(&mut ciphertext, key, MIMC_HASH_PALLAS_ROUND_CONSTANTS);
+
+            let circuit = MiMC5CipherPallasCircuit { message, key, ciphertext };
+            let k = minimal_k(&circuit);
+            prop_assert_eq!(MockProver::run(k, &circuit, vec![]).unwrap().verify(), Ok(()));
+
+            let circuit = MiMC5CipherPallasCircuit { message, key, ciphertext: ciphertext + Fp::one() };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_permutation_failure(&prover, (Any::Advice, 0));
+        }
+
+        #[test]
+        fn test_mimc5_vesta_cipher_matches_native(message in field::<Fq>(), key in field::<Fq>()) {
+            let mut ciphertext = message;
+            mimc5_encrypt::<Fq, { NUM_ROUNDS }>(&mut ciphertext, key, MIMC_HASH_VESTA_ROUND_CONSTANTS);
+
+            let circuit = MiMC5CipherVestaCircuit { message, key, ciphertext };
+            let k = minimal_k(&circuit);
+            prop_assert_eq!(MockProver::run(k, &circuit, vec![]).unwrap().verify(), Ok(()));
+
+            let circuit = MiMC5CipherVestaCircuit { message, key, ciphertext: ciphertext + Fq::one() };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_permutation_failure(&prover, (Any::Advice, 0));
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use halo2_proofs::{
     arithmetic::FieldExt,
     plonk::{
-        Column, Advice, Fixed, Selector, ConstraintSystem, Expression, Error,
+        Circuit, Column, Advice, Fixed, Selector, ConstraintSystem, Expression, Error,
     },
     poly::Rotation,
     circuit::{
-        Layouter, AssignedCell, Value,
+        Layouter, AssignedCell, SimpleFloorPlanner, Value,
     },
 };
 use pasta_curves::{Fp, Fq};
@@ -160,68 +160,125 @@ impl MiMC5HashChip<Fq> for MiMC5HashVestaChip {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::mimc::primitives::mimc5_hash;
+/// Hashes `message` over Pallas and constrains the digest to equal
+/// `message_hash`.
+#[derive(Default)]
+pub(crate) struct MiMC5HashPallasCircuit {
+    pub message: Fp,
+    pub message_hash: Fp,
+}
 
-    use super::*;
-    use halo2_proofs::{dev::MockProver, pasta::Fp, plonk::Circuit, circuit::SimpleFloorPlanner};
-    use crate::mimc::round_constants::{NUM_ROUNDS, MIMC_HASH_PALLAS_ROUND_CONSTANTS, MIMC_HASH_VESTA_ROUND_CONSTANTS};
+impl Circuit<Fp> for MiMC5HashPallasCircuit {
+    type Config = MiMC5HashConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
 
-    #[derive(Default)]
-    struct MiMC5HashPallasCircuit {
-        pub message: Fp,
-        pub message_hash: Fp,
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let state = meta.advice_column();
+        let round_constants = meta.fixed_column();
+        MiMC5HashPallasChip::configure(meta, state, round_constants)
     }
 
-    impl Circuit<Fp> for MiMC5HashPallasCircuit {
-        type Config = MiMC5HashConfig;
-        type FloorPlanner = SimpleFloorPlanner;
-        
-        fn without_witnesses(&self) -> Self {
-            Self::default()
-        }
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = MiMC5HashPallasChip::construct(config.clone());
 
-        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
-            let state = meta.advice_column();
-            let round_constants = meta.fixed_column();
-            MiMC5HashPallasChip::configure(meta, state, round_constants)
-        }
+        let msg_hash = chip.hash_message(
+            layouter.namespace(|| "entire table"),
+            self.message,
+        )?;
 
-        fn synthesize(
-            &self,
-            config: Self::Config,
-            mut layouter: impl Layouter<Fp>,
-        ) -> Result<(), Error> {
-            let chip = MiMC5HashPallasChip::construct(config.clone());
-
-            let msg_hash = chip.hash_message(
-                layouter.namespace(|| "entire table"),
-                self.message,
-            )?;
-
-            layouter.assign_region(
-                || "constrain output", 
-                |mut region| {
-                    let expected_output = region.assign_advice(
-                        || "load output", 
-                        config.state,
-                        0,
-                        || Value::known(self.message_hash),
-                    )?;
-                    region.constrain_equal(msg_hash.cell(), expected_output.cell())
-                }
-            )?;
+        layouter.assign_region(
+            || "constrain output", 
+            |mut region| {
+                let expected_output = region.assign_advice(
+                    || "load output", 
+                    config.state,
+                    0,
+                    || Value::known(self.message_hash),
+                )?;
+                region.constrain_equal(msg_hash.cell(), expected_output.cell())
+            }
+        )?;
 
-            Ok(())
-        }
+        Ok(())
     }
+}
+
+/// Hashes `message` over Vesta and constrains the digest to equal
+/// `message_hash`.
+#[derive(Default)]
+pub(crate) struct MiMC5HashVestaCircuit {
+    pub message: Fq,
+    pub message_hash: Fq,
+}
+
+impl Circuit<Fq> for MiMC5HashVestaCircuit {
+    type Config = MiMC5HashConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fq>) -> Self::Config {
+        let state = meta.advice_column();
+        let round_constants = meta.fixed_column();
+        MiMC5HashVestaChip::configure(meta, state, round_constants)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fq>,
+    ) -> Result<(), Error> {
+        let chip = MiMC5HashVestaChip::construct(config.clone());
+
+        let msg_hash = chip.hash_message(
+            layouter.namespace(|| "entire table"),
+            self.message,
+        )?;
+
+        layouter.assign_region(
+            || "constrain output", 
+            |mut region| {
+                let expected_output = region.assign_advice(
+                    || "load output", 
+                    config.state,
+                    0,
+                    || Value::known(self.message_hash),
+                )?;
+                region.constrain_equal(msg_hash.cell(), expected_output.cell())
+            }
+        )?;
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::mimc::primitives::mimc5_hash;
+
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_permutation_failure;
+    use crate::soundness::mutation::assert_constrained;
+    use crate::strategies::field;
+    use proptest::prelude::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp, plonk::Any};
+    use crate::mimc::round_constants::{NUM_ROUNDS, MIMC_HASH_PALLAS_ROUND_CONSTANTS, MIMC_HASH_VESTA_ROUND_CONSTANTS};
 
  
     #[test]
     fn test_mimc5_pallas_hash() {
-        let k = 7;
-
         let msg = Fp::from(0);
         let mut output = msg;
         mimc5_hash::<Fp, { NUM_ROUNDS }>(&mut output, MIMC_HASH_PALLAS_ROUND_CONSTANTS);
@@ -231,65 +288,15 @@ mod tests {
             message_hash: output,
         };
 
+        let k = minimal_k(&circuit);
         let prover = MockProver::run(k, &circuit, vec![]).unwrap();
         prover.assert_satisfied();
 
     }
 
-    #[derive(Default)]
-    struct MiMC5HashVestaCircuit {
-        pub message: Fq,
-        pub message_hash: Fq,
-    }
-
-    impl Circuit<Fq> for MiMC5HashVestaCircuit {
-        type Config = MiMC5HashConfig;
-        type FloorPlanner = SimpleFloorPlanner;
-        
-        fn without_witnesses(&self) -> Self {
-            Self::default()
-        }
-
-        fn configure(meta: &mut ConstraintSystem<Fq>) -> Self::Config {
-            let state = meta.advice_column();
-            let round_constants = meta.fixed_column();
-            MiMC5HashVestaChip::configure(meta, state, round_constants)
-        }
-
-        fn synthesize(
-            &self,
-            config: Self::Config,
-            mut layouter: impl Layouter<Fq>,
-        ) -> Result<(), Error> {
-            let chip = MiMC5HashVestaChip::construct(config.clone());
-
-            let msg_hash = chip.hash_message(
-                layouter.namespace(|| "entire table"),
-                self.message,
-            )?;
-
-            layouter.assign_region(
-                || "constrain output", 
-                |mut region| {
-                    let expected_output = region.assign_advice(
-                        || "load output", 
-                        config.state,
-                        0,
-                        || Value::known(self.message_hash),
-                    )?;
-                    region.constrain_equal(msg_hash.cell(), expected_output.cell())
-                }
-            )?;
-
-            Ok(())
-        }
-    }
-
      
     #[test]
     fn test_mimc5_vesta_hash() {
-        let k = 7;
-
         let msg = Fq::from(0);
         let mut output = msg;
         mimc5_hash::<Fq, { NUM_ROUNDS }>(&mut output, MIMC_HASH_VESTA_ROUND_CONSTANTS);
@@ -299,6 +306,7 @@ mod tests {
             message_hash: output,
         };
 
+        let k = minimal_k(&circuit);
         let prover = MockProver::run(k, &circuit, vec![]).unwrap();
         prover.assert_satisfied();
 
@@ -309,7 +317,6 @@ mod tests {
     #[test]
     fn plot_mimc5_pallas_hash() {
         use plotters::prelude::*;
-        let k = 7;
         let root = BitMapBackend::new("mimc5-pallas-hash-layout.png", (1024, 3096)).into_drawing_area();
         root.fill(&WHITE).unwrap();
         let root = root.titled("MiMC Hash Layout", ("sans-serif", 60)).unwrap();
@@ -319,8 +326,62 @@ mod tests {
             message_hash: Fp::zero(),
         };
 
+        let k = minimal_k(&circuit);
         halo2_proofs::dev::CircuitLayout::default()
             .render(k, &circuit, &root)
             .unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_mimc5_hash_mutations() {
+        let msg = Fp::from(7);
+        let mut output = msg;
+        mimc5_hash::<Fp, { NUM_ROUNDS }>(&mut output, MIMC_HASH_PALLAS_ROUND_CONSTANTS);
+        let circuit = MiMC5HashPallasCircuit {
+            message: msg,
+            message_hash: output,
+        };
+        assert_constrained(&circuit, vec![]);
+
+        let msg = Fq::from(7);
+        let mut output = msg;
+        mimc5_hash::<Fq, { NUM_ROUNDS }>(&mut output, MIMC_HASH_VESTA_ROUND_CONSTANTS);
+        let circuit = MiMC5HashVestaCircuit {
+            message: msg,
+            message_hash: output,
+        };
+        assert_constrained(&circuit, vec![]);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_mimc5_pallas_hash_matches_native(message in field::<Fp>()) {
+            let mut message_hash = message;
+            mimc5_hash::<Fp, { NUM_ROUNDS }>(&mut message_hash, MIMC_HASH_PALLAS_ROUND_CONSTANTS);
+
+            let circuit = MiMC5HashPallasCircuit { message, message_hash };
+            let k = minimal_k(&circuit);
+            prop_assert_eq!(MockProver::run(k, &circuit, vec![]).unwrap().verify(), Ok(()));
+
+            let circuit = MiMC5HashPallasCircuit { message, message_hash: message_hash + Fp::one() };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_permutation_failure(&prover, (Any::Advice, 0));
+        }
+
+        #[test]
+        fn test_mimc5_vesta_hash_matches_native(message in field::<Fq>()) {
+            let mut message_hash = message;
+            mimc5_hash::<Fq, { NUM_ROUNDS }>(&mut message_hash, MIMC_HASH_VESTA_ROUND_CONSTANTS);
+
+            let circuit = MiMC5HashVestaCircuit { message, message_hash };
+            let k = minimal_k(&circuit);
+            prop_assert_eq!(MockProver::run(k, &circuit, vec![]).unwrap().verify(), Ok(()));
+
+            let circuit = MiMC5HashVestaCircuit { message, message_hash: message_hash + Fq::one() };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_permutation_failure(&prover, (Any::Advice, 0));
+        }
+    }
+}
@@ -1,4 +1,6 @@
-mod mimc_hash;
-mod mimc_cipher;
-mod round_constants;
-mod primitives;
\ No newline at end of file
+pub(crate) mod mimc_hash;
+pub(crate) mod mimc_cipher;
+pub(crate) mod round_constants;
+pub(crate) mod primitives;
+pub mod hash_bytes;
+pub mod compression;
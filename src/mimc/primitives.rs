@@ -1,7 +1,5 @@
-#[allow(unused_imports)]
 use halo2_proofs::arithmetic::FieldExt;
 
-#[cfg(test)]
 pub(crate) fn mimc5_encrypt<F: FieldExt, const ROUNDS: usize>(
     state: &mut F,
     key: F,
@@ -15,10 +13,51 @@ pub(crate) fn mimc5_encrypt<F: FieldExt, const ROUNDS: usize>(
     *state = *state + key;
 }
 
-#[cfg(test)]
 pub(crate) fn mimc5_hash<F: FieldExt, const ROUNDS: usize>(
     state: &mut F,
     round_constants: [F; ROUNDS],
 ) {
     mimc5_encrypt(state, F::zero(), round_constants);
-}
\ No newline at end of file
+}
+/// The rate-one sponge over the keyless permutation `P` of `mimc5_hash` over
+/// Pallas, together with the inverse of `P`. Since `P` is inverted by taking
+/// fifth roots round by round, the sponge is not collision resistant; the
+/// tests use it to forge collisions which the one-way hashes of
+/// `compression` must reject.
+#[cfg(test)]
+pub(crate) mod sponge {
+    use halo2_proofs::{arithmetic::Field, pasta::Fp};
+
+    use super::mimc5_hash;
+    use crate::mimc::round_constants::{MIMC_HASH_PALLAS_ROUND_CONSTANTS, NUM_ROUNDS};
+
+    /// The inverse of 5 modulo `p - 1` for the modulus `p` of `Fp`, in little
+    /// endian limbs, so that `x^INV_5` is the fifth root of `x`.
+    const INV_5: [u64; 4] = [
+        0xe0f0f3f0cccccccd,
+        0x4e9ee0c9a10a60e2,
+        0x3333333333333333,
+        0x3333333333333333,
+    ];
+
+    pub(crate) fn permute(mut state: Fp) -> Fp {
+        mimc5_hash::<Fp, NUM_ROUNDS>(&mut state, MIMC_HASH_PALLAS_ROUND_CONSTANTS);
+        state
+    }
+
+    /// Inverts `permute`, round by round.
+    pub(crate) fn permute_inverse(mut state: Fp) -> Fp {
+        for c in MIMC_HASH_PALLAS_ROUND_CONSTANTS.iter().rev() {
+            state = state.pow_vartime(INV_5) - c;
+        }
+        state
+    }
+
+    /// Starting from the zero state, adds each element to the state and
+    /// permutes it.
+    pub(crate) fn sponge(elements: &[Fp]) -> Fp {
+        elements
+            .iter()
+            .fold(Fp::zero(), |state, element| permute(state + element))
+    }
+}
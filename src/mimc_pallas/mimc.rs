@@ -12,10 +12,12 @@ use halo2_proofs::{
     },
 };
 
+use crate::proving::public_inputs::public_inputs;
+
 
 
 #[derive(Debug, Clone)]
-struct MiMC5Config {
+pub(crate) struct MiMC5Config {
     instance: Column<Instance>,
     state: Column<Advice>,
     key_column: Column<Advice>,
@@ -70,7 +72,7 @@ impl<F: FieldExt> MiMC5Chip<F> {
             };
             let prev_state = meta.query_advice(state, Rotation::prev());
             let key = meta.query_advice(key_column, Rotation::cur());
-            let prev_key = meta.query_advice(key_column, Rotation::cur());
+            let prev_key = meta.query_advice(key_column, Rotation::prev());
             let rc = meta.query_fixed(round_constants, Rotation::prev());
             let current_state = meta.query_advice(state, Rotation::cur());
             vec![
@@ -178,8 +180,13 @@ impl<F: FieldExt> MiMC5Chip<F> {
     }
 }
 
+public_inputs! {
+    /// The message and the ciphertext under the private key
+    struct MiMC5PublicInputs { message, output }
+}
+
 #[derive(Default)]
-struct MiMC5Circuit<F> {
+pub(crate) struct MiMC5Circuit<F> {
     pub message: F,
     pub key: F,
     pub num_rounds: usize,
@@ -217,11 +224,8 @@ impl <F: FieldExt> Circuit<F> for MiMC5Circuit<F> {
             self.num_rounds,
         )?;
 
-        chip.expose_public(layouter.namespace(|| "message"), msg, 0)?;
-        chip.expose_public(layouter.namespace(|| "out"), final_output, 1)?;
-        // instance      | state 
-        // message       |
-        // final_output  |
+        chip.expose_public(layouter.namespace(|| "message"), msg, MiMC5PublicInputs::ROWS.message)?;
+        chip.expose_public(layouter.namespace(|| "out"), final_output, MiMC5PublicInputs::ROWS.output)?;
 
         Ok(())
     }
@@ -230,16 +234,22 @@ impl <F: FieldExt> Circuit<F> for MiMC5Circuit<F> {
 #[cfg(test)]
 mod tests {
     use crate::mimc_pallas::primitives::mimc5_encrypt;
+    use crate::proving::public_inputs::PublicInputs;
 
     use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::{assert_constraint_failure, assert_missing_instance};
+    use crate::soundness::mutation::tampered_prover;
     use halo2_proofs::{dev::MockProver, pasta::Fp};
     use crate::mimc_pallas::pallas_round_constants::{NUM_ROUNDS, ROUND_CONSTANTS};
+    use crate::mimc::round_constants::MIMC_HASH_VESTA_ROUND_CONSTANTS;
+    use crate::strategies::field;
+    use halo2_proofs::pasta::Fq;
+    use proptest::prelude::*;
 
  
     #[test]
     fn test_mimc5_cipher() {
-        let k = 7;
-
         let msg = Fp::from(0);
         let key = Fp::from(0);
         let mut output = msg;
@@ -252,18 +262,28 @@ mod tests {
             num_rounds: NUM_ROUNDS,
         };
 
-        let public_input = vec![msg, output];
+        let public_inputs = MiMC5PublicInputs { message: msg, output };
 
-        let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
         prover.assert_satisfied();
 
+        // The output of a round is no longer the fifth power of its input
+        let prover = tampered_prover(
+            &circuit,
+            public_inputs.to_instances(),
+            "MiMC5 table",
+            "round 1 output",
+            Fp::one(),
+        )
+        .unwrap();
+        assert_constraint_failure(&prover, "MiMC5 rounds", "MiMC5 table");
     }
 
     #[cfg(feature = "dev-graph")]
     #[test]
     fn plot_mimc5_cipher() {
         use plotters::prelude::*;
-        let k = 7;
         let root = BitMapBackend::new("mimc5-cipher-layout.png", (1024, 3096)).into_drawing_area();
         root.fill(&WHITE).unwrap();
         let root = root.titled("MiMC5 Cipher Layout", ("sans-serif", 60)).unwrap();
@@ -275,8 +295,49 @@ mod tests {
             num_rounds: NUM_ROUNDS,
         };
 
+        let k = minimal_k(&circuit);
         halo2_proofs::dev::CircuitLayout::default()
             .render(k, &circuit, &root)
             .unwrap();
     }
-}
\ No newline at end of file
+
+    fn check_cipher<F: FieldExt>(
+        message: F,
+        key: F,
+        round_constants: [F; NUM_ROUNDS],
+    ) -> Result<(), TestCaseError> {
+        let mut output = message;
+        mimc5_encrypt::<F, { NUM_ROUNDS }>(&mut output, key, round_constants);
+
+        let circuit = MiMC5Circuit {
+            message,
+            key,
+            round_constants: round_constants.to_vec(),
+            num_rounds: NUM_ROUNDS,
+        };
+        let mut public_inputs = MiMC5PublicInputs { message, output };
+
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+        prop_assert_eq!(prover.verify(), Ok(()));
+
+        public_inputs.output += F::one();
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+        assert_missing_instance(&prover, 0, MiMC5PublicInputs::ROWS.output);
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_mimc5_cipher_matches_native_pallas(message in field::<Fp>(), key in field::<Fp>()) {
+            check_cipher(message, key, ROUND_CONSTANTS)?;
+        }
+
+        #[test]
+        fn test_mimc5_cipher_matches_native_vesta(message in field::<Fq>(), key in field::<Fq>()) {
+            check_cipher(message, key, MIMC_HASH_VESTA_ROUND_CONSTANTS)?;
+        }
+    }
+}
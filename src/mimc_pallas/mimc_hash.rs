@@ -11,11 +11,12 @@ use halo2_proofs::{
         Layouter, AssignedCell, Value, SimpleFloorPlanner
     },
 };
+use crate::proving::public_inputs::public_inputs;
 
 
 
 #[derive(Debug, Clone)]
-struct MiMC5HashConfig {
+pub(crate) struct MiMC5HashConfig {
     instance: Column<Instance>,
     state: Column<Advice>,
     round_constants: Column<Fixed>,
@@ -93,7 +94,7 @@ impl<F: FieldExt> MiMC5HashChip<F> {
                 region.assign_advice_from_instance(
                     || "message to be hashed",
                     self.config.instance,
-                    0,
+                    MiMC5HashPublicInputs::ROWS.message,
                     self.config.state,
                     0
                 )?;
@@ -137,8 +138,13 @@ impl<F: FieldExt> MiMC5HashChip<F> {
     }
 }
 
+public_inputs! {
+    /// The message and its hash
+    pub(crate) struct MiMC5HashPublicInputs { message, output }
+}
+
 #[derive(Default)]
-struct MiMC5HashCircuit<F> {
+pub(crate) struct MiMC5HashCircuit<F> {
     pub message: F,
     pub num_rounds: usize,
     pub round_constants: Vec<F>,
@@ -173,8 +179,8 @@ impl <F: FieldExt> Circuit<F> for MiMC5HashCircuit<F> {
             self.num_rounds,
         )?;
 
-        chip.expose_public(layouter.namespace(|| "message"), msg, 0)?;
-        chip.expose_public(layouter.namespace(|| "out"), msg_hash, 1)?;
+        chip.expose_public(layouter.namespace(|| "message"), msg, MiMC5HashPublicInputs::ROWS.message)?;
+        chip.expose_public(layouter.namespace(|| "out"), msg_hash, MiMC5HashPublicInputs::ROWS.output)?;
         // instance      | state 
         // message       |
         // key           |
@@ -187,16 +193,21 @@ impl <F: FieldExt> Circuit<F> for MiMC5HashCircuit<F> {
 #[cfg(test)]
 mod tests {
     use crate::mimc_pallas::primitives::mimc5_hash;
+    use crate::proving::public_inputs::PublicInputs;
 
     use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_missing_instance;
     use halo2_proofs::{dev::MockProver, pasta::Fp};
     use crate::mimc_pallas::pallas_round_constants::{NUM_ROUNDS, ROUND_CONSTANTS};
+    use crate::mimc::round_constants::MIMC_HASH_VESTA_ROUND_CONSTANTS;
+    use crate::strategies::field;
+    use halo2_proofs::pasta::Fq;
+    use proptest::prelude::*;
 
  
     #[test]
     fn test_mimc5_hash() {
-        let k = 7;
-
         let msg = Fp::from(0);
         let mut output = msg;
         mimc5_hash::<Fp, { NUM_ROUNDS }>(&mut output, ROUND_CONSTANTS);
@@ -207,9 +218,10 @@ mod tests {
             num_rounds: NUM_ROUNDS,
         };
 
-        let public_input = vec![msg, output];
+        let public_inputs = MiMC5HashPublicInputs { message: msg, output };
 
-        let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
         prover.assert_satisfied();
 
     }
@@ -218,7 +230,6 @@ mod tests {
     #[test]
     fn plot_mimc5_hash() {
         use plotters::prelude::*;
-        let k = 7;
         let root = BitMapBackend::new("mimc5-hash-layout.png", (1024, 3096)).into_drawing_area();
         root.fill(&WHITE).unwrap();
         let root = root.titled("MiMC Hash Layout", ("sans-serif", 60)).unwrap();
@@ -229,8 +240,48 @@ mod tests {
             num_rounds: NUM_ROUNDS,
         };
 
+        let k = minimal_k(&circuit);
         halo2_proofs::dev::CircuitLayout::default()
             .render(k, &circuit, &root)
             .unwrap();
     }
-}
\ No newline at end of file
+
+    fn check_hash<F: FieldExt>(
+        message: F,
+        round_constants: [F; NUM_ROUNDS],
+    ) -> Result<(), TestCaseError> {
+        let mut output = message;
+        mimc5_hash::<F, { NUM_ROUNDS }>(&mut output, round_constants);
+
+        let circuit = MiMC5HashCircuit {
+            message,
+            round_constants: round_constants.to_vec(),
+            num_rounds: NUM_ROUNDS,
+        };
+
+        let mut public_inputs = MiMC5HashPublicInputs { message, output };
+
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+        prop_assert_eq!(prover.verify(), Ok(()));
+
+        public_inputs.output += F::one();
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+        assert_missing_instance(&prover, 0, MiMC5HashPublicInputs::ROWS.output);
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_mimc5_hash_matches_native_pallas(message in field::<Fp>()) {
+            check_hash(message, ROUND_CONSTANTS)?;
+        }
+
+        #[test]
+        fn test_mimc5_hash_matches_native_vesta(message in field::<Fq>()) {
+            check_hash(message, MIMC_HASH_VESTA_ROUND_CONSTANTS)?;
+        }
+    }
+}
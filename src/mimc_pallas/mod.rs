@@ -1,4 +1,4 @@
-mod mimc;
-mod mimc_hash;
-mod pallas_round_constants;
-mod primitives;
\ No newline at end of file
+pub(crate) mod mimc;
+pub(crate) mod mimc_hash;
+pub(crate) mod pallas_round_constants;
+pub(crate) mod primitives;
\ No newline at end of file
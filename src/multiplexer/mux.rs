@@ -0,0 +1,302 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+#[derive(Clone, Debug)]
+pub struct MuxConfig<F, const N: usize> {
+    pub index_bits: [Column<Advice>; N],
+    pub output: Column<Advice>,
+    pub mux_expr: Expression<F>,
+}
+
+impl<F: FieldExt, const N: usize> MuxConfig<F, N> {
+    pub fn expr(&self) -> Expression<F> {
+        self.mux_expr.clone()
+    }
+}
+
+/// Selects `values[index]` for an index in `0..N`. The index is witnessed as
+/// a one-hot vector of bits whose weighted sum must equal the index.
+pub struct MuxChip<F: FieldExt, const N: usize> {
+    config: MuxConfig<F, N>,
+}
+
+impl<F: FieldExt, const N: usize> MuxChip<F, N> {
+    pub fn construct(config: MuxConfig<F, N>) -> Self {
+        MuxChip { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        index: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        values: impl FnOnce(&mut VirtualCells<'_, F>) -> [Expression<F>; N],
+        index_bits: [Column<Advice>; N],
+        output: Column<Advice>,
+    ) -> MuxConfig<F, N> {
+        let mut mux_expr = Expression::Constant(F::zero());
+
+        meta.create_gate("mux", |meta| {
+            //
+            // index_bits[0] ... index_bits[N-1] | output
+            // ----------------------------------+---------------
+            //  one-hot encoding of index        | values[index]
+            //
+            let q_enable = q_enable(meta);
+            let index = index(meta);
+            let values = values(meta);
+            let bits = index_bits.map(|bit| meta.query_advice(bit, Rotation::cur()));
+            let output = meta.query_advice(output, Rotation::cur());
+
+            let one = Expression::Constant(F::one());
+            let zero = Expression::Constant(F::zero());
+            let bit_sum = bits.iter().fold(zero.clone(), |acc, bit| acc + bit.clone());
+            let weighted_sum = bits
+                .iter()
+                .enumerate()
+                .fold(zero.clone(), |acc, (i, bit)| {
+                    acc + bit.clone() * Expression::Constant(F::from(i as u64))
+                });
+            mux_expr = bits
+                .iter()
+                .zip(values)
+                .fold(zero, |acc, (bit, value)| acc + bit.clone() * value);
+
+            let mut constraints: Vec<Expression<F>> = bits
+                .iter()
+                .map(|bit| q_enable.clone() * bit.clone() * (one.clone() - bit.clone()))
+                .collect();
+            constraints.push(q_enable.clone() * (bit_sum - one));
+            constraints.push(q_enable.clone() * (weighted_sum - index));
+            constraints.push(q_enable * (output - mux_expr.clone()));
+            constraints
+        });
+
+        MuxConfig {
+            index_bits,
+            output,
+            mux_expr,
+        }
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        index: Value<F>,
+        values: [Value<F>; N],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        // An out of range index gives all zero bits, which fails the one-hot check
+        let index = index.map(|index| index.get_lower_128() as usize);
+
+        for (i, column) in self.config.index_bits.iter().enumerate() {
+            region.assign_advice(
+                || format!("index bit {}", i),
+                *column,
+                offset,
+                || index.map(|index| F::from(index == i)),
+            )?;
+        }
+
+        let output = index.zip(Value::<Vec<F>>::from_iter(values)).map(|(index, values)| {
+            values.get(index).copied().unwrap_or(F::zero())
+        });
+        region.assign_advice(|| "mux output", self.config.output, offset, || output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_constraint_failure;
+    use crate::soundness::mutation::{unconstrained_cells, UnconstrainedCells};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance, Selector},
+    };
+
+    const N: usize = 4;
+
+    #[derive(Clone, Debug)]
+    struct MuxTestConfig {
+        selector: Selector,
+        index: Column<Advice>,
+        values: [Column<Advice>; N],
+        instance: Column<Instance>,
+        mux: MuxConfig<Fp, N>,
+    }
+
+    #[derive(Default)]
+    struct MuxTestCircuit {
+        index: Fp,
+        values: [Fp; N],
+        // Malicious witness for the index bits
+        index_bits: Option<[Fp; N]>,
+    }
+
+    impl Circuit<Fp> for MuxTestCircuit {
+        type Config = MuxTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let selector = meta.selector();
+            let index = meta.advice_column();
+            let values = [(); N].map(|_| meta.advice_column());
+            let index_bits = [(); N].map(|_| meta.advice_column());
+            let output = meta.advice_column();
+            let instance = meta.instance_column();
+
+            meta.enable_equality(output);
+            meta.enable_equality(instance);
+
+            let mux = MuxChip::configure(
+                meta,
+                |meta| meta.query_selector(selector),
+                |meta| meta.query_advice(index, Rotation::cur()),
+                |meta| values.map(|value| meta.query_advice(value, Rotation::cur())),
+                index_bits,
+                output,
+            );
+
+            MuxTestConfig {
+                selector,
+                index,
+                values,
+                instance,
+                mux,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = MuxChip::construct(config.mux.clone());
+
+            let output = layouter.assign_region(
+                || "mux",
+                |mut region| {
+                    config.selector.enable(&mut region, 0)?;
+                    let index = Value::known(self.index);
+                    let values = self.values.map(Value::known);
+                    region.assign_advice(|| "index", config.index, 0, || index)?;
+                    for (column, value) in config.values.iter().zip(values) {
+                        region.assign_advice(|| "value", *column, 0, || value)?;
+                    }
+                    let mut output = chip.assign(&mut region, 0, index, values)?;
+
+                    if let Some(index_bits) = self.index_bits {
+                        for (column, bit) in config.mux.index_bits.iter().zip(index_bits) {
+                            region.assign_advice(|| "malicious bit", *column, 0, || Value::known(bit))?;
+                        }
+                        let blended = index_bits
+                            .iter()
+                            .zip(self.values)
+                            .fold(Fp::zero(), |acc, (bit, value)| acc + *bit * value);
+                        output = region.assign_advice(
+                            || "blended output",
+                            config.mux.output,
+                            0,
+                            || Value::known(blended),
+                        )?;
+                    }
+                    Ok(output)
+                },
+            )?;
+
+            layouter.constrain_instance(output.cell(), config.instance, 0)
+        }
+    }
+
+    fn values() -> [Fp; N] {
+        [Fp::from(10), Fp::from(20), Fp::from(30), Fp::from(40)]
+    }
+
+    #[test]
+    fn test_mux() {
+        let values = values();
+
+        for (index, value) in values.iter().enumerate() {
+            let circuit = MuxTestCircuit {
+                index: Fp::from(index as u64),
+                values,
+                index_bits: None,
+            };
+            let k = minimal_k(&circuit);
+            let prover = MockProver::run(k, &circuit, vec![vec![*value]]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_mux_out_of_range_index() {
+        let circuit = MuxTestCircuit {
+            index: Fp::from(N as u64),
+            values: values(),
+            index_bits: None,
+        };
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::zero()]]).unwrap();
+        assert_constraint_failure(&prover, "mux", "mux");
+    }
+
+    #[test]
+    fn test_mux_non_boolean_index_bits() {
+        // Both encodings sum to one and have weighted sum equal to the index 1,
+        // but neither is one-hot.
+        let attacks = [
+            [Fp::one(), -Fp::one(), Fp::one(), Fp::zero()],
+            [Fp::TWO_INV, Fp::zero(), Fp::TWO_INV, Fp::zero()],
+        ];
+        for index_bits in attacks {
+            let sum: Fp = index_bits.iter().fold(Fp::zero(), |acc, bit| acc + bit);
+            let weighted = index_bits
+                .iter()
+                .enumerate()
+                .fold(Fp::zero(), |acc, (i, bit)| acc + Fp::from(i as u64) * bit);
+            assert_eq!(sum, Fp::one());
+            assert_eq!(weighted, Fp::one());
+
+            let circuit = MuxTestCircuit {
+                index: Fp::one(),
+                values: values(),
+                index_bits: Some(index_bits),
+            };
+            let blended = index_bits
+                .iter()
+                .zip(values())
+                .fold(Fp::zero(), |acc, (bit, value)| acc + *bit * value);
+            let k = minimal_k(&circuit);
+            let prover = MockProver::run(k, &circuit, vec![vec![blended]]).unwrap();
+            assert_constraint_failure(&prover, "mux", "mux");
+        }
+    }
+
+    #[test]
+    fn test_mux_mutations() {
+        // The values which are not selected are free
+        let values = values();
+        let circuit = MuxTestCircuit {
+            index: Fp::from(2),
+            values,
+            index_bits: None,
+        };
+        let unconstrained = unconstrained_cells(&circuit, vec![vec![values[2]]]).unwrap();
+        let names: Vec<&str> = unconstrained
+            .iter()
+            .flat_map(UnconstrainedCells::names)
+            .collect();
+        assert_eq!(names, ["value", "value", "value"]);
+    }
+}
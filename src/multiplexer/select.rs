@@ -0,0 +1,223 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+#[derive(Clone, Debug)]
+pub struct SelectConfig<F> {
+    pub output: Column<Advice>,
+    pub select_expr: Expression<F>,
+}
+
+impl<F: FieldExt> SelectConfig<F> {
+    pub fn expr(&self) -> Expression<F> {
+        self.select_expr.clone()
+    }
+}
+
+/// Computes `if cond { when_true } else { when_false }`. The condition is
+/// constrained to be a bit, so a malicious prover cannot blend the two branches.
+pub struct SelectChip<F: FieldExt> {
+    config: SelectConfig<F>,
+}
+
+impl<F: FieldExt> SelectChip<F> {
+    pub fn construct(config: SelectConfig<F>) -> Self {
+        SelectChip { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        cond: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        when_true: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        when_false: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        output: Column<Advice>,
+    ) -> SelectConfig<F> {
+        let mut select_expr = Expression::Constant(F::zero());
+
+        meta.create_gate("select", |meta| {
+            //
+            // cond | output
+            // -----+------------
+            //  1   | when_true
+            //  0   | when_false
+            //
+            let q_enable = q_enable(meta);
+            let cond = cond(meta);
+            let when_true = when_true(meta);
+            let when_false = when_false(meta);
+            let output = meta.query_advice(output, Rotation::cur());
+
+            // when_false + cond * (when_true - when_false)
+            select_expr = when_false.clone() + cond.clone() * (when_true - when_false);
+            vec![
+                q_enable.clone() * cond.clone() * (Expression::Constant(F::one()) - cond),
+                q_enable * (output - select_expr.clone()),
+            ]
+        });
+
+        SelectConfig {
+            output,
+            select_expr,
+        }
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        cond: Value<F>,
+        when_true: Value<F>,
+        when_false: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let output = when_false + cond * (when_true - when_false);
+        region.assign_advice(|| "selected value", self.config.output, offset, || output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::soundness::failures::assert_constraint_failure;
+    use crate::soundness::mutation::{unconstrained_cells, UnconstrainedCells};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance, Selector},
+    };
+
+    #[derive(Clone, Debug)]
+    struct SelectTestConfig {
+        selector: Selector,
+        cond: Column<Advice>,
+        when_true: Column<Advice>,
+        when_false: Column<Advice>,
+        instance: Column<Instance>,
+        select: SelectConfig<Fp>,
+    }
+
+    #[derive(Default)]
+    struct SelectTestCircuit {
+        cond: Fp,
+        when_true: Fp,
+        when_false: Fp,
+    }
+
+    impl Circuit<Fp> for SelectTestCircuit {
+        type Config = SelectTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let selector = meta.selector();
+            let cond = meta.advice_column();
+            let when_true = meta.advice_column();
+            let when_false = meta.advice_column();
+            let output = meta.advice_column();
+            let instance = meta.instance_column();
+
+            meta.enable_equality(output);
+            meta.enable_equality(instance);
+
+            let select = SelectChip::configure(
+                meta,
+                |meta| meta.query_selector(selector),
+                |meta| meta.query_advice(cond, Rotation::cur()),
+                |meta| meta.query_advice(when_true, Rotation::cur()),
+                |meta| meta.query_advice(when_false, Rotation::cur()),
+                output,
+            );
+
+            SelectTestConfig {
+                selector,
+                cond,
+                when_true,
+                when_false,
+                instance,
+                select,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = SelectChip::construct(config.select.clone());
+
+            let output = layouter.assign_region(
+                || "select",
+                |mut region| {
+                    config.selector.enable(&mut region, 0)?;
+                    let cond = Value::known(self.cond);
+                    let when_true = Value::known(self.when_true);
+                    let when_false = Value::known(self.when_false);
+                    region.assign_advice(|| "cond", config.cond, 0, || cond)?;
+                    region.assign_advice(|| "when true", config.when_true, 0, || when_true)?;
+                    region.assign_advice(|| "when false", config.when_false, 0, || when_false)?;
+                    chip.assign(&mut region, 0, cond, when_true, when_false)
+                },
+            )?;
+
+            layouter.constrain_instance(output.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let (when_true, when_false) = (Fp::from(11), Fp::from(22));
+
+        for (cond, expected) in [(Fp::one(), when_true), (Fp::zero(), when_false)] {
+            let circuit = SelectTestCircuit {
+                cond,
+                when_true,
+                when_false,
+            };
+            let k = minimal_k(&circuit);
+            let prover = MockProver::run(k, &circuit, vec![vec![expected]]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn test_select_non_boolean_condition() {
+        let (when_true, when_false) = (Fp::from(11), Fp::from(22));
+
+        // With cond = 2 the output would be 2 * when_true - when_false, which
+        // satisfies the selection constraint but not the boolean constraint.
+        let cond = Fp::from(2);
+        let circuit = SelectTestCircuit {
+            cond,
+            when_true,
+            when_false,
+        };
+        let blended = when_false + cond * (when_true - when_false);
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, vec![vec![blended]]).unwrap();
+        assert_constraint_failure(&prover, "select", "select");
+    }
+
+    #[test]
+    fn test_select_mutations() {
+        // The value which is not selected is free
+        let circuit = SelectTestCircuit {
+            cond: Fp::one(),
+            when_true: Fp::from(11),
+            when_false: Fp::from(22),
+        };
+        let unconstrained = unconstrained_cells(&circuit, vec![vec![Fp::from(11)]]).unwrap();
+        let names: Vec<&str> = unconstrained
+            .iter()
+            .flat_map(UnconstrainedCells::names)
+            .collect();
+        assert_eq!(names, ["when false"]);
+    }
+}
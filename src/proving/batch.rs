@@ -0,0 +1,196 @@
+use halo2_proofs::{
+    arithmetic::{CurveAffine, Field},
+    plonk::{verify_proof, Error, SingleVerifier, VerificationStrategy, VerifyingKey},
+    poly::commitment::{Guard, Params, MSM},
+    transcript::{Blake2bRead, Challenge255, EncodedChallenge},
+};
+use rand_core::OsRng;
+
+/// A verification strategy which returns the MSM of a proof instead of
+/// evaluating it, so that the MSMs of several proofs can be accumulated and
+/// evaluated at once. This is the strategy used by `BatchVerifier`, which
+/// does not expose it.
+#[derive(Debug)]
+pub struct AccumulatorStrategy<'params, C: CurveAffine> {
+    msm: MSM<'params, C>,
+}
+
+impl<'params, C: CurveAffine> AccumulatorStrategy<'params, C> {
+    pub fn new(params: &'params Params<C>) -> Self {
+        Self {
+            msm: MSM::new(params),
+        }
+    }
+}
+
+impl<'params, C: CurveAffine> VerificationStrategy<'params, C> for AccumulatorStrategy<'params, C> {
+    type Output = MSM<'params, C>;
+
+    fn process<E: EncodedChallenge<C>>(
+        self,
+        f: impl FnOnce(MSM<'params, C>) -> Result<Guard<'params, C, E>, Error>,
+    ) -> Result<Self::Output, Error> {
+        let guard = f(self.msm)?;
+        Ok(guard.use_challenges())
+    }
+}
+
+/// A proof together with the values of each instance column.
+pub type BatchItem<F> = (Vec<u8>, Vec<Vec<F>>);
+
+/// Verifies a proof on its own. `instances` holds the values of each
+/// instance column.
+pub fn verify<C: CurveAffine>(
+    params: &Params<C>,
+    vk: &VerifyingKey<C>,
+    proof: &[u8],
+    instances: &[Vec<C::Scalar>],
+) -> Result<(), Error> {
+    let instances: Vec<&[C::Scalar]> = instances.iter().map(|column| &column[..]).collect();
+    let strategy = SingleVerifier::new(params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    verify_proof(params, vk, strategy, &[&instances], &mut transcript)
+}
+
+/// Verifies a batch of `(proof, instances)` pairs for the same verifying key,
+/// and returns the indices of the proofs which failed.
+///
+/// The MSMs of all proofs are scaled by random factors and evaluated at once,
+/// which is much cheaper than evaluating them one by one. Only if the batch
+/// fails are the proofs verified separately to find the failing ones.
+pub fn verify_batch<C: CurveAffine>(
+    params: &Params<C>,
+    vk: &VerifyingKey<C>,
+    proofs: &[BatchItem<C::Scalar>],
+) -> Result<(), Vec<usize>> {
+    let mut failed = vec![];
+    let mut accumulated = params.empty_msm();
+    let mut accumulated_indices = vec![];
+
+    for (i, (proof, instances)) in proofs.iter().enumerate() {
+        let instances: Vec<&[C::Scalar]> = instances.iter().map(|column| &column[..]).collect();
+        let strategy = AccumulatorStrategy::new(params);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+
+        match verify_proof(params, vk, strategy, &[&instances], &mut transcript) {
+            Ok(msm) => {
+                // The random factor ensures that the MSMs of invalid proofs
+                // cannot cancel out, with high probability.
+                accumulated.scale(C::Scalar::random(OsRng));
+                accumulated.add_msm(&msm);
+                accumulated_indices.push(i);
+            }
+            Err(_) => failed.push(i),
+        }
+    }
+
+    if !accumulated.eval() {
+        failed.extend(accumulated_indices.into_iter().filter(|&i| {
+            let (proof, instances) = &proofs[i];
+            verify(params, vk, proof, instances).is_err()
+        }));
+        failed.sort_unstable();
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::fibonacci::fib_three_column::{FibPublicInputs, MyCircuit};
+    use crate::proving::public_inputs::PublicInputs;
+    use halo2_proofs::{
+        circuit::Value,
+        pasta::{EqAffine, Fp},
+        plonk::{create_proof, keygen_pk, keygen_vk, BatchVerifier, ProvingKey},
+        transcript::Blake2bWrite,
+    };
+
+    fn fibonacci(a: u64, b: u64) -> Vec<Vec<Fp>> {
+        let (mut x, mut y) = (a, b);
+        for _ in 2..10 {
+            (x, y) = (y, x + y);
+        }
+        FibPublicInputs {
+            a: Fp::from(a),
+            b: Fp::from(b),
+            out: Fp::from(y),
+        }
+        .to_instances()
+    }
+
+    fn prove(
+        params: &Params<EqAffine>,
+        pk: &ProvingKey<EqAffine>,
+        instances: &[Vec<Fp>],
+    ) -> Vec<u8> {
+        let public_inputs = FibPublicInputs::from_instances(instances).unwrap();
+        let circuit = MyCircuit {
+            a: Value::known(public_inputs.a),
+            b: Value::known(public_inputs.b),
+        };
+        let instances: Vec<&[Fp]> = instances.iter().map(|column| &column[..]).collect();
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            params,
+            pk,
+            &[circuit],
+            &[&instances],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        transcript.finalize()
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let k = minimal_k(&MyCircuit::<Fp>::default());
+        let params: Params<EqAffine> = Params::new(k);
+        let vk = keygen_vk(&params, &MyCircuit::<Fp>::default()).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &MyCircuit::<Fp>::default()).unwrap();
+
+        let mut proofs: Vec<BatchItem<Fp>> = (1..=6)
+            .map(|i| {
+                let instances = fibonacci(i, i + 1);
+                (prove(&params, &pk, &instances), instances)
+            })
+            .collect();
+        assert_eq!(verify_batch(&params, &vk, &proofs), Ok(()));
+
+        // The batch verifier of halo2 agrees, but cannot tell which proofs failed
+        let mut batch = BatchVerifier::new();
+        for (proof, instances) in proofs.iter() {
+            batch.add_proof(vec![instances.clone()], proof.clone());
+        }
+        assert!(batch.finalize(&params, &vk));
+
+        // A proof with a flipped bit, a proof for the wrong output and a
+        // truncated proof
+        let last = proofs[1].0.len() - 1;
+        proofs[1].0[last] ^= 1;
+        let mut wrong_output = FibPublicInputs::from_instances(&proofs[2].1).unwrap();
+        wrong_output.out += Fp::one();
+        proofs[2].1 = wrong_output.to_instances();
+        proofs[4].0.truncate(64);
+
+        assert_eq!(verify_batch(&params, &vk, &proofs), Err(vec![1, 2, 4]));
+        let mut batch = BatchVerifier::new();
+        for (proof, instances) in proofs.iter() {
+            batch.add_proof(vec![instances.clone()], proof.clone());
+        }
+        assert!(!batch.finalize(&params, &vk));
+        for (i, (proof, instances)) in proofs.iter().enumerate() {
+            assert_eq!(
+                verify(&params, &vk, proof, instances).is_ok(),
+                ![1, 2, 4].contains(&i)
+            );
+        }
+    }
+}
@@ -0,0 +1,576 @@
+use std::fmt;
+
+use halo2_proofs::{
+    arithmetic::{CurveAffine, FieldExt},
+    plonk::{Error, VerifyingKey},
+    poly::commitment::Params,
+};
+use pasta_curves::{
+    group::ff::PrimeField,
+    {Fp, Fq},
+};
+use serde::{Deserialize, Serialize};
+
+use super::batch;
+
+/// The first bytes of every serialized envelope.
+pub const MAGIC: [u8; 4] = *b"H2PE";
+
+/// The version of the envelope format written by `to_bytes` and `to_json`.
+pub const VERSION: u8 = 1;
+
+/// The size of the canonical encoding of a Pallas or Vesta element.
+const REPR_BYTES: usize = 32;
+
+/// The field of the circuit, i.e. the scalar field of the proof's curve.
+/// As in the `mimc` module, `Fp` is called Pallas and `Fq` Vesta.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldId {
+    Pallas,
+    Vesta,
+}
+
+impl FieldId {
+    /// Returns the identifier of `F`, which must be `Fp` or `Fq`.
+    pub fn of<F: FieldExt>() -> Self {
+        if F::MODULUS == Fp::MODULUS {
+            FieldId::Pallas
+        } else if F::MODULUS == Fq::MODULUS {
+            FieldId::Vesta
+        } else {
+            panic!("unsupported field with modulus {}", F::MODULUS)
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            FieldId::Pallas => 0,
+            FieldId::Vesta => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Self, EnvelopeError> {
+        match byte {
+            0 => Ok(FieldId::Pallas),
+            1 => Ok(FieldId::Vesta),
+            _ => Err(EnvelopeError::UnknownField(byte)),
+        }
+    }
+
+    fn is_canonical(self, repr: &[u8; REPR_BYTES]) -> bool {
+        match self {
+            FieldId::Pallas => bool::from(Fp::from_repr(*repr).is_some()),
+            FieldId::Vesta => bool::from(Fq::from_repr(*repr).is_some()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum EnvelopeError {
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnknownField(u8),
+    Truncated,
+    TrailingBytes,
+    InvalidCircuitId,
+    NonCanonicalInstance,
+    FieldMismatch { expected: FieldId, found: FieldId },
+    VkDigestMismatch,
+    Hex(hex::FromHexError),
+    Json(serde_json::Error),
+    Proof(Error),
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::BadMagic => write!(f, "not a proof envelope"),
+            EnvelopeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported envelope version {}", version)
+            }
+            EnvelopeError::UnknownField(byte) => write!(f, "unknown field identifier {}", byte),
+            EnvelopeError::Truncated => write!(f, "envelope is truncated"),
+            EnvelopeError::TrailingBytes => write!(f, "envelope has trailing bytes"),
+            EnvelopeError::InvalidCircuitId => write!(f, "circuit identifier is not UTF-8"),
+            EnvelopeError::NonCanonicalInstance => {
+                write!(f, "instance value is not a canonical field element")
+            }
+            EnvelopeError::FieldMismatch { expected, found } => {
+                write!(f, "expected a proof over {:?}, found {:?}", expected, found)
+            }
+            EnvelopeError::VkDigestMismatch => {
+                write!(f, "proof was not created for this verifying key")
+            }
+            EnvelopeError::Hex(e) => write!(f, "invalid hex: {}", e),
+            EnvelopeError::Json(e) => write!(f, "invalid JSON: {}", e),
+            EnvelopeError::Proof(e) => write!(f, "invalid proof: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for EnvelopeError {}
+
+/// Returns a digest of the verifying key for a circuit of `2^k` rows.
+pub fn vk_digest<C: CurveAffine>(k: u32, vk: &VerifyingKey<C>) -> [u8; 32] {
+    // The pinned representation is what halo2 itself hashes into the
+    // transcript, and covers the domain, the constraint system and the
+    // commitments to the fixed columns and permutation.
+    let hash = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(b"Halo2-VK-Digest")
+        .to_state()
+        .update(&k.to_le_bytes())
+        .update(format!("{:?}", vk.pinned()).as_bytes())
+        .finalize();
+    hash.as_bytes().try_into().unwrap()
+}
+
+/// A proof together with everything needed to check it against a verifying
+/// key: the circuit it was created for, the field, the values of each
+/// instance column and the raw transcript bytes.
+///
+/// The binary format written by `to_bytes` is
+///
+/// ```text
+/// magic (4) | version (1) | field (1) | k (u32)
+/// | circuit id length (u32) | circuit id (UTF-8)
+/// | vk digest (32)
+/// | number of columns (u32) | for each column: number of rows (u32) | 32-byte values
+/// | proof length (u32) | proof
+/// ```
+///
+/// where all integers are little-endian and field elements are in their
+/// canonical encoding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofEnvelope {
+    pub circuit_id: String,
+    pub k: u32,
+    pub field: FieldId,
+    pub vk_digest: [u8; 32],
+    pub instances: Vec<Vec<[u8; REPR_BYTES]>>,
+    pub proof: Vec<u8>,
+}
+
+impl ProofEnvelope {
+    pub fn new<C: CurveAffine>(
+        circuit_id: &str,
+        k: u32,
+        vk: &VerifyingKey<C>,
+        instances: &[Vec<C::Scalar>],
+        proof: Vec<u8>,
+    ) -> Self {
+        let instances = instances
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|value| value.to_repr().as_ref().try_into().unwrap())
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            circuit_id: circuit_id.to_string(),
+            k,
+            field: FieldId::of::<C::Scalar>(),
+            vk_digest: vk_digest(k, vk),
+            instances,
+            proof,
+        }
+    }
+
+    /// Decodes the instance columns as elements of `F`.
+    pub fn instances<F: FieldExt>(&self) -> Result<Vec<Vec<F>>, EnvelopeError> {
+        let expected = FieldId::of::<F>();
+        if self.field != expected {
+            return Err(EnvelopeError::FieldMismatch {
+                expected,
+                found: self.field,
+            });
+        }
+
+        self.instances
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|bytes| {
+                        let mut repr = F::Repr::default();
+                        repr.as_mut().copy_from_slice(bytes);
+                        Option::from(F::from_repr(repr)).ok_or(EnvelopeError::NonCanonicalInstance)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Checks that the envelope was created for `vk` and verifies the proof.
+    pub fn verify<C: CurveAffine>(
+        &self,
+        params: &Params<C>,
+        vk: &VerifyingKey<C>,
+    ) -> Result<(), EnvelopeError> {
+        let instances = self.instances::<C::Scalar>()?;
+        if self.vk_digest != vk_digest(self.k, vk) {
+            return Err(EnvelopeError::VkDigestMismatch);
+        }
+        batch::verify(params, vk, &self.proof, &instances).map_err(EnvelopeError::Proof)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.extend_from_slice(&MAGIC);
+        bytes.push(VERSION);
+        bytes.push(self.field.to_byte());
+        bytes.extend_from_slice(&self.k.to_le_bytes());
+        write_len(&mut bytes, self.circuit_id.len());
+        bytes.extend_from_slice(self.circuit_id.as_bytes());
+        bytes.extend_from_slice(&self.vk_digest);
+        write_len(&mut bytes, self.instances.len());
+        for column in self.instances.iter() {
+            write_len(&mut bytes, column.len());
+            for value in column.iter() {
+                bytes.extend_from_slice(value);
+            }
+        }
+        write_len(&mut bytes, self.proof.len());
+        bytes.extend_from_slice(&self.proof);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EnvelopeError> {
+        let mut reader = Reader(bytes);
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(EnvelopeError::BadMagic);
+        }
+        let version = reader.take(1)?[0];
+        if version != VERSION {
+            return Err(EnvelopeError::UnsupportedVersion(version));
+        }
+        let field = FieldId::from_byte(reader.take(1)?[0])?;
+        let k = reader.read_u32()?;
+
+        let len = reader.read_len()?;
+        let circuit_id = String::from_utf8(reader.take(len)?.to_vec())
+            .map_err(|_| EnvelopeError::InvalidCircuitId)?;
+
+        let vk_digest = reader.take(32)?.try_into().unwrap();
+
+        let num_columns = reader.read_len()?;
+        let mut instances = vec![];
+        for _ in 0..num_columns {
+            let num_rows = reader.read_len()?;
+            let mut column = vec![];
+            for _ in 0..num_rows {
+                let value: [u8; REPR_BYTES] = reader.take(REPR_BYTES)?.try_into().unwrap();
+                if !field.is_canonical(&value) {
+                    return Err(EnvelopeError::NonCanonicalInstance);
+                }
+                column.push(value);
+            }
+            instances.push(column);
+        }
+
+        let len = reader.read_len()?;
+        let proof = reader.take(len)?.to_vec();
+
+        if !reader.0.is_empty() {
+            return Err(EnvelopeError::TrailingBytes);
+        }
+
+        Ok(Self {
+            circuit_id,
+            k,
+            field,
+            vk_digest,
+            instances,
+            proof,
+        })
+    }
+
+    /// Encodes the envelope as JSON, with the digest, the instance values and
+    /// the proof in hex.
+    pub fn to_json(&self) -> String {
+        let json = JsonEnvelope {
+            version: VERSION,
+            circuit_id: self.circuit_id.clone(),
+            k: self.k,
+            field: self.field,
+            vk_digest: hex::encode(self.vk_digest),
+            instances: self
+                .instances
+                .iter()
+                .map(|column| column.iter().map(hex::encode).collect())
+                .collect(),
+            proof: hex::encode(&self.proof),
+        };
+        serde_json::to_string_pretty(&json).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, EnvelopeError> {
+        let json: JsonEnvelope = serde_json::from_str(json).map_err(EnvelopeError::Json)?;
+        if json.version != VERSION {
+            return Err(EnvelopeError::UnsupportedVersion(json.version));
+        }
+
+        let instances = json
+            .instances
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|value| {
+                        let value = decode_hex::<REPR_BYTES>(value)?;
+                        if json.field.is_canonical(&value) {
+                            Ok(value)
+                        } else {
+                            Err(EnvelopeError::NonCanonicalInstance)
+                        }
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            circuit_id: json.circuit_id,
+            k: json.k,
+            field: json.field,
+            vk_digest: decode_hex(&json.vk_digest)?,
+            instances,
+            proof: hex::decode(&json.proof).map_err(EnvelopeError::Hex)?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonEnvelope {
+    version: u8,
+    circuit_id: String,
+    k: u32,
+    field: FieldId,
+    vk_digest: String,
+    instances: Vec<Vec<String>>,
+    proof: String,
+}
+
+fn decode_hex<const N: usize>(value: &str) -> Result<[u8; N], EnvelopeError> {
+    let mut bytes = [0u8; N];
+    hex::decode_to_slice(value, &mut bytes).map_err(EnvelopeError::Hex)?;
+    Ok(bytes)
+}
+
+fn write_len(bytes: &mut Vec<u8>, len: usize) {
+    let len = u32::try_from(len).expect("length does not fit in a u32");
+    bytes.extend_from_slice(&len.to_le_bytes());
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], EnvelopeError> {
+        if self.0.len() < n {
+            return Err(EnvelopeError::Truncated);
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, EnvelopeError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn read_len(&mut self) -> Result<usize, EnvelopeError> {
+        Ok(self.read_u32()? as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::fibonacci::fib_three_column::{FibPublicInputs, MyCircuit};
+    use crate::proving::public_inputs::PublicInputs;
+    use halo2_proofs::{
+        circuit::Value,
+        pasta::EqAffine,
+        plonk::{create_proof, keygen_pk, keygen_vk},
+        transcript::{Blake2bWrite, Challenge255},
+    };
+    use rand_core::OsRng;
+
+    fn fibonacci_envelope(
+        params: &Params<EqAffine>,
+        vk: &VerifyingKey<EqAffine>,
+        k: u32,
+    ) -> ProofEnvelope {
+        let pk = keygen_pk(params, vk.clone(), &MyCircuit::<Fp>::default()).unwrap();
+        let circuit = MyCircuit {
+            a: Value::known(Fp::one()),
+            b: Value::known(Fp::one()),
+        };
+        let instances = FibPublicInputs {
+            a: Fp::one(),
+            b: Fp::one(),
+            out: Fp::from(55),
+        }
+        .to_instances();
+        let instance_slices: Vec<&[Fp]> = instances.iter().map(|column| &column[..]).collect();
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            params,
+            &pk,
+            &[circuit],
+            &[&instance_slices],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+
+        ProofEnvelope::new("fib_three_column", k, vk, &instances, transcript.finalize())
+    }
+
+    #[test]
+    fn test_envelope_round_trip() {
+        let k = minimal_k(&MyCircuit::<Fp>::default());
+        let params: Params<EqAffine> = Params::new(k);
+        let vk = keygen_vk(&params, &MyCircuit::<Fp>::default()).unwrap();
+        let envelope = fibonacci_envelope(&params, &vk, k);
+        assert_eq!(envelope.field, FieldId::Pallas);
+        envelope.verify(&params, &vk).unwrap();
+
+        let bytes = envelope.to_bytes();
+        assert_eq!(&bytes[..4], b"H2PE");
+        let decoded = ProofEnvelope::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, envelope);
+        decoded.verify(&params, &vk).unwrap();
+
+        let decoded = ProofEnvelope::from_json(&envelope.to_json()).unwrap();
+        assert_eq!(decoded, envelope);
+        assert_eq!(
+            decoded.instances::<Fp>().unwrap(),
+            vec![vec![Fp::one(), Fp::one(), Fp::from(55)]]
+        );
+    }
+
+    #[test]
+    fn test_envelope_rejects_mismatched_vk() {
+        let k = minimal_k(&MyCircuit::<Fp>::default());
+        let params: Params<EqAffine> = Params::new(k);
+        let vk = keygen_vk(&params, &MyCircuit::<Fp>::default()).unwrap();
+        let mut envelope = fibonacci_envelope(&params, &vk, k);
+
+        // The verifying key of the same circuit with a larger domain
+        let other_params: Params<EqAffine> = Params::new(k + 1);
+        let other_vk = keygen_vk(&other_params, &MyCircuit::<Fp>::default()).unwrap();
+        assert!(matches!(
+            envelope.verify(&other_params, &other_vk),
+            Err(EnvelopeError::VkDigestMismatch)
+        ));
+
+        // A wrong `k` also changes the digest
+        envelope.k = k + 1;
+        assert!(matches!(
+            envelope.verify(&params, &vk),
+            Err(EnvelopeError::VkDigestMismatch)
+        ));
+        envelope.k = k;
+
+        envelope.vk_digest[0] ^= 1;
+        let decoded = ProofEnvelope::from_bytes(&envelope.to_bytes()).unwrap();
+        assert!(matches!(
+            decoded.verify(&params, &vk),
+            Err(EnvelopeError::VkDigestMismatch)
+        ));
+        envelope.vk_digest[0] ^= 1;
+
+        // The digest matches but the proof does not
+        envelope.instances[0][2] = Fp::from(56).to_repr();
+        assert!(matches!(
+            envelope.verify(&params, &vk),
+            Err(EnvelopeError::Proof(_))
+        ));
+
+        assert!(matches!(
+            envelope.instances::<Fq>(),
+            Err(EnvelopeError::FieldMismatch {
+                expected: FieldId::Vesta,
+                found: FieldId::Pallas,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_envelope_rejects_malformed_bytes() {
+        let k = minimal_k(&MyCircuit::<Fp>::default());
+        let params: Params<EqAffine> = Params::new(k);
+        let vk = keygen_vk(&params, &MyCircuit::<Fp>::default()).unwrap();
+        let envelope = fibonacci_envelope(&params, &vk, k);
+        let bytes = envelope.to_bytes();
+
+        for len in 0..bytes.len() {
+            assert!(
+                matches!(
+                    ProofEnvelope::from_bytes(&bytes[..len]),
+                    Err(EnvelopeError::Truncated)
+                ),
+                "truncated to {} bytes",
+                len
+            );
+        }
+
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert!(matches!(
+            ProofEnvelope::from_bytes(&extended),
+            Err(EnvelopeError::TrailingBytes)
+        ));
+
+        let mut corrupted = bytes.clone();
+        corrupted[0] = b'X';
+        assert!(matches!(
+            ProofEnvelope::from_bytes(&corrupted),
+            Err(EnvelopeError::BadMagic)
+        ));
+
+        let mut corrupted = bytes.clone();
+        corrupted[4] = VERSION + 1;
+        assert!(matches!(
+            ProofEnvelope::from_bytes(&corrupted),
+            Err(EnvelopeError::UnsupportedVersion(2))
+        ));
+
+        let mut corrupted = bytes.clone();
+        corrupted[5] = 2;
+        assert!(matches!(
+            ProofEnvelope::from_bytes(&corrupted),
+            Err(EnvelopeError::UnknownField(2))
+        ));
+
+        // An instance value of p, which is not a canonical Pallas element
+        let mut non_canonical = envelope.clone();
+        non_canonical.instances[0][0] = (-Fp::one()).to_repr();
+        non_canonical.instances[0][0][0] += 1;
+        assert!(matches!(
+            ProofEnvelope::from_bytes(&non_canonical.to_bytes()),
+            Err(EnvelopeError::NonCanonicalInstance)
+        ));
+        assert!(matches!(
+            ProofEnvelope::from_json(&non_canonical.to_json()),
+            Err(EnvelopeError::NonCanonicalInstance)
+        ));
+
+        let json = envelope.to_json();
+        assert!(matches!(
+            ProofEnvelope::from_json(&json[..json.len() - 2]),
+            Err(EnvelopeError::Json(_))
+        ));
+        let truncated_proof = json.replace(&hex::encode(&envelope.proof), "abc");
+        assert!(matches!(
+            ProofEnvelope::from_json(&truncated_proof),
+            Err(EnvelopeError::Hex(_))
+        ));
+    }
+}
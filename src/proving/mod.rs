@@ -0,0 +1,4 @@
+pub mod batch;
+pub mod envelope;
+pub mod prover;
+pub mod public_inputs;
@@ -0,0 +1,214 @@
+use halo2_proofs::{
+    arithmetic::CurveAffine,
+    plonk::{create_proof, Circuit, Error, ProvingKey},
+    poly::commitment::Params,
+    transcript::{Blake2bWrite, Challenge255},
+};
+use rand_chacha::ChaCha20Rng;
+use rand_core::{OsRng, RngCore, SeedableRng};
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+
+/// The source of the blinding factors of the proofs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofRng {
+    /// The randomness of the operating system, for proofs which are sent out.
+    Os,
+    /// A ChaCha20 stream per proof, seeded with the given seed and numbered by
+    /// the index of the circuit in the batch, so that the proofs of a batch
+    /// are the same whatever the number of threads. This is for tests and
+    /// throughput measurements only, since the blinding factors are known.
+    Seeded(u64),
+}
+
+/// Proves many circuits with the same proving key in parallel, one proof per
+/// circuit, on a dedicated thread pool. The parallel steps of `create_proof`
+/// itself also run on this pool, so that the prover never uses more than its
+/// number of threads.
+pub struct BatchProver<'a, C: CurveAffine> {
+    params: &'a Params<C>,
+    pk: &'a ProvingKey<C>,
+    pool: ThreadPool,
+    rng: ProofRng,
+}
+
+impl<'a, C: CurveAffine> BatchProver<'a, C> {
+    /// Creates a prover with a pool of `threads` threads, or one thread per
+    /// CPU if `threads` is 0.
+    pub fn new(
+        params: &'a Params<C>,
+        pk: &'a ProvingKey<C>,
+        threads: usize,
+        rng: ProofRng,
+    ) -> Result<Self, ThreadPoolBuildError> {
+        let pool = ThreadPoolBuilder::new().num_threads(threads).build()?;
+        Ok(Self {
+            params,
+            pk,
+            pool,
+            rng,
+        })
+    }
+
+    pub fn threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Proves each circuit with the values of its instance columns, and
+    /// returns the proofs in the order of the circuits. Fails with the error
+    /// of one of the circuits which could not be proven.
+    pub fn prove<ConcreteCircuit>(
+        &self,
+        batch: &[(ConcreteCircuit, Vec<Vec<C::Scalar>>)],
+    ) -> Result<Vec<Vec<u8>>, Error>
+    where
+        ConcreteCircuit: Circuit<C::Scalar> + Sync,
+    {
+        self.pool.install(|| {
+            batch
+                .par_iter()
+                .enumerate()
+                .map(|(index, (circuit, instances))| {
+                    prove_in_batch(self.params, self.pk, circuit, instances, self.rng, index)
+                })
+                .collect()
+        })
+    }
+}
+
+/// Proves a circuit with the values of its instance columns. With
+/// `ProofRng::Seeded`, the same circuit, witness and seed always give the same
+/// proof bytes, which is the proof of the circuit at the first position of a
+/// seeded batch.
+pub fn prove<C, ConcreteCircuit>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuit: &ConcreteCircuit,
+    instances: &[Vec<C::Scalar>],
+    rng: ProofRng,
+) -> Result<Vec<u8>, Error>
+where
+    C: CurveAffine,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    prove_in_batch(params, pk, circuit, instances, rng, 0)
+}
+
+/// Proves the circuit at position `index` of a batch, whose seeded RNG is
+/// the stream numbered `index`.
+fn prove_in_batch<C, ConcreteCircuit>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuit: &ConcreteCircuit,
+    instances: &[Vec<C::Scalar>],
+    rng: ProofRng,
+    index: usize,
+) -> Result<Vec<u8>, Error>
+where
+    C: CurveAffine,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    match rng {
+        ProofRng::Os => prove_with(params, pk, circuit, instances, OsRng),
+        ProofRng::Seeded(seed) => {
+            let mut rng = ChaCha20Rng::seed_from_u64(seed);
+            rng.set_stream(index as u64);
+            prove_with(params, pk, circuit, instances, rng)
+        }
+    }
+}
+
+fn prove_with<C, ConcreteCircuit>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuit: &ConcreteCircuit,
+    instances: &[Vec<C::Scalar>],
+    rng: impl RngCore,
+) -> Result<Vec<u8>, Error>
+where
+    C: CurveAffine,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    let instances: Vec<&[C::Scalar]> = instances.iter().map(|column| &column[..]).collect();
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(
+        params,
+        pk,
+        std::slice::from_ref(circuit),
+        &[&instances],
+        rng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::mimc_pallas::mimc_hash::{MiMC5HashCircuit, MiMC5HashPublicInputs};
+    use crate::mimc_pallas::pallas_round_constants::{NUM_ROUNDS, ROUND_CONSTANTS};
+    use crate::mimc_pallas::primitives::mimc5_hash;
+    use crate::proving::batch::{verify_batch, BatchItem};
+    use crate::proving::public_inputs::PublicInputs;
+    use halo2_proofs::{
+        pasta::{EqAffine, Fp},
+        plonk::{keygen_pk, keygen_vk},
+    };
+
+    fn hashes(messages: &[u64]) -> Vec<(MiMC5HashCircuit<Fp>, Vec<Vec<Fp>>)> {
+        messages
+            .iter()
+            .map(|&message| {
+                let message = Fp::from(message);
+                let mut output = message;
+                mimc5_hash::<Fp, { NUM_ROUNDS }>(&mut output, ROUND_CONSTANTS);
+                let circuit = MiMC5HashCircuit {
+                    message,
+                    num_rounds: NUM_ROUNDS,
+                    round_constants: ROUND_CONSTANTS.to_vec(),
+                };
+                let public_inputs = MiMC5HashPublicInputs { message, output };
+                (circuit, public_inputs.to_instances())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_batch_prover() {
+        let batch = hashes(&[1, 2, 3, 4]);
+        let k = minimal_k(&batch[0].0);
+        let params: Params<EqAffine> = Params::new(k);
+        let vk = keygen_vk(&params, &batch[0].0).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &batch[0].0).unwrap();
+
+        let prover = BatchProver::new(&params, &pk, 2, ProofRng::Seeded(7)).unwrap();
+        assert_eq!(prover.threads(), 2);
+        let proofs = prover.prove(&batch).unwrap();
+
+        let items: Vec<BatchItem<Fp>> = proofs
+            .iter()
+            .cloned()
+            .zip(batch.iter().map(|(_, instances)| instances.clone()))
+            .collect();
+        assert_eq!(verify_batch(&params, &vk, &items), Ok(()));
+
+        // The seeded proofs do not depend on the number of threads, but on
+        // the seed and the position in the batch
+        let single_thread = BatchProver::new(&params, &pk, 1, ProofRng::Seeded(7)).unwrap();
+        assert_eq!(single_thread.prove(&batch).unwrap(), proofs);
+        assert_eq!(
+            prove(&params, &pk, &batch[0].0, &batch[0].1, ProofRng::Seeded(7)).unwrap(),
+            proofs[0]
+        );
+        let reseeded = BatchProver::new(&params, &pk, 1, ProofRng::Seeded(8)).unwrap();
+        assert_ne!(reseeded.prove(&batch[..1]).unwrap()[0], proofs[0]);
+
+        // A wrong digest is only caught by the verifier
+        let mut wrong = hashes(&[5]);
+        wrong[0].1[0][MiMC5HashPublicInputs::ROWS.output] += Fp::one();
+        let os = BatchProver::new(&params, &pk, 0, ProofRng::Os).unwrap();
+        let proof = os.prove(&wrong).unwrap().remove(0);
+        let items = vec![(proof, wrong.remove(0).1)];
+        assert_eq!(verify_batch(&params, &vk, &items), Err(vec![0]));
+    }
+}
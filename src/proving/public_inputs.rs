@@ -0,0 +1,160 @@
+use std::fmt;
+
+use halo2_proofs::arithmetic::FieldExt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PublicInputsError {
+    WrongNumberOfColumns {
+        expected: usize,
+        found: usize,
+    },
+    WrongNumberOfRows {
+        column: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for PublicInputsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PublicInputsError::WrongNumberOfColumns { expected, found } => {
+                write!(f, "expected {} instance columns, found {}", expected, found)
+            }
+            PublicInputsError::WrongNumberOfRows {
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected {} rows in instance column {}, found {}",
+                expected, column, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PublicInputsError {}
+
+/// The public inputs of a circuit as named fields, which are converted to and
+/// from the values of each instance column passed to `MockProver::run`,
+/// `create_proof` and `verify_proof`.
+pub trait PublicInputs<F: FieldExt>: Sized {
+    /// The names of the public inputs in each instance column, by row.
+    const LAYOUT: &'static [&'static [&'static str]];
+
+    fn to_instances(&self) -> Vec<Vec<F>>;
+
+    fn from_instances(instances: &[Vec<F>]) -> Result<Self, PublicInputsError>;
+
+    /// Checks that `instances` has the shape given by `LAYOUT`.
+    fn check_layout(instances: &[Vec<F>]) -> Result<(), PublicInputsError> {
+        if instances.len() != Self::LAYOUT.len() {
+            return Err(PublicInputsError::WrongNumberOfColumns {
+                expected: Self::LAYOUT.len(),
+                found: instances.len(),
+            });
+        }
+        for (column, (values, names)) in instances.iter().zip(Self::LAYOUT).enumerate() {
+            if values.len() != names.len() {
+                return Err(PublicInputsError::WrongNumberOfRows {
+                    column,
+                    expected: names.len(),
+                    found: values.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Declares a struct of public inputs stored in a single instance column, one
+/// per row in the order of the fields. `ROWS` holds the row of each field,
+/// e.g. `FibPublicInputs::ROWS.out`, for use in `constrain_instance` and
+/// `assign_advice_from_instance`.
+macro_rules! public_inputs {
+    ($(#[$meta:meta])* $vis:vis struct $name:ident { $($field:ident),+ $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+        $vis struct $name<F> {
+            $(pub $field: F,)+
+        }
+
+        impl $name<usize> {
+            /// The row of each public input in the instance column.
+            #[allow(unused_assignments)]
+            pub const ROWS: Self = {
+                let mut row = 0;
+                Self {
+                    $($field: {
+                        let field_row = row;
+                        row += 1;
+                        field_row
+                    },)+
+                }
+            };
+        }
+
+        impl<F: halo2_proofs::arithmetic::FieldExt>
+            $crate::proving::public_inputs::PublicInputs<F> for $name<F>
+        {
+            const LAYOUT: &'static [&'static [&'static str]] = &[&[$(stringify!($field)),+]];
+
+            fn to_instances(&self) -> Vec<Vec<F>> {
+                vec![vec![$(self.$field),+]]
+            }
+
+            fn from_instances(
+                instances: &[Vec<F>],
+            ) -> Result<Self, $crate::proving::public_inputs::PublicInputsError> {
+                Self::check_layout(instances)?;
+                let mut values = instances[0].iter().copied();
+                Ok(Self {
+                    $($field: values.next().unwrap(),)+
+                })
+            }
+        }
+    };
+}
+
+pub(crate) use public_inputs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::pasta::Fp;
+
+    public_inputs! {
+        struct TestInputs { x, y, z }
+    }
+
+    #[test]
+    fn test_public_inputs_layout() {
+        let inputs = TestInputs {
+            x: Fp::from(1),
+            y: Fp::from(2),
+            z: Fp::from(3),
+        };
+        let instances = inputs.to_instances();
+        assert_eq!(instances, vec![vec![Fp::from(1), Fp::from(2), Fp::from(3)]]);
+        assert_eq!(TestInputs::from_instances(&instances), Ok(inputs));
+
+        assert_eq!(TestInputs::ROWS, TestInputs { x: 0, y: 1, z: 2 });
+
+        assert_eq!(
+            TestInputs::<Fp>::from_instances(&[]),
+            Err(PublicInputsError::WrongNumberOfColumns {
+                expected: 1,
+                found: 0
+            })
+        );
+        assert_eq!(
+            TestInputs::from_instances(&[vec![Fp::one(); 4]]),
+            Err(PublicInputsError::WrongNumberOfRows {
+                column: 0,
+                expected: 3,
+                found: 4
+            })
+        );
+    }
+}
@@ -0,0 +1,472 @@
+//! A first step towards recursion on the Pasta cycle.
+//!
+//! A proof for a circuit over `Fp` (Pallas in the naming of the `mimc`
+//! module) commits to its columns with points of the Vesta curve, whose
+//! coordinates are elements of `Fq`. Arithmetic on these points is therefore
+//! native in a circuit over `Fq` (Vesta), which is why the two curves are
+//! used together for recursion.
+//!
+//! `FoldCircuit` checks the parts of such a proof's verification which are
+//! non-native for the `Fp` circuit itself: it derives a challenge `x` from two
+//! commitments `A` and `B` with a transcript hash, and computes the combined
+//! commitment `C = A + [x]B` with a variable-base scalar multiplication, as a
+//! verifier does when it folds commitments before the final opening check.
+//!
+//! halo2 hashes the transcript with Blake2b, which is very expensive in a
+//! circuit, so recursive proofs use an algebraic transcript instead. Here the
+//! challenge is the one-way hash of `mimc::compression` of the commitment
+//! coordinates over `Fq`, in a domain of its own. The keyless MiMC sponge
+//! would not do, since it can be inverted to find commitments for a chosen
+//! challenge.
+
+use halo2_proofs::{
+    arithmetic::{CurveAffine, CurveExt, FieldExt},
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    pasta::{vesta, EqAffine, Fp, Fq},
+    plonk::{Circuit, ConstraintSystem, Error},
+    transcript::{Blake2bRead, Challenge255, TranscriptRead},
+};
+use pasta_curves::group::{ff::PrimeField, Curve, Group};
+
+use crate::arithmetic::standard_plonk::{
+    FieldInstructions, Number, StandardPlonkChip, StandardPlonkConfig,
+};
+use crate::encoding::field_bytes::{FieldBytesChip, FieldBytesConfig};
+use crate::logic::boolean::{BooleanChip, BooleanConfig};
+use crate::mimc::compression::{
+    hash_in_domain, Domain, MiMC5CompressionChip, MiMC5CompressionConfig,
+};
+use crate::mimc::mimc_cipher::MiMC5CipherVestaChip;
+use crate::mimc::round_constants::{MIMC_HASH_VESTA_ROUND_CONSTANTS, NUM_ROUNDS};
+use crate::proving::public_inputs::public_inputs;
+
+/// Number of bits of the challenge used as a scalar. The challenge is less
+/// than the modulus of `Fq`, which has 255 bits, but `FieldBytesChip`
+/// decomposes it into 32 bytes, so the double-and-add loop also runs over the
+/// most significant bit, which is always 0.
+const SCALAR_BITS: usize = 256;
+
+/// Reads the first `n` commitments of a proof created with a Blake2b
+/// transcript over Vesta, i.e. for a circuit over `Fp`. These are the
+/// commitments to the advice columns.
+pub fn read_commitments(proof: &[u8], n: usize) -> std::io::Result<Vec<vesta::Affine>> {
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(proof);
+    (0..n).map(|_| transcript.read_point()).collect()
+}
+
+/// Returns the challenge for the commitments, the hash of their coordinates
+/// in the `FoldChallenge` domain of `mimc::compression`.
+pub fn transcript_challenge(commitments: &[vesta::Affine]) -> Fq {
+    let coordinates: Vec<Fq> = commitments
+        .iter()
+        .flat_map(|commitment| {
+            let coordinates = commitment.coordinates().unwrap();
+            [*coordinates.x(), *coordinates.y()]
+        })
+        .collect();
+    hash_in_domain::<Fq, NUM_ROUNDS>(
+        Domain::FoldChallenge,
+        &coordinates,
+        MIMC_HASH_VESTA_ROUND_CONSTANTS,
+    )
+}
+
+/// Returns `A + [x]B`, where the challenge `x` is reduced modulo the order of
+/// Vesta, i.e. the modulus of `Fp`.
+pub fn fold(a: vesta::Affine, b: vesta::Affine, challenge: Fq) -> vesta::Affine {
+    let mut bytes = [0u8; 64];
+    bytes[..32].copy_from_slice(challenge.to_repr().as_ref());
+    (a + b * Fp::from_bytes_wide(&bytes)).to_affine()
+}
+
+/// The point which the double-and-add loop starts from, so that no addition
+/// involves the identity. Its discrete logarithm is unknown, so the
+/// accumulator never equals `±B` in practice.
+fn offset_point() -> vesta::Affine {
+    vesta::Point::hash_to_curve("halo2lg-experiments:cycle")(b"offset").to_affine()
+}
+
+/// Returns `-[2^SCALAR_BITS]T`, which removes the offset point `T` from the
+/// result of the double-and-add loop.
+fn offset_correction() -> vesta::Affine {
+    let mut point = vesta::Point::from(offset_point());
+    for _ in 0..SCALAR_BITS {
+        point = point.double();
+    }
+    (-point).to_affine()
+}
+
+public_inputs! {
+    /// The commitments `A` and `B` and the folded commitment `C = A + [x]B`
+    pub struct FoldPublicInputs { a_x, a_y, b_x, b_y, c_x, c_y }
+}
+
+#[derive(Clone, Debug)]
+pub struct FoldConfig {
+    plonk: StandardPlonkConfig,
+    hash: MiMC5CompressionConfig,
+    field_bytes: FieldBytesConfig<Fq>,
+    boolean: BooleanConfig<8>,
+}
+
+/// Derives the challenge `x` from the commitments `A` and `B` of a proof over
+/// `Fp`, and computes `C = A + [x]B`.
+///
+/// The point arithmetic is built from the instructions of the
+/// `StandardPlonkChip`, with incomplete addition formulas. The challenge is
+/// decomposed into its canonical bytes with the `FieldBytesChip` and the
+/// bytes into bits with the `BooleanChip`.
+#[derive(Default)]
+pub struct FoldCircuit {
+    pub a: Value<vesta::Affine>,
+    pub b: Value<vesta::Affine>,
+}
+
+/// A point of Vesta in affine coordinates.
+#[derive(Clone, Debug)]
+struct Point {
+    x: Number<Fq>,
+    y: Number<Fq>,
+}
+
+impl FoldCircuit {
+    fn load_point(
+        chip: &StandardPlonkChip<Fq>,
+        mut layouter: impl Layouter<Fq>,
+        point: Value<vesta::Affine>,
+    ) -> Result<Point, Error> {
+        let coordinates = point.map(|point| point.coordinates().unwrap());
+        let x = chip.load_private(
+            layouter.namespace(|| "x"),
+            coordinates.map(|coordinates| *coordinates.x()),
+        )?;
+        let y = chip.load_private(
+            layouter.namespace(|| "y"),
+            coordinates.map(|coordinates| *coordinates.y()),
+        )?;
+        Self::check_on_curve(chip, layouter.namespace(|| "on curve"), &x, &y)?;
+        Ok(Point { x, y })
+    }
+
+    fn load_constant_point(
+        chip: &StandardPlonkChip<Fq>,
+        mut layouter: impl Layouter<Fq>,
+        point: vesta::Affine,
+    ) -> Result<Point, Error> {
+        let coordinates = point.coordinates().unwrap();
+        Ok(Point {
+            x: chip.load_constant(layouter.namespace(|| "x"), *coordinates.x())?,
+            y: chip.load_constant(layouter.namespace(|| "y"), *coordinates.y())?,
+        })
+    }
+
+    /// Constrains `y^2 = x^3 + 5`.
+    fn check_on_curve(
+        chip: &StandardPlonkChip<Fq>,
+        mut layouter: impl Layouter<Fq>,
+        x: &Number<Fq>,
+        y: &Number<Fq>,
+    ) -> Result<(), Error> {
+        let y2 = chip.square(layouter.namespace(|| "y^2"), y.clone())?;
+        let x2 = chip.square(layouter.namespace(|| "x^2"), x.clone())?;
+        let x3 = chip.mul(layouter.namespace(|| "x^3"), x2, x.clone())?;
+        let rhs = chip.add_constant(layouter.namespace(|| "x^3 + b"), x3, vesta::Point::b())?;
+        chip.assert_equal(layouter.namespace(|| "y^2 = x^3 + b"), y2, rhs)
+    }
+
+    /// Returns `p + q`. The constraints cannot be satisfied when `p` and `q`
+    /// have the same x-coordinate.
+    fn add(
+        chip: &StandardPlonkChip<Fq>,
+        mut layouter: impl Layouter<Fq>,
+        p: &Point,
+        q: &Point,
+    ) -> Result<Point, Error> {
+        let dy = chip.sub(layouter.namespace(|| "dy"), q.y.clone(), p.y.clone())?;
+        let dx = chip.sub(layouter.namespace(|| "dx"), q.x.clone(), p.x.clone())?;
+        let lambda = chip.div(layouter.namespace(|| "lambda"), dy, dx)?;
+        Self::complete_line(chip, layouter, lambda, p, &q.x)
+    }
+
+    /// Returns `2p`. Vesta has no point of order two, so `y` is never zero.
+    fn double(
+        chip: &StandardPlonkChip<Fq>,
+        mut layouter: impl Layouter<Fq>,
+        p: &Point,
+    ) -> Result<Point, Error> {
+        let x2 = chip.square(layouter.namespace(|| "x^2"), p.x.clone())?;
+        let numerator = chip.mul_by_constant(layouter.namespace(|| "3x^2"), x2, Fq::from(3))?;
+        let denominator =
+            chip.mul_by_constant(layouter.namespace(|| "2y"), p.y.clone(), Fq::from(2))?;
+        let lambda = chip.div(layouter.namespace(|| "lambda"), numerator, denominator)?;
+        Self::complete_line(chip, layouter, lambda, p, &p.x)
+    }
+
+    /// Returns the third intersection, reflected, of the curve and the line
+    /// of slope `lambda` through `p` and the point with x-coordinate `qx`.
+    fn complete_line(
+        chip: &StandardPlonkChip<Fq>,
+        mut layouter: impl Layouter<Fq>,
+        lambda: Number<Fq>,
+        p: &Point,
+        qx: &Number<Fq>,
+    ) -> Result<Point, Error> {
+        // x_r = lambda^2 - x_p - x_q
+        let lambda2 = chip.square(layouter.namespace(|| "lambda^2"), lambda.clone())?;
+        let t = chip.sub(
+            layouter.namespace(|| "lambda^2 - x_p"),
+            lambda2,
+            p.x.clone(),
+        )?;
+        let x = chip.sub(layouter.namespace(|| "x_r"), t, qx.clone())?;
+        // y_r = lambda * (x_p - x_r) - y_p
+        let dx = chip.sub(layouter.namespace(|| "x_p - x_r"), p.x.clone(), x.clone())?;
+        let t = chip.mul(layouter.namespace(|| "lambda * (x_p - x_r)"), lambda, dx)?;
+        let y = chip.sub(layouter.namespace(|| "y_r"), t, p.y.clone())?;
+        Ok(Point { x, y })
+    }
+
+    /// Returns `bit ? p : q` for a constrained bit.
+    fn select(
+        chip: &StandardPlonkChip<Fq>,
+        mut layouter: impl Layouter<Fq>,
+        bit: &Number<Fq>,
+        p: &Point,
+        q: &Point,
+    ) -> Result<Point, Error> {
+        let mut select = |name: &'static str, p: &Number<Fq>, q: &Number<Fq>| {
+            // q + bit * (p - q)
+            let d = chip.sub(layouter.namespace(|| name), p.clone(), q.clone())?;
+            let t = chip.mul(layouter.namespace(|| name), bit.clone(), d)?;
+            chip.add(layouter.namespace(|| name), q.clone(), t)
+        };
+        Ok(Point {
+            x: select("select x", &p.x, &q.x)?,
+            y: select("select y", &p.y, &q.y)?,
+        })
+    }
+}
+
+impl Circuit<Fq> for FoldCircuit {
+    type Config = FoldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fq>) -> Self::Config {
+        let plonk_advice = [(); 3].map(|_| meta.advice_column());
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+        let plonk = StandardPlonkChip::configure(meta, plonk_advice, instance, constant);
+
+        // The byte decomposition, the bit decomposition of each byte and the
+        // hash of the challenge share their advice columns
+        let advice = [(); 9].map(|_| meta.advice_column());
+        let round_constants = meta.fixed_column();
+        let hash = MiMC5CompressionChip::<Fq, MiMC5CipherVestaChip>::configure(
+            meta,
+            advice[0],
+            advice[1],
+            round_constants,
+        );
+
+        let modulus = meta.fixed_column();
+        let u8_table = meta.lookup_table_column();
+        let field_bytes =
+            FieldBytesChip::configure(meta, advice[..6].try_into().unwrap(), modulus, u8_table);
+        let boolean = BooleanChip::configure(meta, advice[..8].try_into().unwrap(), advice[8]);
+
+        FoldConfig {
+            plonk,
+            hash,
+            field_bytes,
+            boolean,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fq>,
+    ) -> Result<(), Error> {
+        let chip = StandardPlonkChip::construct(config.plonk);
+        let hash = MiMC5CompressionChip::<Fq, MiMC5CipherVestaChip>::construct(config.hash);
+        let field_bytes = FieldBytesChip::construct(config.field_bytes);
+        let boolean = BooleanChip::<Fq, 8>::construct(config.boolean);
+        field_bytes.load(&mut layouter)?;
+
+        let a = Self::load_point(&chip, layouter.namespace(|| "load A"), self.a)?;
+        let b = Self::load_point(&chip, layouter.namespace(|| "load B"), self.b)?;
+        let rows = FoldPublicInputs::ROWS;
+        for (name, num, row) in [
+            ("a_x", &a.x, rows.a_x),
+            ("a_y", &a.y, rows.a_y),
+            ("b_x", &b.x, rows.b_x),
+            ("b_y", &b.y, rows.b_y),
+        ] {
+            chip.expose_public(layouter.namespace(|| name), num.clone(), row)?;
+        }
+
+        // Hash the coordinates in the domain of the challenge
+        let coordinates = [&a.x, &a.y, &b.x, &b.y].map(|number| number.0.clone());
+        let challenge = Number(hash.hash(
+            layouter.namespace(|| "challenge"),
+            Domain::FoldChallenge,
+            &coordinates,
+        )?);
+
+        // Decompose the challenge into bits, most significant bit first
+        let bytes =
+            field_bytes.decompose(layouter.namespace(|| "challenge bytes"), &challenge.0)?;
+        let mut bits = vec![];
+        for byte in bytes.bytes.iter().rev() {
+            let word =
+                boolean.assign_word(layouter.namespace(|| "byte bits"), byte.value().copied())?;
+            chip.assert_equal(
+                layouter.namespace(|| "bits of byte"),
+                Number(word.value),
+                Number(byte.clone()),
+            )?;
+            bits.extend(word.bits.into_iter().map(|bit| Number(bit.0)));
+        }
+
+        // [x]B + [2^SCALAR_BITS]T by double-and-add from the offset point T
+        let mut acc =
+            Self::load_constant_point(&chip, layouter.namespace(|| "offset"), offset_point())?;
+        for (i, bit) in bits.iter().enumerate() {
+            let mut layouter = layouter.namespace(|| format!("bit {}", SCALAR_BITS - 1 - i));
+            acc = Self::double(&chip, layouter.namespace(|| "double"), &acc)?;
+            let sum = Self::add(&chip, layouter.namespace(|| "add"), &acc, &b)?;
+            acc = Self::select(&chip, layouter.namespace(|| "select"), bit, &sum, &acc)?;
+        }
+        let correction = Self::load_constant_point(
+            &chip,
+            layouter.namespace(|| "offset correction"),
+            offset_correction(),
+        )?;
+        let xb = Self::add(&chip, layouter.namespace(|| "[x]B"), &acc, &correction)?;
+
+        let c = Self::add(&chip, layouter.namespace(|| "A + [x]B"), &a, &xb)?;
+        chip.expose_public(
+            layouter.namespace(|| "c_x"),
+            c.x,
+            FoldPublicInputs::ROWS.c_x,
+        )?;
+        chip.expose_public(
+            layouter.namespace(|| "c_y"),
+            c.y,
+            FoldPublicInputs::ROWS.c_y,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::fibonacci::fib_three_column::{FibPublicInputs, MyCircuit};
+    use crate::proving::public_inputs::PublicInputs;
+    use crate::soundness::failures::assert_missing_instance;
+    use halo2_proofs::{
+        arithmetic::Field,
+        dev::{CircuitCost, MockProver},
+        pasta::pallas,
+        plonk::{create_proof, keygen_pk, keygen_vk},
+        poly::commitment::Params,
+        transcript::Blake2bWrite,
+    };
+    use rand_core::OsRng;
+
+    /// Creates a proof for the Fibonacci circuit over `Fp`.
+    fn pallas_proof() -> Vec<u8> {
+        let params: Params<EqAffine> = Params::new(minimal_k(&MyCircuit::<Fp>::default()));
+        let vk = keygen_vk(&params, &MyCircuit::<Fp>::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &MyCircuit::<Fp>::default()).unwrap();
+        let public_inputs = FibPublicInputs {
+            a: Fp::one(),
+            b: Fp::one(),
+            out: Fp::from(55),
+        };
+        let circuit = MyCircuit {
+            a: Value::known(public_inputs.a),
+            b: Value::known(public_inputs.b),
+        };
+        let instances = public_inputs.to_instances();
+        let instances: Vec<&[Fp]> = instances.iter().map(|column| &column[..]).collect();
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            &[circuit],
+            &[&instances],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        transcript.finalize()
+    }
+
+    fn coordinates(point: vesta::Affine) -> (Fq, Fq) {
+        let coordinates = point.coordinates().unwrap();
+        (*coordinates.x(), *coordinates.y())
+    }
+
+    #[test]
+    fn test_fold_pallas_proof() {
+        let proof = pallas_proof();
+        let commitments = read_commitments(&proof, 2).unwrap();
+        let (a, b) = (commitments[0], commitments[1]);
+        let challenge = transcript_challenge(&commitments);
+        let c = fold(a, b, challenge);
+
+        // The offset cancels out natively
+        let offset = vesta::Point::from(offset_point());
+        let scalar = Fp::from(2).pow_vartime([SCALAR_BITS as u64]);
+        assert!(bool::from(
+            (offset * scalar + offset_correction()).is_identity()
+        ));
+
+        let ((a_x, a_y), (b_x, b_y), (c_x, c_y)) = (coordinates(a), coordinates(b), coordinates(c));
+        let mut public_inputs = FoldPublicInputs {
+            a_x,
+            a_y,
+            b_x,
+            b_y,
+            c_x,
+            c_y,
+        };
+        let circuit = FoldCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+
+        // The 256 double-and-add steps fit in 2^13 rows, with a proof of
+        // less than 5 kB
+        let k = minimal_k(&circuit);
+        assert!(k <= 13, "k = {}", k);
+        let proof_size: usize =
+            CircuitCost::<pallas::Point, FoldCircuit>::measure(k as usize, &circuit)
+                .proof_size(1)
+                .into();
+        assert!(
+            proof_size < 5000,
+            "estimated proof size = {} bytes",
+            proof_size
+        );
+
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+        prover.assert_satisfied();
+
+        // The result for another challenge, e.g. one which ignores `B`
+        let (c_x, c_y) = coordinates(fold(a, b, transcript_challenge(&commitments[..1])));
+        public_inputs.c_x = c_x;
+        public_inputs.c_y = c_y;
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+        assert_missing_instance(&prover, 0, FoldPublicInputs::ROWS.c_x);
+        assert_missing_instance(&prover, 0, FoldPublicInputs::ROWS.c_y);
+    }
+}
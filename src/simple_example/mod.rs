@@ -1 +1 @@
-mod simple_example;
\ No newline at end of file
+pub(crate) mod simple_example;
\ No newline at end of file
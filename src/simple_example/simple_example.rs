@@ -7,6 +7,8 @@ use halo2_proofs::{
     poly::Rotation,
 };
 
+use crate::proving::public_inputs::public_inputs;
+
 // ANCHOR: instructions
 trait NumericInstructions<F: FieldExt>: Chip<F> {
     /// Variable representing a number.
@@ -50,7 +52,7 @@ struct FieldChip<F: FieldExt> {
 /// Chip state is stored in a config struct. This is generated by the chip
 /// during configuration, and then stored inside the chip.
 #[derive(Clone, Debug)]
-struct FieldConfig {
+pub(crate) struct FieldConfig {
     /// For this chip, we will use two advice columns to implement our instructions.
     /// These are also the columns through which we communicate with other parts of
     /// the circuit.
@@ -233,6 +235,11 @@ impl<F: FieldExt> NumericInstructions<F> for FieldChip<F> {
 }
 // ANCHOR_END: instructions-impl
 
+public_inputs! {
+    /// The result `c = constant * a^2 * b^2`
+    struct SimplePublicInputs { c }
+}
+
 // ANCHOR: circuit
 /// The full circuit implementation.
 ///
@@ -240,10 +247,10 @@ impl<F: FieldExt> NumericInstructions<F> for FieldChip<F> {
 /// they won't have any value during key generation. During proving, if any of these
 /// were `None` we would get an error.
 #[derive(Default)]
-struct MyCircuit<F: FieldExt> {
-    constant: F,
-    a: Value<F>,
-    b: Value<F>,
+pub(crate) struct MyCircuit<F: FieldExt> {
+    pub constant: F,
+    pub a: Value<F>,
+    pub b: Value<F>,
 }
 
 impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
@@ -299,7 +306,7 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
         let c = field_chip.mul("c*absq", layouter.namespace(|| "constant * absq"), constant, absq)?;
 
         // Expose the result as a public input to the circuit.
-        field_chip.expose_public(layouter.namespace(|| "expose c"), c, 0)
+        field_chip.expose_public(layouter.namespace(|| "expose c"), c, SimplePublicInputs::ROWS.c)
     }
 }
 // ANCHOR_END: circuit
@@ -307,6 +314,9 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::proving::public_inputs::PublicInputs;
+    use crate::soundness::failures::assert_missing_instance;
     use halo2_proofs::{dev::MockProver, pasta::Fp};
 
     #[test]
@@ -314,8 +324,6 @@ mod tests {
         // ANCHOR: test-circuit
         // The number of rows in our circuit cannot exceed 2^k. Since our example
         // circuit is very small, we can pick a very small value here.
-        let k = 4;
-
         // Prepare the private and public inputs to the circuit!
         let constant = Fp::from(7);
         let a = Fp::from(2);
@@ -329,18 +337,19 @@ mod tests {
             b: Value::known(b),
         };
 
-        // Arrange the public input. We expose the multiplication result in row 0
-        // of the instance column, so we position it there in our public inputs.
-        let mut public_inputs = vec![c];
+        // Arrange the public input. `SimplePublicInputs` places the
+        // multiplication result in the row where the circuit exposes it.
+        let mut public_inputs = SimplePublicInputs { c };
 
         // Given the correct public input, our circuit will verify.
-        let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+        let k = minimal_k(&circuit);
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
         assert_eq!(prover.verify(), Ok(()));
 
         // If we try some other public input, the proof will fail!
-        public_inputs[0] += Fp::one();
-        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
-        assert!(prover.verify().is_err());
+        public_inputs.c += Fp::one();
+        let prover = MockProver::run(k, &circuit, public_inputs.to_instances()).unwrap();
+        assert_missing_instance(&prover, 0, SimplePublicInputs::ROWS.c);
         // ANCHOR_END: test-circuit
     }
 
@@ -358,7 +367,7 @@ mod tests {
             b: Value::unknown(),
         };
         halo2_proofs::dev::CircuitLayout::default()
-            .render(4, &circuit, &root)
+            .render(minimal_k(&circuit), &circuit, &root)
             .unwrap();
     }
 }
@@ -0,0 +1,187 @@
+//! Assertions for negative tests. `assert!(prover.verify().is_err())` passes
+//! whatever makes the circuit fail, including a mistake in the test itself;
+//! these check that the `MockProver` rejects the witness for the expected
+//! reason.
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    dev::{metadata, FailureLocation, MockProver, VerifyFailure},
+    plonk::Any,
+};
+
+/// Whether the metadata of a gate or a region, e.g. `Region 2 ('first row')`,
+/// ends with the given name.
+fn named(metadata: impl ToString, name: &str) -> bool {
+    metadata.to_string().ends_with(&format!("('{}')", name))
+}
+
+fn in_region(location: &FailureLocation, region: &str) -> bool {
+    match location {
+        FailureLocation::InRegion { region: r, .. } => named(r, region),
+        FailureLocation::OutsideRegion { .. } => false,
+    }
+}
+
+/// Panics unless the circuit fails with a failure matching `expected`,
+/// listing the failures it has.
+fn assert_fails<F: FieldExt>(
+    prover: &MockProver<F>,
+    expected: &str,
+    matches: impl Fn(&VerifyFailure) -> bool,
+) {
+    match prover.verify() {
+        Ok(()) => panic!("expected {}, but the circuit is satisfied", expected),
+        Err(failures) => {
+            if !failures.iter().any(matches) {
+                let failures: Vec<String> = failures.iter().map(ToString::to_string).collect();
+                panic!(
+                    "expected {}, but the failures are:\n    {}",
+                    expected,
+                    failures.join("\n    ")
+                );
+            }
+        }
+    }
+}
+
+/// Asserts that a constraint of the gate named `gate` is not satisfied in a
+/// region named `region`.
+pub fn assert_constraint_failure<F: FieldExt>(prover: &MockProver<F>, gate: &str, region: &str) {
+    assert_fails(
+        prover,
+        &format!("a constraint of gate '{}' in region '{}'", gate, region),
+        |failure| match failure {
+            VerifyFailure::ConstraintNotSatisfied {
+                constraint,
+                location,
+                ..
+            } => named(constraint, gate) && in_region(location, region),
+            _ => false,
+        },
+    );
+}
+
+/// Asserts that an input of the lookup with index `lookup_index`, in the
+/// order of `ConstraintSystem::lookup`, is not in its table in a region named
+/// `region`.
+pub fn assert_lookup_failure<F: FieldExt>(
+    prover: &MockProver<F>,
+    lookup_index: usize,
+    region: &str,
+) {
+    assert_fails(
+        prover,
+        &format!("lookup {} in region '{}'", lookup_index, region),
+        |failure| match failure {
+            VerifyFailure::Lookup {
+                lookup_index: index,
+                location,
+            } => *index == lookup_index && in_region(location, region),
+            _ => false,
+        },
+    );
+}
+
+/// Asserts that a copy constraint of a cell of `column`, such as
+/// `(Any::Advice, 0)`, is not satisfied.
+pub fn assert_permutation_failure<F: FieldExt>(
+    prover: &MockProver<F>,
+    column: impl Into<metadata::Column>,
+) {
+    let column = column.into();
+    assert_fails(
+        prover,
+        &format!("a copy constraint in {}", column),
+        |failure| matches!(failure, VerifyFailure::Permutation { column: c, .. } if *c == column),
+    );
+}
+
+/// Asserts that the value in `row` of the instance column with index `column`
+/// differs from the cell constrained to equal it, which is also the case when
+/// the instance value is missing, since the `MockProver` pads the instance
+/// columns with zeros.
+pub fn assert_missing_instance<F: FieldExt>(prover: &MockProver<F>, column: usize, row: usize) {
+    let column = metadata::Column::from((Any::Instance, column));
+    assert_fails(
+        prover,
+        &format!("a copy constraint in {} at row {}", column, row),
+        |failure| match failure {
+            VerifyFailure::Permutation {
+                column: c,
+                location: FailureLocation::OutsideRegion { row: r },
+            } => *c == column && *r == row,
+            _ => false,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::rows::minimal_k;
+    use crate::fibonacci::fib_three_column::{FibPublicInputs, MyCircuit};
+    use crate::proving::public_inputs::PublicInputs;
+    use crate::soundness::mutation::tampered_prover;
+    use halo2_proofs::{circuit::Value, pasta::Fp};
+
+    fn fib3() -> (MyCircuit<Fp>, FibPublicInputs<Fp>) {
+        let (a, b) = (Fp::one(), Fp::one());
+        let circuit = MyCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        (
+            circuit,
+            FibPublicInputs {
+                a,
+                b,
+                out: Fp::from(55),
+            },
+        )
+    }
+
+    #[test]
+    fn test_failure_assertions() {
+        let (circuit, mut public_inputs) = fib3();
+        public_inputs.out += Fp::one();
+        let prover =
+            MockProver::run(minimal_k(&circuit), &circuit, public_inputs.to_instances()).unwrap();
+        assert_missing_instance(&prover, 0, 2);
+        assert_permutation_failure(&prover, (Any::Instance, 0));
+
+        let (circuit, public_inputs) = fib3();
+        let prover = tampered_prover(
+            &circuit,
+            public_inputs.to_instances(),
+            "next row",
+            "c",
+            Fp::one(),
+        )
+        .unwrap();
+        assert_constraint_failure(&prover, "add", "next row");
+    }
+
+    #[test]
+    #[should_panic(expected = "but the circuit is satisfied")]
+    fn test_failure_assertion_on_satisfied_circuit() {
+        let (circuit, public_inputs) = fib3();
+        let prover =
+            MockProver::run(minimal_k(&circuit), &circuit, public_inputs.to_instances()).unwrap();
+        assert_constraint_failure(&prover, "add", "next row");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected a constraint of gate 'add' in region 'first row'")]
+    fn test_failure_assertion_in_other_region() {
+        let (circuit, public_inputs) = fib3();
+        let prover = tampered_prover(
+            &circuit,
+            public_inputs.to_instances(),
+            "next row",
+            "c",
+            Fp::one(),
+        )
+        .unwrap();
+        assert_constraint_failure(&prover, "add", "first row");
+    }
+}
@@ -0,0 +1,676 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{layouter::RegionLayouter, Cell, Layouter, Region, Table, Value},
+    dev::{MockProver, VerifyFailure},
+    plonk::{
+        Advice, Assigned, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector,
+    },
+};
+use rand_core::RngCore;
+
+use crate::cost::layout::LayoutColumn;
+use crate::cost::report::Counts;
+use crate::cost::rows::minimal_k;
+
+/// An advice cell assigned by a circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WitnessCell {
+    pub region: String,
+    pub column: LayoutColumn,
+    /// The offset of the cell in its region
+    pub offset: usize,
+    pub name: String,
+}
+
+impl fmt::Display for WitnessCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at offset {} of \"{}\" ({})",
+            self.column, self.offset, self.region, self.name
+        )
+    }
+}
+
+/// Advice cells which can be changed together without making the circuit
+/// fail. They are copies of each other, so none of them is constrained by a
+/// gate, a lookup, a constant or an instance value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnconstrainedCells {
+    pub cells: Vec<WitnessCell>,
+}
+
+impl UnconstrainedCells {
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.cells.iter().map(|cell| cell.name.as_str())
+    }
+}
+
+impl fmt::Display for UnconstrainedCells {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, cell) in self.cells.iter().enumerate() {
+            if i > 0 {
+                write!(f, " = ")?;
+            }
+            write!(f, "{}", cell)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum MutationError {
+    Synthesis(Error),
+    /// The circuit fails with its unmutated witness
+    Unsatisfied(Vec<VerifyFailure>),
+    /// No advice cell has this name in a region with this name
+    UnknownCell {
+        region: String,
+        name: String,
+    },
+}
+
+impl fmt::Display for MutationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MutationError::Synthesis(e) => write!(f, "failed to synthesize the circuit: {}", e),
+            MutationError::Unsatisfied(failures) => {
+                write!(f, "the circuit is not satisfied:")?;
+                for failure in failures {
+                    write!(f, "\n    {}", failure)?;
+                }
+                Ok(())
+            }
+            MutationError::UnknownCell { region, name } => {
+                write!(f, "no advice cell \"{}\" in a region \"{}\"", name, region)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MutationError {}
+
+/// What a run of a `Mutated` circuit records and perturbs. The cells are
+/// identified by their `Debug` output, since `Cell` has no `Eq` in
+/// halo2_proofs 0.2.
+#[derive(Debug, Default)]
+struct Recorder<F> {
+    /// The number of advice cells assigned so far in the run
+    assigned: usize,
+    /// The advice cells to perturb, by their assignment order, and the value
+    /// added to them. The other fields are only recorded without a mutation.
+    mutation: Option<(Vec<usize>, F)>,
+    /// The advice cells assigned with a witness, in assignment order
+    cells: Vec<(String, WitnessCell)>,
+    values: HashMap<String, F>,
+    copies: Vec<(String, String)>,
+    /// The cells which are equal to a constant or an instance value
+    pinned: Vec<String>,
+    /// The cells which are equal to an instance value, with its column and row
+    instances: Vec<(String, usize, usize)>,
+}
+
+impl<F> Recorder<F> {
+    fn recording(&self) -> bool {
+        self.mutation.is_none()
+    }
+}
+
+fn key(cell: Cell) -> String {
+    format!("{:?}", cell)
+}
+
+fn known<V>(value: Value<V>) -> Option<V> {
+    let mut known = None;
+    value.map(|value| known = Some(value));
+    known
+}
+
+/// A circuit whose regions are assigned through a `MutatingLayouter`. It is
+/// only run by `MockProver`, which does not call `without_witnesses`.
+struct Mutated<'a, F: FieldExt, C> {
+    circuit: &'a C,
+    recorder: &'a RefCell<Recorder<F>>,
+}
+
+impl<'a, F: FieldExt, C: Circuit<F>> Circuit<F> for Mutated<'a, F, C> {
+    type Config = C::Config;
+    type FloorPlanner = C::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            circuit: self.circuit,
+            recorder: self.recorder,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.circuit.synthesize(
+            config,
+            MutatingLayouter {
+                layouter,
+                recorder: self.recorder,
+            },
+        )
+    }
+}
+
+/// A `Layouter` which wraps the regions of the circuit in `MutatingRegion`s
+/// and records the cells which are constrained to instance values.
+struct MutatingLayouter<'a, F: FieldExt, L> {
+    layouter: L,
+    recorder: &'a RefCell<Recorder<F>>,
+}
+
+impl<'a, F: FieldExt, L: Layouter<F>> Layouter<F> for MutatingLayouter<'a, F, L> {
+    type Root = Self;
+
+    fn assign_region<A, AR, N, NR>(&mut self, name: N, mut assignment: A) -> Result<AR, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let recorder = self.recorder;
+        let region_name = name().into();
+        self.layouter.assign_region(name, |region| {
+            let mut region = MutatingRegion {
+                region,
+                name: &region_name,
+                recorder,
+            };
+            assignment(Region::from(&mut region as &mut dyn RegionLayouter<F>))
+        })
+    }
+
+    fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
+    where
+        A: FnMut(Table<'_, F>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.layouter.assign_table(name, assignment)
+    }
+
+    fn constrain_instance(
+        &mut self,
+        cell: Cell,
+        column: Column<Instance>,
+        row: usize,
+    ) -> Result<(), Error> {
+        let mut recorder = self.recorder.borrow_mut();
+        if recorder.recording() {
+            recorder.pinned.push(key(cell));
+            let column = LayoutColumn::of(column.into()).index;
+            recorder.instances.push((key(cell), column, row));
+        }
+        self.layouter.constrain_instance(cell, column, row)
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.layouter.get_root().push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.layouter.get_root().pop_namespace(gadget_name)
+    }
+}
+
+/// A `RegionLayouter` which numbers the advice cells assigned with a witness,
+/// and perturbs the values of the cells of the mutation. The values are only
+/// computed in the assignment pass of the floor planner, so the cells are
+/// numbered in the same order whichever floor planner is used.
+#[derive(Debug)]
+struct MutatingRegion<'r, 'a, F: FieldExt> {
+    region: Region<'r, F>,
+    name: &'a str,
+    recorder: &'a RefCell<Recorder<F>>,
+}
+
+impl<'r, 'a, F: FieldExt> MutatingRegion<'r, 'a, F> {
+    fn pin(&self, cell: Cell, value: Option<F>) {
+        let mut recorder = self.recorder.borrow_mut();
+        if recorder.recording() {
+            recorder.pinned.push(key(cell));
+            recorder
+                .values
+                .extend(value.map(|value| (key(cell), value)));
+        }
+    }
+}
+
+impl<'r, 'a, F: FieldExt> RegionLayouter<F> for MutatingRegion<'r, 'a, F> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        _: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        selector.enable(&mut self.region, offset)
+    }
+
+    fn assign_advice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        let recorder = self.recorder;
+        let mut value = None;
+        let cell = self
+            .region
+            .assign_advice(annotation, column, offset, || {
+                let mut recorder = recorder.borrow_mut();
+                let index = recorder.assigned;
+                recorder.assigned += 1;
+                let assigned = match &recorder.mutation {
+                    Some((cells, delta)) if cells.contains(&index) => to().map(|v| v + *delta),
+                    _ => to(),
+                };
+                value = Some(known(assigned.map(|v| v.evaluate())));
+                assigned
+            })?
+            .cell();
+
+        let mut recorder = recorder.borrow_mut();
+        if let (Some(value), true) = (value, recorder.recording()) {
+            let witness = WitnessCell {
+                region: self.name.to_string(),
+                column: LayoutColumn::of(column.into()),
+                offset,
+                name: annotation(),
+            };
+            recorder.cells.push((key(cell), witness));
+            recorder
+                .values
+                .extend(value.map(|value| (key(cell), value)));
+        }
+        Ok(cell)
+    }
+
+    fn assign_advice_from_constant<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    ) -> Result<Cell, Error> {
+        let cell = self
+            .region
+            .assign_advice_from_constant(annotation, column, offset, constant)?
+            .cell();
+        self.pin(cell, Some(constant.evaluate()));
+        Ok(cell)
+    }
+
+    fn assign_advice_from_instance<'v>(
+        &mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        instance: Column<Instance>,
+        row: usize,
+        advice: Column<Advice>,
+        offset: usize,
+    ) -> Result<(Cell, Value<F>), Error> {
+        let assigned = self
+            .region
+            .assign_advice_from_instance(annotation, instance, row, advice, offset)?;
+        let value = assigned.value().copied();
+        self.pin(assigned.cell(), known(value));
+        Ok((assigned.cell(), value))
+    }
+
+    fn assign_fixed<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        let cell = self
+            .region
+            .assign_fixed(annotation, column, offset, to)?
+            .cell();
+        self.pin(cell, None);
+        Ok(cell)
+    }
+
+    fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
+        self.pin(cell, None);
+        self.region.constrain_constant(cell, constant)
+    }
+
+    fn constrain_equal(&mut self, left: Cell, right: Cell) -> Result<(), Error> {
+        let mut recorder = self.recorder.borrow_mut();
+        if recorder.recording() {
+            recorder.copies.push((key(left), key(right)));
+        }
+        drop(recorder);
+        self.region.constrain_equal(left, right)
+    }
+}
+
+/// A circuit with a witness which satisfies it, and its advice cells grouped
+/// by copies. The cells of a group are mutated together, since a copy
+/// constraint alone would catch the mutation of one of them.
+struct Harness<'a, F: FieldExt, C> {
+    circuit: &'a C,
+    instances: Vec<Vec<F>>,
+    k: u32,
+    cells: Vec<WitnessCell>,
+    /// The groups of copied cells which are not pinned to a constant or an
+    /// instance value, as indices into `cells`
+    groups: Vec<Vec<usize>>,
+}
+
+impl<'a, F: FieldExt, C: Circuit<F>> Harness<'a, F, C> {
+    fn new(circuit: &'a C, instances: Vec<Vec<F>>) -> Result<Self, MutationError> {
+        let k = minimal_k(circuit);
+        let recorder = run(k, circuit, &instances, None)?;
+        recorder.verify().map_err(MutationError::Unsatisfied)?;
+        let recorder = recorder.recorder.into_inner();
+
+        // Groups the cells with a union-find over the copies, in which the
+        // pinned cells and the fixed cells share the last node
+        let indices: HashMap<&str, usize> = recorder
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(i, (key, _))| (key.as_str(), i))
+            .collect();
+        let pinned = recorder.cells.len();
+        let node = |key: &str| indices.get(key).copied().unwrap_or(pinned);
+        let mut parents: Vec<usize> = (0..=pinned).collect();
+        fn find(parents: &mut [usize], mut i: usize) -> usize {
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
+        }
+        let unions = recorder
+            .copies
+            .iter()
+            .map(|(left, right)| (node(left), node(right)))
+            .chain(recorder.pinned.iter().map(|key| (node(key), pinned)));
+        for (left, right) in unions {
+            let (left, right) = (find(&mut parents, left), find(&mut parents, right));
+            // Keeps the pinned node as the root of its group
+            parents[left.min(right)] = left.max(right);
+        }
+
+        let mut groups: Vec<Vec<usize>> = vec![];
+        let mut roots = HashMap::new();
+        for (i, (key, _)) in recorder.cells.iter().enumerate() {
+            // Skips the assignments which are overwritten by a later one
+            let root = find(&mut parents, i);
+            if root == pinned || indices[key.as_str()] != i {
+                continue;
+            }
+            let group = *roots.entry(root).or_insert_with(|| {
+                groups.push(vec![]);
+                groups.len() - 1
+            });
+            groups[group].push(i);
+        }
+
+        Ok(Self {
+            circuit,
+            instances,
+            k,
+            cells: recorder.cells.into_iter().map(|(_, cell)| cell).collect(),
+            groups,
+        })
+    }
+
+    /// Adds `delta` to the cells of a group, and returns them if the circuit
+    /// is still satisfied.
+    fn mutate(
+        &self,
+        group: &[usize],
+        delta: F,
+    ) -> Result<Option<UnconstrainedCells>, MutationError> {
+        let prover = run(
+            self.k,
+            self.circuit,
+            &self.instances,
+            Some((group.to_vec(), delta)),
+        )?;
+        Ok(prover.verify().is_ok().then(|| UnconstrainedCells {
+            cells: group.iter().map(|&i| self.cells[i].clone()).collect(),
+        }))
+    }
+}
+
+/// The result of a run of a `Mutated` circuit.
+struct Run<F: FieldExt> {
+    prover: MockProver<F>,
+    recorder: RefCell<Recorder<F>>,
+}
+
+impl<F: FieldExt> Run<F> {
+    fn verify(&self) -> Result<(), Vec<VerifyFailure>> {
+        self.prover.verify()
+    }
+}
+
+fn run<F: FieldExt, C: Circuit<F>>(
+    k: u32,
+    circuit: &C,
+    instances: &[Vec<F>],
+    mutation: Option<(Vec<usize>, F)>,
+) -> Result<Run<F>, MutationError> {
+    let recorder = RefCell::new(Recorder {
+        mutation,
+        ..Default::default()
+    });
+    let mutated = Mutated {
+        circuit,
+        recorder: &recorder,
+    };
+    let prover =
+        MockProver::run(k, &mutated, instances.to_vec()).map_err(MutationError::Synthesis)?;
+    Ok(Run { prover, recorder })
+}
+
+/// Mutates the advice cells of a circuit one at a time, with the cells they
+/// are copied to, and returns those whose mutation still satisfies the
+/// constraints of the circuit with the same `instances`. Each value is
+/// incremented by one, and the other cells keep their values.
+pub fn unconstrained_cells<F: FieldExt, C: Circuit<F>>(
+    circuit: &C,
+    instances: Vec<Vec<F>>,
+) -> Result<Vec<UnconstrainedCells>, MutationError> {
+    let harness = Harness::new(circuit, instances)?;
+    let mut unconstrained = vec![];
+    for group in &harness.groups {
+        unconstrained.extend(harness.mutate(group, F::one())?);
+    }
+    Ok(unconstrained)
+}
+
+/// Like `unconstrained_cells`, but only mutates `samples` advice cells drawn
+/// at random, by random values, for circuits with too many cells to mutate
+/// each of them.
+pub fn sample_unconstrained_cells<F: FieldExt, C: Circuit<F>>(
+    circuit: &C,
+    instances: Vec<Vec<F>>,
+    samples: usize,
+    mut rng: impl RngCore,
+) -> Result<Vec<UnconstrainedCells>, MutationError> {
+    let harness = Harness::new(circuit, instances)?;
+    let mut groups: Vec<&Vec<usize>> = harness.groups.iter().collect();
+    let mut unconstrained = vec![];
+    for i in 0..samples.min(groups.len()) {
+        let j = i + (rng.next_u64() % (groups.len() - i) as u64) as usize;
+        groups.swap(i, j);
+        unconstrained.extend(harness.mutate(groups[i], F::random(&mut rng))?);
+    }
+    Ok(unconstrained)
+}
+
+/// Returns the instance values which the witness of a circuit copies to its
+/// instance columns, for circuits whose witness does not depend on them.
+pub fn witness_instances<F: FieldExt, C: Circuit<F>>(
+    circuit: &C,
+) -> Result<Vec<Vec<F>>, MutationError> {
+    let mut cs = ConstraintSystem::default();
+    C::configure(&mut cs);
+    let mut instances = vec![vec![]; Counts::of(&cs).instance_columns];
+
+    let recorder = run(minimal_k(circuit), circuit, &instances, None)?
+        .recorder
+        .into_inner();
+    for (cell, column, row) in &recorder.instances {
+        let column: &mut Vec<F> = &mut instances[*column];
+        if column.len() <= *row {
+            column.resize(row + 1, F::zero());
+        }
+        column[*row] = recorder.values.get(cell).copied().unwrap_or_else(F::zero);
+    }
+    Ok(instances)
+}
+
+/// Runs the `MockProver` on a circuit with `delta` added to one of its advice
+/// cells, the first one assigned with the annotation `name` in a region named
+/// `region`. The cells it is copied to keep their values. This gives the
+/// negative tests of circuits which compute their own witness a wrong one.
+pub fn tampered_prover<F: FieldExt, C: Circuit<F>>(
+    circuit: &C,
+    instances: Vec<Vec<F>>,
+    region: &str,
+    name: &str,
+    delta: F,
+) -> Result<MockProver<F>, MutationError> {
+    let k = minimal_k(circuit);
+    let recorder = run(k, circuit, &instances, None)?.recorder.into_inner();
+    let index = recorder
+        .cells
+        .iter()
+        .position(|(_, cell)| cell.region == region && cell.name == name)
+        .ok_or_else(|| MutationError::UnknownCell {
+            region: region.to_string(),
+            name: name.to_string(),
+        })?;
+    Ok(run(k, circuit, &instances, Some((vec![index], delta)))?.prover)
+}
+
+/// Panics if a mutation of the advice cells of a circuit satisfies its
+/// constraints, listing the unconstrained cells.
+pub fn assert_constrained<F: FieldExt, C: Circuit<F>>(circuit: &C, instances: Vec<Vec<F>>) {
+    let unconstrained = unconstrained_cells(circuit, instances).unwrap_or_else(|e| panic!("{}", e));
+    if !unconstrained.is_empty() {
+        let cells: Vec<String> = unconstrained.iter().map(ToString::to_string).collect();
+        panic!("unconstrained cells:\n    {}", cells.join("\n    "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::examples::{visit_examples, ExampleVisitor};
+    use halo2_proofs::arithmetic::CurveAffine;
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::SeedableRng;
+
+    /// The examples with more advice cells than this are only sampled.
+    const MAX_CELLS: usize = 1000;
+
+    /// The number of cells mutated in each sampled example. The cells are
+    /// drawn with a fixed seed, so that a failure can be reproduced.
+    const SAMPLES: usize = 16;
+
+    /// Mutates the cells of each example, with the instance values copied from
+    /// its witness, or `samples` of them in the examples with more than
+    /// `MAX_CELLS` cells.
+    fn example_mutations(samples: Option<usize>) -> Vec<(String, Vec<UnconstrainedCells>)> {
+        struct Mutations {
+            samples: Option<usize>,
+            unconstrained: Vec<(String, Vec<UnconstrainedCells>)>,
+        }
+
+        impl ExampleVisitor for Mutations {
+            fn visit<C, ConcreteCircuit>(&mut self, name: &str, circuit: &ConcreteCircuit)
+            where
+                C: CurveAffine,
+                ConcreteCircuit: Circuit<C::Scalar>,
+            {
+                let instances = witness_instances(circuit).unwrap();
+                let harness = Harness::new(circuit, instances.clone()).unwrap();
+                let unconstrained = match self.samples {
+                    Some(samples) if harness.cells.len() > MAX_CELLS => {
+                        let rng = ChaCha20Rng::seed_from_u64(0);
+                        sample_unconstrained_cells(circuit, instances, samples, rng)
+                    }
+                    _ => unconstrained_cells(circuit, instances),
+                };
+                self.unconstrained
+                    .push((name.to_string(), unconstrained.unwrap()));
+            }
+        }
+
+        let mut mutations = Mutations {
+            samples,
+            unconstrained: vec![],
+        };
+        visit_examples(&mut mutations);
+        mutations.unconstrained
+    }
+
+    fn check_example_mutations(mutations: Vec<(String, Vec<UnconstrainedCells>)>) {
+        assert_eq!(mutations.len(), 15);
+
+        for (name, unconstrained) in mutations {
+            let names: Vec<&str> = unconstrained
+                .iter()
+                .flat_map(UnconstrainedCells::names)
+                .collect();
+            match name.as_str() {
+                // The inverse of a - b is free when a = b
+                "conditional_calc" => assert_eq!(names, ["value inv"]),
+                // The unused wires of the standard PLONK gates are free
+                "recursion::cycle" => assert!(names.iter().all(|&name| name == "wire")),
+                // The auxiliary witnesses of complete addition are free in the
+                // cases which do not use them, and so is the unused wire of
+                // the inversion of pk.x
+                "schnorr" => assert!(
+                    names.iter().all(|name| {
+                        ["alpha", "beta", "gamma", "delta", "lambda", "wire"].contains(name)
+                    }),
+                    "{:?}",
+                    names
+                ),
+                _ => assert!(names.is_empty(), "{}: {:?}", name, names),
+            }
+        }
+    }
+
+    #[test]
+    fn test_example_mutations() {
+        check_example_mutations(example_mutations(Some(SAMPLES)));
+    }
+
+    /// Mutates every cell of the examples, which takes hours for the larger
+    /// ones.
+    #[test]
+    #[ignore]
+    fn test_example_mutations_exhaustive() {
+        check_example_mutations(example_mutations(None));
+    }
+}
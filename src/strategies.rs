@@ -0,0 +1,18 @@
+//! Proptest strategies shared by the property tests of the chips.
+
+use halo2_proofs::arithmetic::FieldExt;
+use proptest::prelude::*;
+
+/// Draws elements of `F` from the whole field, together with the edge cases
+/// 0, 1 and -1.
+pub(crate) fn field<F: FieldExt>() -> impl Strategy<Value = F> {
+    prop_oneof![
+        1 => Just(F::zero()),
+        1 => Just(F::one()),
+        1 => Just(-F::one()),
+        // A 256-bit integer reduced modulo p
+        7 => any::<[u128; 2]>().prop_map(|[lo, hi]| {
+            F::from_u128(hi) * F::from_u128(1 << 64).square() + F::from_u128(lo)
+        }),
+    ]
+}